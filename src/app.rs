@@ -0,0 +1,347 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API for application developers to define their own birth and death
+//! predicates, see the Zcash, restricted Zcash and Tornado Cash examples.
+//!
+//! An application is a pair of predicate circuits over `InnerScalarField`
+//! that must satisfy the following contract:
+//! - the only public input of each circuit is the local data commitment, and
+//!   each circuit constrains it with [`local_data_commitment_circuit`] over
+//!   the variables of the entire input notes, output records and memo;
+//! - the first input (fee) and first output (fee change) are handled by the
+//!   UTXO circuit, application logic only applies to the remaining ones;
+//! - the birth and death circuits have the same number of gates: the birth
+//!   circuit is padded with `BirthPredicateCircuit::PAD_GATES` gates, and the
+//!   death circuit is padded to the size of the preprocessed birth circuit;
+//! - circuits are finalized with `MergeableCircuitType::TypeA` for birth
+//!   predicates and `MergeableCircuitType::TypeB` for death predicates.
+//!
+//! The policy identifiers of a preprocessed predicate pair are then used as
+//! `pid_birth` and `pid_death` of the records of the application.
+//! Transactions spending and creating records of the application are built
+//! with `transaction::TransactionBuilder`.
+
+pub use crate::{
+    circuit::{
+        local_data::local_data_commitment_circuit,
+        structs::{NoteInputVar, PayloadVariable, RecordOpeningVar},
+    },
+    proofs::predicates::{Predicate, PredicateCircuit},
+    types::{InnerScalarField, InnerUniversalParam, OuterUniversalParam},
+};
+use crate::{
+    errors::{DPCApiError, TxnParameterError},
+    keys::ProofGenerationKey,
+    params::DPCParams,
+    proofs::transaction::{DPCProvingKey, DPCVerifyingKey},
+    structs::{NoteInput, PolicyIdentifier, RecordOpening},
+};
+use ark_std::{vec, vec::Vec, Zero};
+use jf_plonk::circuit::{Circuit, PlonkCircuit};
+
+/// A birth predicate that is shared among all example applications: all
+/// non-fee, non-dummy records have the same asset code, and the sum of input
+/// values equals the sum of output values.
+pub trait BirthPredicateCircuit
+where
+    Self: Sized + From<PredicateCircuit>,
+{
+    /// Number of dummy gates padded to the birth circuit.
+    ///
+    /// Our code requires that #gates in a birth circuit to be greater
+    /// than that of a death circuit. If birth circuit has a smaller size,
+    /// we need to pad the birth circuit to make it larger.
+    const PAD_GATES: usize;
+
+    /// This internal logic will prove that
+    /// 1. all the inputs are correctly w.r.t. commitment
+    /// 2. all asset_ids of non-dummy records match, dummy records have no
+    ///    value
+    /// 3. sum inputs = sum outputs
+    fn gen_birth_circuit_core(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        let mut birth_circuit = PlonkCircuit::new_turbo_plonk();
+
+        // build all the variables
+        let comm_local_data_var = birth_circuit.create_public_variable(comm_local_data)?;
+        let blinding_local_data_var = birth_circuit.create_variable(blinding_local_data)?;
+
+        let entire_input_notes_vars = entire_input_notes
+            .iter()
+            .map(|x| NoteInputVar::new(&mut birth_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entire_outputs_vars = entire_output_records
+            .iter()
+            .map(|x| RecordOpeningVar::new(&mut birth_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let memo_vars = memo
+            .iter()
+            .map(|x| birth_circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 1. argue that the local data is correct w.r.t. to the commitment of local
+        // data
+        local_data_commitment_circuit(
+            &mut birth_circuit,
+            &entire_input_notes_vars,
+            &entire_outputs_vars,
+            &memo_vars,
+            &blinding_local_data_var,
+            &comm_local_data_var,
+        )?;
+
+        // 2. all asset_ids of non-dummy records match, and dummy records (e.g.
+        // padding) carry no value; asset_id is encoded in the first byte of
+        // payload and the value in the second one
+        let asset_id = entire_input_notes
+            .iter()
+            .skip(1)
+            .map(|note| &note.ro.payload)
+            .chain(entire_output_records.iter().skip(1).map(|ro| &ro.payload))
+            .find(|payload| !payload.is_dummy)
+            .map_or_else(InnerScalarField::zero, |payload| payload.data[0]);
+        let asset_id_var = birth_circuit.create_variable(asset_id)?;
+        let zero_var = birth_circuit.zero();
+        for payload in entire_input_notes_vars
+            .iter()
+            .skip(1)
+            .map(|note| &note.record_opening_var.payload)
+            .chain(
+                entire_outputs_vars
+                    .iter()
+                    .skip(1)
+                    .map(|record| &record.payload),
+            )
+        {
+            let is_not_dummy = birth_circuit.check_is_zero(payload.is_dummy)?;
+            let is_dummy = birth_circuit.logic_neg(is_not_dummy)?;
+            let asset_id_diff = birth_circuit.sub(payload.data[0], asset_id_var)?;
+            birth_circuit.mul_gate(asset_id_diff, is_not_dummy, zero_var)?;
+            birth_circuit.mul_gate(payload.data[1], is_dummy, zero_var)?;
+        }
+
+        // 3. sum inputs = sum outputs
+        let mut sum_input_var = entire_input_notes_vars[1].record_opening_var.payload.data[1];
+        for note in entire_input_notes_vars.iter().skip(2) {
+            sum_input_var =
+                birth_circuit.add(sum_input_var, note.record_opening_var.payload.data[1])?;
+        }
+        let mut sum_output_var = entire_outputs_vars[1].payload.data[1];
+        for record in entire_outputs_vars.iter().skip(2) {
+            sum_output_var = birth_circuit.add(sum_output_var, record.payload.data[1])?;
+        }
+        birth_circuit.equal_gate(sum_input_var, sum_output_var)?;
+
+        // pad the birth circuit with dummy gates so that it will always be greater
+        // than the supported death ones
+        birth_circuit.pad_gate(Self::PAD_GATES);
+
+        Ok(Self::from(PredicateCircuit(birth_circuit)))
+    }
+
+    /// build a preprocessed birth circuit for the record layout of `params`
+    fn preprocessed_birth_circuit(
+        entire_input_size: usize,
+        params: &DPCParams,
+    ) -> Result<Self, DPCApiError> {
+        let proof_gen_key = ProofGenerationKey::default();
+
+        let dummy_blinding_local_data = InnerScalarField::default();
+        let dummy_comm_local_data = InnerScalarField::default();
+        let dummy_input_notes =
+            vec![NoteInput::dummy_with_params(&proof_gen_key, params); entire_input_size];
+        let dummy_output_records =
+            vec![RecordOpening::dummy_with_params(params); entire_input_size];
+        let dummy_memo = vec![InnerScalarField::zero(); params.memo_len];
+
+        Self::gen_birth_circuit_core(
+            &dummy_input_notes,
+            &dummy_output_records,
+            &dummy_memo,
+            dummy_blinding_local_data,
+            dummy_comm_local_data,
+        )
+    }
+
+    /// Build a birth circuit with real data.
+    /// Inputs:
+    /// - entire input notes
+    /// - entire output records
+    /// - local data blinding factor
+    /// - local data commitment
+    /// - allowed policy identifiers
+    ///
+    /// Note that native token fee related proofs are handled via UTXO circuits.
+    ///
+    /// For both input note and output records, the data payload is
+    /// an array of fixed length `params.payload_data_len()`,
+    /// formatted as `[ asset_id | asset_value | 0 | ... 0 ]`
+    fn gen_birth_circuit(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        if entire_input_notes.len() != entire_output_records.len() {
+            return Err(TxnParameterError::InputsOutputsMismatch {
+                inputs: entire_input_notes.len(),
+                outputs: entire_output_records.len(),
+            }
+            .into());
+        }
+        Self::gen_birth_circuit_core(
+            entire_input_notes,
+            entire_output_records,
+            memo,
+            blinding_local_data,
+            comm_local_data,
+        )
+    }
+}
+
+/// A death predicate that may vary among applications.
+pub trait DeathPredicateCircuit
+where
+    Self: Sized + From<PredicateCircuit> + BirthPredicateCircuit,
+{
+    /// Internal function that generates the actual circuit for the
+    /// customized statements.
+    fn gen_death_circuit_core(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError>;
+
+    /// build a dummy death circuit for the record layout of `params`
+    fn preprocessed_death_circuit(
+        entire_input_size: usize,
+        params: &DPCParams,
+    ) -> Result<Self, DPCApiError> {
+        let proof_gen_key = ProofGenerationKey::default();
+
+        let dummy_blinding_local_data = InnerScalarField::default();
+        let dummy_comm_local_data = InnerScalarField::default();
+        let dummy_input_notes =
+            vec![NoteInput::dummy_with_params(&proof_gen_key, params); entire_input_size];
+        let dummy_output_records =
+            vec![RecordOpening::dummy_with_params(params); entire_input_size];
+        let dummy_memo = vec![InnerScalarField::zero(); params.memo_len];
+
+        Self::gen_death_circuit_core(
+            &dummy_input_notes,
+            &dummy_output_records,
+            &dummy_memo,
+            dummy_blinding_local_data,
+            dummy_comm_local_data,
+        )
+    }
+
+    /// Build a birth circuit with real data.
+    /// Inputs:
+    /// - entire input notes
+    /// - entire output records
+    /// - local data blinding factor
+    /// - local data commitment
+    ///
+    /// Note that native token fee related proofs are handled via UTXO circuits.
+    ///
+    /// For both input note and output records, the data payload is
+    /// an array of fixed length `params.payload_data_len()`,
+    /// formatted as `[ asset_id | asset_value | 0 | ... 0 ]`
+    fn gen_death_circuit(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        Self::gen_death_circuit_core(
+            entire_input_notes,
+            entire_output_records,
+            memo,
+            blinding_local_data,
+            comm_local_data,
+        )
+    }
+}
+
+/// Setup and finalization of the predicates of an application.
+pub trait PredicateOps<'a>
+where
+    Self: Sized + From<Predicate<'a>>,
+{
+    /// Setup the circuit and related parameters
+    ///
+    /// Inputs:
+    /// - rng
+    /// - inner SRS
+    /// - outer SRS
+    /// - total number of inputs (including fee record)
+    /// - record layout and accumulator parameters
+    ///
+    /// Outputs:
+    /// - DPC proving key
+    /// - DPC verification key
+    /// - Birth predicate (with dummy local commitment)
+    /// - Birth predicate PIDs
+    /// - Death predicate (with dummy local commitment)
+    /// - Death predicate PIDs
+    ///
+    /// The proving key does not borrow from the SRS and can be shared
+    /// across threads, whereas the predicates borrow from the inner SRS and
+    /// stay on the thread that proves the transaction.
+    fn preprocess(
+        inner_srs: &'a InnerUniversalParam,
+        outer_srs: &'a OuterUniversalParam,
+        entire_input_size: usize,
+        params: &DPCParams,
+    ) -> Result<
+        (
+            DPCProvingKey,
+            DPCVerifyingKey,
+            Self,
+            PolicyIdentifier,
+            Self,
+            PolicyIdentifier,
+        ),
+        DPCApiError,
+    >;
+
+    /// Finalize a predicate circuit.
+    ///
+    /// This function will need to be called to prepare
+    /// the circuit for proof generation.
+    /// When a predicate circuit was initialized, it does not have the
+    /// correct commitment to the local data (and thus cannot generate)
+    /// a correct proof.
+    fn finalize_for_proving(
+        &mut self,
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+        is_birth_predicate: bool,
+    ) -> Result<(), DPCApiError>;
+
+    /// The underlying predicate, as listed in the input death predicates and
+    /// output birth predicates of a transaction.
+    fn as_predicate(&self) -> &Predicate<'a>;
+}
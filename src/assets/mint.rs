@@ -0,0 +1,653 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Predicates of the records of defined assets.
+//!
+//! The same pair of predicates serves all assets defined with an
+//! `AssetDefinition`, so that records of all assets share the same policy
+//! identifiers. Records are formatted as
+//! `[ asset_code | value | description_digest | 0 | ... | 0 ]`.
+//!
+//! - the birth predicate checks:
+//!     - the values are correctly committed via the `common_local_data`
+//!     - all non-dummy input/output records share a same asset code,
+//!       description digest and pair of policy identifiers, and dummy records
+//!       carry no value
+//!     - all values fit in `VALUE_BIT_LEN` bits, so that sums do not wrap
+//!       around the field modulus
+//!     - either the sum of input values matches the sum of output values, or
+//!       the asset code is derived from the address of the fee input and the
+//!       description digest, i.e. the supply only changes in transactions
+//!       whose fee is paid by the issuer
+//!
+//! - the death predicate checks:
+//!     - the values are correctly committed via the `common_local_data`
+//!
+//! The UTXO circuit proves that the fee input is a real record owned by the
+//! prover, so only the owner of the issuer address can change the supply of
+//! an asset when creating records with the birth predicate. The policy
+//! identifiers are derived from the verifying keys, so the circuits cannot
+//! commit to them. Instead, requiring inputs to have the policy identifiers
+//! of the outputs prevents records born with another predicate, e.g. with a
+//! forged value, from being spent into asset records. Records can still be
+//! spent into records of other applications, so holders may burn their own
+//! units.
+
+use crate::{
+    app::{
+        local_data_commitment_circuit, BirthPredicateCircuit, DeathPredicateCircuit, NoteInputVar,
+        PredicateOps, RecordOpeningVar,
+    },
+    errors::DPCApiError,
+    params::DPCParams,
+    predicates::PredicateTrait,
+    proofs::{
+        predicates::{Predicate, PredicateCircuit},
+        transaction::{DPCProvingKey, DPCVerifyingKey},
+    },
+    structs::{NoteInput, PolicyIdentifier, RecordOpening},
+    types::{InnerScalarField, InnerUniversalParam, OuterUniversalParam},
+};
+use ark_std::{format, vec, vec::Vec, Zero};
+use jf_plonk::circuit::{customized::rescue::RescueGadget, Arithmetization, Circuit, PlonkCircuit};
+
+/// Number of bits of the values of asset records
+pub const VALUE_BIT_LEN: usize = 64;
+
+// Asset records hold the asset code, the value and the description digest.
+const MIN_PAYLOAD_DATA_LEN: usize = 3;
+
+fn check_payload_data_len(payload_data_len: usize) -> Result<(), DPCApiError> {
+    if payload_data_len < MIN_PAYLOAD_DATA_LEN {
+        return Err(DPCApiError::InvalidParameter(format!(
+            "Payload data length {} is too short for asset records, at least {} are needed",
+            payload_data_len, MIN_PAYLOAD_DATA_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// A simple wrapper of predicate circuit
+pub struct MintPredicateCircuit(pub PredicateCircuit);
+
+impl From<PredicateCircuit> for MintPredicateCircuit {
+    fn from(circuit: PredicateCircuit) -> Self {
+        Self(circuit)
+    }
+}
+
+/// A simple wrapper of predicate
+pub struct MintPredicate<'a>(pub Predicate<'a>);
+
+impl<'a> From<Predicate<'a>> for MintPredicate<'a> {
+    fn from(predicate: Predicate<'a>) -> Self {
+        Self(predicate)
+    }
+}
+
+impl BirthPredicateCircuit for MintPredicateCircuit {
+    // The death circuit is padded to the size of the birth circuit.
+    const PAD_GATES: usize = 0;
+
+    fn gen_birth_circuit_core(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        for payload in entire_input_notes
+            .iter()
+            .map(|note| &note.ro.payload)
+            .chain(entire_output_records.iter().map(|ro| &ro.payload))
+        {
+            check_payload_data_len(payload.data.len())?;
+        }
+        let mut birth_circuit = PlonkCircuit::new_turbo_plonk();
+
+        // build all the variables
+        let comm_local_data_var = birth_circuit.create_public_variable(comm_local_data)?;
+        let blinding_local_data_var = birth_circuit.create_variable(blinding_local_data)?;
+
+        let entire_input_notes_vars = entire_input_notes
+            .iter()
+            .map(|x| NoteInputVar::new(&mut birth_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entire_outputs_vars = entire_output_records
+            .iter()
+            .map(|x| RecordOpeningVar::new(&mut birth_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let memo_vars = memo
+            .iter()
+            .map(|x| birth_circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 1. argue that the local data is correct w.r.t. to the commitment of local
+        // data
+        local_data_commitment_circuit(
+            &mut birth_circuit,
+            &entire_input_notes_vars,
+            &entire_outputs_vars,
+            &memo_vars,
+            &blinding_local_data_var,
+            &comm_local_data_var,
+        )?;
+
+        // 2. asset codes, description digests and policy identifiers of non-dummy
+        // records match, dummy records carry no value, and values are in range
+        let first_non_dummy = entire_input_notes
+            .iter()
+            .skip(1)
+            .map(|note| &note.ro)
+            .chain(entire_output_records.iter().skip(1))
+            .find(|ro| !ro.payload.is_dummy);
+        let shared_values = match first_non_dummy {
+            Some(ro) => [
+                ro.payload.data[0],
+                ro.payload.data[2],
+                ro.pid_birth,
+                ro.pid_death,
+            ],
+            None => [InnerScalarField::zero(); 4],
+        };
+        let shared_vars = shared_values
+            .iter()
+            .map(|x| birth_circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (asset_code_var, description_digest_var) = (shared_vars[0], shared_vars[1]);
+        let zero_var = birth_circuit.zero();
+        for record in entire_input_notes_vars
+            .iter()
+            .skip(1)
+            .map(|note| &note.record_opening_var)
+            .chain(entire_outputs_vars.iter().skip(1))
+        {
+            let payload = &record.payload;
+            let is_not_dummy = birth_circuit.check_is_zero(payload.is_dummy)?;
+            let is_dummy = birth_circuit.logic_neg(is_not_dummy)?;
+            let record_vars = [
+                payload.data[0],
+                payload.data[2],
+                record.pid_birth,
+                record.pid_death,
+            ];
+            for (&record_var, &shared_var) in record_vars.iter().zip(shared_vars.iter()) {
+                let diff = birth_circuit.sub(record_var, shared_var)?;
+                birth_circuit.mul_gate(diff, is_not_dummy, zero_var)?;
+            }
+            birth_circuit.mul_gate(payload.data[1], is_dummy, zero_var)?;
+            birth_circuit.range_gate(payload.data[1], VALUE_BIT_LEN)?;
+        }
+
+        // 3. sum inputs = sum outputs, or the fee input is owned by the issuer
+        let mut sum_input_var = entire_input_notes_vars[1].record_opening_var.payload.data[1];
+        for note in entire_input_notes_vars.iter().skip(2) {
+            sum_input_var =
+                birth_circuit.add(sum_input_var, note.record_opening_var.payload.data[1])?;
+        }
+        let mut sum_output_var = entire_outputs_vars[1].payload.data[1];
+        for record in entire_outputs_vars.iter().skip(2) {
+            sum_output_var = birth_circuit.add(sum_output_var, record.payload.data[1])?;
+        }
+        let is_balanced = birth_circuit.check_equal(sum_input_var, sum_output_var)?;
+
+        // same hash as `assets::derive_asset_code`
+        let fee_input_addr = &entire_input_notes_vars[0].record_opening_var.addr;
+        let issued_asset_code = birth_circuit.rescue_sponge_with_padding(
+            &[
+                fee_input_addr.0,
+                fee_input_addr.1.get_x(),
+                fee_input_addr.1.get_y(),
+                description_digest_var,
+            ],
+            1,
+        )?[0];
+        let is_issuer = birth_circuit.check_equal(issued_asset_code, asset_code_var)?;
+        birth_circuit.logic_or_gate(is_balanced, is_issuer)?;
+
+        // pad the birth circuit with dummy gates so that it will always be greater
+        // than the supported death ones
+        birth_circuit.pad_gate(Self::PAD_GATES);
+
+        Ok(Self::from(PredicateCircuit(birth_circuit)))
+    }
+}
+
+impl DeathPredicateCircuit for MintPredicateCircuit {
+    // The death circuit only constrains that it uses a same local data
+    // commitment as the birth predicate
+    fn gen_death_circuit_core(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        let mut death_circuit = PlonkCircuit::new_turbo_plonk();
+
+        // build all the variables
+        let comm_local_data_var = death_circuit.create_public_variable(comm_local_data)?;
+        let blinding_local_data_var = death_circuit.create_variable(blinding_local_data)?;
+
+        let entire_input_notes_vars = entire_input_notes
+            .iter()
+            .map(|x| NoteInputVar::new(&mut death_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entire_outputs_vars = entire_output_records
+            .iter()
+            .map(|x| RecordOpeningVar::new(&mut death_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let memo_vars = memo
+            .iter()
+            .map(|x| death_circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // argue that the local data is correct w.r.t. to the commitment of local data
+        local_data_commitment_circuit(
+            &mut death_circuit,
+            &entire_input_notes_vars,
+            &entire_outputs_vars,
+            &memo_vars,
+            &blinding_local_data_var,
+            &comm_local_data_var,
+        )?;
+
+        // pad the death circuit with dummy gates
+        let current_gate_count = death_circuit.num_gates();
+        let target_gate_count = Self::gen_birth_circuit_core(
+            entire_input_notes,
+            entire_output_records,
+            memo,
+            blinding_local_data,
+            comm_local_data,
+        )?
+        .0
+         .0
+        .num_gates();
+
+        death_circuit.pad_gate(target_gate_count - current_gate_count);
+
+        Ok(MintPredicateCircuit(PredicateCircuit(death_circuit)))
+    }
+}
+
+impl<'a> PredicateOps<'a> for MintPredicate<'a> {
+    fn preprocess(
+        inner_srs: &'a InnerUniversalParam,
+        outer_srs: &'a OuterUniversalParam,
+        entire_input_size: usize,
+        params: &DPCParams,
+    ) -> Result<
+        (
+            DPCProvingKey,
+            DPCVerifyingKey,
+            Self,
+            PolicyIdentifier,
+            Self,
+            PolicyIdentifier,
+        ),
+        DPCApiError,
+    > {
+        check_payload_data_len(params.payload_data_len)?;
+
+        // setup the dummy circuit/predicate/pid
+        let death_predicate_circuit =
+            MintPredicateCircuit::preprocessed_death_circuit(entire_input_size, params)?;
+        let death_predicate = Predicate::new(inner_srs, &death_predicate_circuit.0, false)?;
+        let death_pid = PolicyIdentifier::from_verifying_key(death_predicate.verifying_key());
+
+        let mut birth_predicate_circuit =
+            MintPredicateCircuit::preprocessed_birth_circuit(entire_input_size, params)?;
+        let birth_predicate = Predicate::new(inner_srs, &birth_predicate_circuit.0, true)?;
+        let birth_pid = PolicyIdentifier::from_verifying_key(birth_predicate.verifying_key());
+
+        birth_predicate_circuit
+            .0
+             .0
+            .finalize_for_mergeable_circuit(jf_plonk::MergeableCircuitType::TypeA)?;
+
+        // the inner domain size is the birth (or death) circuit's domain size
+        let unmerged_inner_domain_size = birth_predicate_circuit.0 .0.eval_domain_size()?;
+
+        let (dpc_pk, dpc_vk, (..)) = crate::proofs::transaction::preprocess(
+            outer_srs,
+            inner_srs,
+            entire_input_size - 1,
+            unmerged_inner_domain_size,
+            params,
+        )?;
+        Ok((
+            dpc_pk,
+            dpc_vk,
+            Self::from(birth_predicate),
+            birth_pid,
+            Self::from(death_predicate),
+            death_pid,
+        ))
+    }
+
+    fn finalize_for_proving(
+        &mut self,
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+        is_birth_predicate: bool,
+    ) -> Result<(), DPCApiError> {
+        let mut final_circuit = if is_birth_predicate {
+            MintPredicateCircuit::gen_birth_circuit(
+                entire_input_notes,
+                entire_output_records,
+                memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+        } else {
+            MintPredicateCircuit::gen_death_circuit(
+                entire_input_notes,
+                entire_output_records,
+                memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+        };
+
+        // sanity check: circuit is satisfied
+        final_circuit
+            .0
+             .0
+            .check_circuit_satisfiability(&[comm_local_data])?;
+
+        // finalize the circuit, and update the witness accordingly
+        let circuit_type = if is_birth_predicate {
+            jf_plonk::MergeableCircuitType::TypeA
+        } else {
+            jf_plonk::MergeableCircuitType::TypeB
+        };
+
+        final_circuit
+            .0
+             .0
+            .finalize_for_mergeable_circuit(circuit_type)?;
+
+        self.0.update_witness(final_circuit.0)?;
+        Ok(())
+    }
+
+    fn as_predicate(&self) -> &Predicate<'a> {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        assets::AssetDefinition,
+        constants::{MEMO_LEN, TREE_DEPTH},
+        examples::tests::{build_notes, build_notes_and_records},
+        keys::{DiversifiedAddress, DiversifierRandomizer, KeyChainMasterKey, ProofGenerationKey},
+        proofs::{universal_setup_inner, universal_setup_outer},
+        structs::compress_local_data,
+        transaction::{pad_records, TransactionBuilder},
+    };
+    use ark_ff::UniformRand;
+    use ark_std::{
+        rand::{CryptoRng, RngCore},
+        test_rng,
+    };
+
+    // Records of `definition` with the given values whose fee is paid by
+    // `addr`, padded to 3 inputs and 3 outputs
+    fn build_asset_records<'a, R: RngCore + CryptoRng>(
+        rng: &mut R,
+        definition: &AssetDefinition,
+        addr: &DiversifiedAddress,
+        pgk: &'a ProofGenerationKey,
+        rd: &DiversifierRandomizer,
+        input_values: &[u64],
+        output_values: &[u64],
+    ) -> Result<(Vec<NoteInput<'a>>, Vec<RecordOpening>), DPCApiError> {
+        let (pid_birth, pid_death) = (PolicyIdentifier::default(), PolicyIdentifier::default());
+        let (mut input_records, mut output_records) = build_notes_and_records(
+            rng,
+            addr,
+            pgk,
+            300,
+            295,
+            0,
+            input_values,
+            output_values,
+            pid_birth,
+            pid_death,
+        )?;
+        for (record, &value) in input_records.iter_mut().skip(1).zip(input_values) {
+            record.payload = definition.payload(value)?;
+        }
+        for (record, &value) in output_records.iter_mut().skip(1).zip(output_values) {
+            record.payload = definition.payload(value)?;
+        }
+        let mut input_notes = build_notes(&input_records, pgk, rd)?;
+        pad_records(
+            rng,
+            3,
+            &DPCParams::default(),
+            &mut input_notes,
+            &mut output_records,
+            pid_birth,
+            pid_death,
+        )?;
+        Ok((input_notes, output_records))
+    }
+
+    #[test]
+    fn test_mint_birth_circuit() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let issuer_msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (_, issuer_pgk, issuer_ivk) = issuer_msk.derive_key_chain_single_consumer();
+        let (issuer, issuer_rd) =
+            issuer_msk.derive_diversified_address(&issuer_pgk, &issuer_ivk, 0)?;
+        let user_msk = KeyChainMasterKey::generate([2u8; 32], &[]);
+        let (_, user_pgk, user_ivk) = user_msk.derive_key_chain_single_consumer();
+        let (user, user_rd) = user_msk.derive_diversified_address(&user_pgk, &user_ivk, 0)?;
+        let definition = AssetDefinition::new(issuer.clone(), b"Gold");
+
+        let memo = vec![InnerScalarField::zero(); MEMO_LEN];
+        let check_birth_circuit = |input_notes: &[NoteInput], output_records: &[RecordOpening]| {
+            let blinding_local_data = InnerScalarField::rand(&mut test_rng());
+            let comm_local_data = compress_local_data(input_notes, output_records, memo.clone())?
+                .commit(blinding_local_data)?;
+            MintPredicateCircuit::gen_birth_circuit(
+                input_notes,
+                output_records,
+                &memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+            .0
+             .0
+            .check_circuit_satisfiability(&[comm_local_data])
+            .map_err(DPCApiError::from)
+        };
+
+        // the issuer mints new supply
+        let (input_notes, output_records) = build_asset_records(
+            rng,
+            &definition,
+            &issuer,
+            &issuer_pgk,
+            &issuer_rd,
+            &[],
+            &[60, 40],
+        )?;
+        check_birth_circuit(&input_notes, &output_records)?;
+        let (input_notes, output_records) = build_asset_records(
+            rng,
+            &definition,
+            &issuer,
+            &issuer_pgk,
+            &issuer_rd,
+            &[10],
+            &[10, 30],
+        )?;
+        check_birth_circuit(&input_notes, &output_records)?;
+
+        // other users can only transfer
+        let (input_notes, output_records) = build_asset_records(
+            rng,
+            &definition,
+            &user,
+            &user_pgk,
+            &user_rd,
+            &[40],
+            &[10, 30],
+        )?;
+        check_birth_circuit(&input_notes, &output_records)?;
+        let (input_notes, output_records) = build_asset_records(
+            rng,
+            &definition,
+            &user,
+            &user_pgk,
+            &user_rd,
+            &[10],
+            &[10, 30],
+        )?;
+        assert!(check_birth_circuit(&input_notes, &output_records).is_err());
+
+        // values cannot wrap around the field modulus
+        let (input_notes, mut output_records) = build_asset_records(
+            rng,
+            &definition,
+            &user,
+            &user_pgk,
+            &user_rd,
+            &[10],
+            &[1000, 0],
+        )?;
+        output_records[2].payload.data[1] =
+            InnerScalarField::from(10u64) - InnerScalarField::from(1000u64);
+        assert!(check_birth_circuit(&input_notes, &output_records).is_err());
+
+        // inputs must have the policy identifiers of the outputs
+        let (mut input_notes, output_records) = build_asset_records(
+            rng,
+            &definition,
+            &user,
+            &user_pgk,
+            &user_rd,
+            &[40],
+            &[10, 30],
+        )?;
+        input_notes[1].ro.pid_birth = InnerScalarField::from(1u64);
+        assert!(check_birth_circuit(&input_notes, &output_records).is_err());
+        let (mut input_notes, output_records) = build_asset_records(
+            rng,
+            &definition,
+            &user,
+            &user_pgk,
+            &user_rd,
+            &[40],
+            &[10, 30],
+        )?;
+        input_notes[1].ro.pid_death = InnerScalarField::from(1u64);
+        assert!(check_birth_circuit(&input_notes, &output_records).is_err());
+
+        // records must carry the description digest of the asset
+        let (input_notes, mut output_records) = build_asset_records(
+            rng,
+            &definition,
+            &issuer,
+            &issuer_pgk,
+            &issuer_rd,
+            &[],
+            &[60, 40],
+        )?;
+        output_records[2].payload.data[2] = InnerScalarField::from(1u64);
+        assert!(check_birth_circuit(&input_notes, &output_records).is_err());
+
+        // assets of other issuers cannot be minted
+        let other_definition = AssetDefinition::new(user, b"Gold");
+        let (input_notes, output_records) = build_asset_records(
+            rng,
+            &other_definition,
+            &issuer,
+            &issuer_pgk,
+            &issuer_rd,
+            &[],
+            &[60],
+        )?;
+        assert!(check_birth_circuit(&input_notes, &output_records).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mint_payload_data_len() {
+        let short_params = DPCParams::new(TREE_DEPTH, 2, MEMO_LEN).unwrap();
+        assert!(MintPredicateCircuit::preprocessed_birth_circuit(3, &short_params).is_err());
+        assert!(MintPredicateCircuit::preprocessed_death_circuit(3, &short_params).is_err());
+        assert!(MintPredicateCircuit::preprocessed_birth_circuit(3, &DPCParams::default()).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_mint_transaction() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            MintPredicate::preprocess(&inner_srs, &outer_srs, 2, &DPCParams::default())?;
+
+        let issuer_msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (issuer_ak, issuer_pgk, issuer_ivk) = issuer_msk.derive_key_chain_single_consumer();
+        let (issuer, issuer_rd) =
+            issuer_msk.derive_diversified_address(&issuer_pgk, &issuer_ivk, 0)?;
+        let user_msk = KeyChainMasterKey::generate([2u8; 32], &[]);
+        let (user_ak, user_pgk, user_ivk) = user_msk.derive_key_chain_single_consumer();
+        let (user, user_rd) = user_msk.derive_diversified_address(&user_pgk, &user_ivk, 0)?;
+        let definition = AssetDefinition::new(issuer.clone(), b"Gold");
+
+        // the issuer mints 100 units to the user
+        let (mut input_notes, _) =
+            build_asset_records(rng, &definition, &issuer, &issuer_pgk, &issuer_rd, &[], &[])?;
+        let issuer_fee_input = input_notes.remove(0);
+        let merkle_root = issuer_fee_input.acc_member_witness.root;
+        let note = TransactionBuilder::new(
+            &dpc_pk,
+            MintPredicate(birth_predicate.0.clone()),
+            birth_pid,
+            MintPredicate(death_predicate.0.clone()),
+            death_pid,
+        )
+        .fee_input(issuer_fee_input, &issuer_ak)
+        .add_recipient(user.clone(), definition.payload(100)?, None)
+        .fee(5)
+        .build(rng)?;
+        note.verify(&dpc_vk, merkle_root)?;
+
+        // the user cannot mint
+        let (mut input_notes, _) =
+            build_asset_records(rng, &definition, &user, &user_pgk, &user_rd, &[], &[])?;
+        assert!(TransactionBuilder::new(
+            &dpc_pk,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+        )
+        .fee_input(input_notes.remove(0), &user_ak)
+        .add_recipient(user, definition.payload(100)?, None)
+        .fee(5)
+        .build(rng)
+        .is_err());
+        Ok(())
+    }
+}
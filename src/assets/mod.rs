@@ -0,0 +1,245 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Asset definitions and registry.
+//!
+//! Apart from the native asset (`constants::NATIVE_ASSET_CODE`), an asset is
+//! defined by the address of its issuer and a description. Its code is the
+//! Rescue hash of the issuer address and of a digest of the description, so
+//! that nobody can define an asset on behalf of someone else.
+//!
+//! Records of a defined asset have payloads formatted as
+//! `[ asset_code | value | description_digest | 0 | ... | 0 ]`, and the
+//! `mint` predicate only lets the issuer change the supply of the asset.
+//! Wallets and validators keep the definitions they know of in an
+//! `AssetRegistry`.
+
+#[cfg(feature = "prover")]
+pub mod mint;
+
+use crate::{
+    constants::dom_sep::ASSET_DESCRIPTION_DOM_SEP,
+    errors::DPCApiError,
+    keys::DiversifiedAddress,
+    params::DPCParams,
+    structs::Payload,
+    types::InnerScalarField,
+};
+use ark_serialize::*;
+use ark_std::{collections::BTreeMap, format, vec::Vec};
+use jf_rescue::Permutation;
+
+/// Definition of a non-native asset
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AssetDefinition {
+    issuer: DiversifiedAddress,
+    description: Vec<u8>,
+}
+
+impl AssetDefinition {
+    /// Define an asset issued by the owner of `issuer`
+    pub fn new(issuer: DiversifiedAddress, description: &[u8]) -> Self {
+        Self {
+            issuer,
+            description: description.to_vec(),
+        }
+    }
+
+    /// Address of the issuer, the only one allowed to mint the asset
+    pub fn issuer(&self) -> &DiversifiedAddress {
+        &self.issuer
+    }
+
+    /// Free form description of the asset
+    pub fn description(&self) -> &[u8] {
+        &self.description
+    }
+
+    /// Digest of the description, as stored in the payload of the records
+    pub fn description_digest(&self) -> InnerScalarField {
+        let bytes = [ASSET_DESCRIPTION_DOM_SEP.as_bytes(), &self.description].concat();
+        jf_utils::hash_to_field(&bytes)
+    }
+
+    /// Asset code derived from the issuer address and the description
+    pub fn code(&self) -> InnerScalarField {
+        derive_asset_code(&self.issuer, self.description_digest())
+    }
+
+    /// Payload of a record holding `value` units of the asset
+    pub fn payload(&self, value: u64) -> Result<Payload, DPCApiError> {
+        self.payload_with_params(value, &DPCParams::default())
+    }
+
+    /// Payload of a record holding `value` units of the asset, for the record
+    /// layout of `params`
+    pub fn payload_with_params(
+        &self,
+        value: u64,
+        params: &DPCParams,
+    ) -> Result<Payload, DPCApiError> {
+        Payload::from_scalars_with_params(
+            &[
+                self.code(),
+                InnerScalarField::from(value),
+                self.description_digest(),
+            ],
+            params,
+        )
+    }
+}
+
+// Same hash as computed by the mint predicate circuit
+pub(crate) fn derive_asset_code(
+    issuer: &DiversifiedAddress,
+    description_digest: InnerScalarField,
+) -> InnerScalarField {
+    let mut input = issuer.to_elems();
+    input.push(description_digest);
+    Permutation::default().sponge_with_padding(&input, 1)[0]
+}
+
+/// Registry of asset definitions indexed by asset code
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssetRegistry {
+    definitions: BTreeMap<InnerScalarField, AssetDefinition>,
+}
+
+impl AssetRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a definition to the registry and return its asset code.
+    /// Registering the same definition twice has no effect.
+    pub fn register(&mut self, definition: AssetDefinition) -> InnerScalarField {
+        let code = definition.code();
+        self.definitions.entry(code).or_insert(definition);
+        code
+    }
+
+    /// Definition of the asset with code `code`, if registered
+    pub fn get(&self, code: &InnerScalarField) -> Option<&AssetDefinition> {
+        self.definitions.get(code)
+    }
+
+    /// Whether the asset with code `code` is registered
+    pub fn contains(&self, code: &InnerScalarField) -> bool {
+        self.definitions.contains_key(code)
+    }
+
+    /// Codes of the registered assets, in increasing order
+    pub fn codes(&self) -> impl Iterator<Item = &InnerScalarField> {
+        self.definitions.keys()
+    }
+
+    /// Number of registered assets
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    /// Whether no asset is registered
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Definition of the asset held by a record with payload `payload`.
+    /// Return an error if the asset is not registered or if the payload does
+    /// not carry the digest of its description.
+    pub fn definition_of(&self, payload: &Payload) -> Result<&AssetDefinition, DPCApiError> {
+        let data = payload.data();
+        if data.len() < 3 {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Payload length {} is too short for an asset record",
+                data.len()
+            )));
+        }
+        let definition = self.get(&data[0]).ok_or_else(|| {
+            DPCApiError::InvalidParameter(format!("Unknown asset code {}", data[0]))
+        })?;
+        if definition.description_digest() != data[2] {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Payload does not match the definition of asset {}",
+                data[0]
+            )));
+        }
+        Ok(definition)
+    }
+
+    /// Write all the definitions to `writer`
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), DPCApiError> {
+        // same layout as a serialized `Vec<AssetDefinition>`
+        (self.definitions.len() as u64).serialize(&mut writer)?;
+        for definition in self.definitions.values() {
+            definition.serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Add the definitions written by `save` to the registry and return their
+    /// number
+    pub fn load<R: Read>(&mut self, reader: R) -> Result<usize, DPCApiError> {
+        let definitions = Vec::<AssetDefinition>::deserialize(reader)?;
+        let num_definitions = definitions.len();
+        for definition in definitions {
+            self.register(definition);
+        }
+        Ok(num_definitions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{constants::NATIVE_ASSET_CODE, keys::KeyChainMasterKey};
+
+    #[test]
+    fn test_asset_registry() -> Result<(), DPCApiError> {
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (issuer, _) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (other_issuer, _) = msk.derive_diversified_address(&pgk, &ivk, 1)?;
+
+        // codes are bound to both the issuer and the description
+        let definition = AssetDefinition::new(issuer.clone(), b"Gold");
+        let code = definition.code();
+        assert_ne!(code, NATIVE_ASSET_CODE);
+        assert_eq!(code, AssetDefinition::new(issuer.clone(), b"Gold").code());
+        assert_ne!(code, AssetDefinition::new(issuer, b"Silver").code());
+        assert_ne!(code, AssetDefinition::new(other_issuer, b"Gold").code());
+
+        let mut registry = AssetRegistry::new();
+        assert!(registry.is_empty());
+        assert_eq!(registry.register(definition.clone()), code);
+        assert_eq!(registry.register(definition.clone()), code);
+        assert_eq!(registry.len(), 1);
+        assert!(registry.contains(&code));
+        assert!(!registry.contains(&NATIVE_ASSET_CODE));
+        assert_eq!(registry.get(&code), Some(&definition));
+
+        // payloads are checked against the registered definitions
+        let payload = definition.payload(10)?;
+        assert_eq!(registry.definition_of(&payload)?, &definition);
+        let mut bad_payload = payload.clone();
+        bad_payload.data[2] = InnerScalarField::from(1u64);
+        assert!(registry.definition_of(&bad_payload).is_err());
+        let native_payload = Payload::from_scalars(&[NATIVE_ASSET_CODE])?;
+        assert!(registry.definition_of(&native_payload).is_err());
+
+        let mut bytes = Vec::new();
+        registry.save(&mut bytes)?;
+        let mut loaded = AssetRegistry::new();
+        assert_eq!(loaded.load(&bytes[..])?, 1);
+        assert_eq!(loaded, registry);
+        Ok(())
+    }
+}
@@ -10,15 +10,17 @@
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+    app::{BirthPredicateCircuit, DeathPredicateCircuit, PredicateOps},
     constants::MEMO_LEN,
     errors::DPCApiError,
     examples::{
         tests::{build_notes, build_notes_and_records},
-        zcash_example::ZcashPredicate,
-        PredicateOps,
+        zcash_example::{ZcashPredicate, ZcashPredicateCircuit},
     },
     keys::{aggregate_authorization_signing_keypairs, KeyChainMasterKey},
+    params::DPCParams,
     predicates::PredicateTrait,
+    proofs::stats::CircuitStats,
     structs::compress_local_data,
     transaction::DPCTxnBody,
     types::InnerScalarField,
@@ -28,13 +30,6 @@ use ark_std::{end_timer, println, rand::Rng, start_timer, vec, UniformRand, Zero
 #[test]
 fn dpc_bench() -> Result<(), DPCApiError> {
     let rng = &mut ark_std::test_rng();
-    // TODO: Maybe adjust these two parameters after getting the circuit size
-    let max_inner_degree = (1 << 17) + 4;
-    let max_outer_degree = (1 << 18) + 4;
-
-    let start = start_timer!(|| "DPC::Setup");
-    let inner_srs = crate::proofs::universal_setup_inner(max_inner_degree, rng)?;
-    let outer_srs = crate::proofs::universal_setup_outer(max_outer_degree, rng)?;
 
     // 2-input-2-output (including fee and fee change then: 3-in-3-out)
     let num_non_fee_inputs = 2;
@@ -42,8 +37,30 @@ fn dpc_bench() -> Result<(), DPCApiError> {
 
     println!("ℹ️ num of inputs/outputs: {}", num_input);
 
+    let params = DPCParams::default();
+    let stats = CircuitStats::new(
+        num_non_fee_inputs,
+        &ZcashPredicateCircuit::preprocessed_birth_circuit(num_input, &params)?.0,
+        &ZcashPredicateCircuit::preprocessed_death_circuit(num_input, &params)?.0,
+        &params,
+    )?;
+    println!(
+        "ℹ️ num_constraint of (unpadded) UTXO circuit: {}, of (unpadded) outer circuit: {}",
+        stats.utxo.num_gates, stats.policies_vfy.num_gates,
+    );
+    let max_inner_degree = stats.inner_srs_degree();
+    let max_outer_degree = stats.outer_srs_degree();
+    println!(
+        "ℹ️ inner SRS degree: {}, outer SRS degree: {}",
+        max_inner_degree, max_outer_degree,
+    );
+
+    let start = start_timer!(|| "DPC::Setup");
+    let inner_srs = crate::proofs::universal_setup_inner(max_inner_degree, rng)?;
+    let outer_srs = crate::proofs::universal_setup_outer(max_outer_degree, rng)?;
+
     let (dpc_pk, dpc_vk, mut birth_predicate, birth_pid, mut death_predicate, death_pid) =
-        ZcashPredicate::preprocess(&inner_srs, &outer_srs, num_input)?;
+        ZcashPredicate::preprocess(&inner_srs, &outer_srs, num_input, &params)?;
 
     println!(
         "ℹ️ birth predicate size: {}; death predicate size: {}",
@@ -130,7 +147,7 @@ fn dpc_bench() -> Result<(), DPCApiError> {
     let input_death_predicates = vec![death_predicate.0; num_non_fee_inputs];
     let output_birth_predicates = vec![birth_predicate.0; num_non_fee_inputs];
 
-    let txn_body = DPCTxnBody::generate(
+    let (txn_body, timings) = DPCTxnBody::generate_with_timings(
         rng,
         &dpc_pk,
         entire_input_notes.clone(),
@@ -142,6 +159,11 @@ fn dpc_bench() -> Result<(), DPCApiError> {
         blinding_local_data,
     )?;
 
+    println!(
+        "ℹ️ proving time: UTXO {:?}, inner batch {:?}, outer {:?}, total {:?}",
+        timings.utxo, timings.inner_batch, timings.outer, timings.total
+    );
+
     let txn_note = {
         // TODO: move this earlier to avoid cloning entire_input_notes
         let auth_keys = vec![ak.0; num_input];
@@ -0,0 +1,216 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local multi-party ceremony generating the inner and outer universal
+//! parameters.
+//!
+//! Commands:
+//!
+//! ```text
+//! veri-zexe-ceremony init --transcript <FILE> --inner-degree <DEGREE> --outer-degree <DEGREE>
+//! veri-zexe-ceremony contribute --transcript <FILE>
+//! veri-zexe-ceremony verify --transcript <FILE>
+//! veri-zexe-ceremony finalize --transcript <FILE> --inner-srs <FILE> --outer-srs <FILE>
+//! veri-zexe-ceremony import --inner-powers <FILE> --outer-powers <FILE>
+//!     --inner-degree <DEGREE> --outer-degree <DEGREE> --inner-srs <FILE> --outer-srs <FILE>
+//! ```
+//!
+//! - `init` writes a transcript without any contribution.
+//! - `contribute` verifies the transcript, adds a contribution with
+//!   randomness from the OS and prints the hash of its proof. Participants
+//!   pass the transcript file on to the next one and publish the hash.
+//! - `verify` checks the whole chain of contributions and prints the hashes
+//!   of all contribution proofs, so that participants can check theirs is
+//!   included.
+//! - `finalize` verifies the transcript and writes the universal parameters
+//!   in the format expected by `veri-zexe-wallet`.
+//! - `import` converts the BLS12-377 and BW6-761 powers of tau of another
+//!   ceremony, in the transcript format described in `proofs::srs`, into the
+//!   universal parameters expected by `veri-zexe-wallet`. The powers are
+//!   checked to be consistent and to support the given degrees.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, env, fs, process};
+use veri_zexe::proofs::{
+    ceremony::{DPCCeremony, DPCContributionProof},
+    srs::{load_inner_srs, load_outer_srs},
+};
+
+type CliResult<T> = Result<T, String>;
+
+fn err<E: ToString>(context: &str) -> impl FnOnce(E) -> String + '_ {
+    move |e| format!("{}: {}", context, e.to_string())
+}
+
+fn load_transcript(path: &str) -> CliResult<DPCCeremony> {
+    let bytes = fs::read(path).map_err(err("Cannot read transcript"))?;
+    DPCCeremony::deserialize(&bytes[..]).map_err(err("Invalid transcript"))
+}
+
+// The transcript is stored compressed and fully checked when loaded, the
+// universal parameters are stored as expected by `veri-zexe-wallet`.
+fn save_transcript(path: &str, ceremony: &DPCCeremony) -> CliResult<()> {
+    let mut bytes = vec![];
+    ceremony
+        .serialize(&mut bytes)
+        .map_err(err("Cannot serialize transcript"))?;
+    write_file(path, bytes)
+}
+
+fn save_srs<T: CanonicalSerialize>(path: &str, srs: &T) -> CliResult<()> {
+    let mut bytes = vec![];
+    srs.serialize_unchecked(&mut bytes)
+        .map_err(err("Cannot serialize SRS"))?;
+    write_file(path, bytes)
+}
+
+// Write to a temporary file first so that a failure never leaves a truncated
+// file behind.
+fn write_file(path: &str, bytes: Vec<u8>) -> CliResult<()> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, bytes).map_err(err("Cannot write file"))?;
+    fs::rename(&tmp_path, path).map_err(err("Cannot write file"))
+}
+
+fn proof_hash(proof: &DPCContributionProof) -> CliResult<String> {
+    let mut bytes = vec![];
+    proof
+        .serialize(&mut bytes)
+        .map_err(err("Cannot serialize proof"))?;
+    Ok(Sha256::digest(&bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn cmd_init(args: &Args) -> CliResult<()> {
+    let path = args.required("--transcript")?;
+    let ceremony = DPCCeremony::new(args.parse("--inner-degree")?, args.parse("--outer-degree")?)
+        .map_err(err("Cannot start ceremony"))?;
+    save_transcript(path, &ceremony)?;
+    println!("Ceremony transcript written to {}", path);
+    Ok(())
+}
+
+fn cmd_contribute(args: &Args) -> CliResult<()> {
+    let path = args.required("--transcript")?;
+    let mut ceremony = load_transcript(path)?;
+    if ceremony.num_contributions() > 0 {
+        ceremony
+            .verify(&mut OsRng)
+            .map_err(err("Invalid transcript"))?;
+    }
+    let proof = ceremony
+        .contribute(&mut OsRng)
+        .map_err(err("Cannot contribute"))?;
+    save_transcript(path, &ceremony)?;
+    println!(
+        "Contribution {}: {}",
+        ceremony.num_contributions() - 1,
+        proof_hash(&proof)?
+    );
+    Ok(())
+}
+
+fn cmd_verify(args: &Args) -> CliResult<()> {
+    let ceremony = load_transcript(args.required("--transcript")?)?;
+    ceremony
+        .verify(&mut OsRng)
+        .map_err(err("Invalid transcript"))?;
+    for index in 0..ceremony.num_contributions() {
+        let proof = ceremony
+            .contribution_proof(index)
+            .ok_or("Missing contribution proof")?;
+        println!("Contribution {}: {}", index, proof_hash(&proof)?);
+    }
+    println!("Transcript is valid");
+    Ok(())
+}
+
+fn cmd_finalize(args: &Args) -> CliResult<()> {
+    let ceremony = load_transcript(args.required("--transcript")?)?;
+    let (inner_srs, outer_srs) = ceremony
+        .into_universal_params(&mut OsRng)
+        .map_err(err("Invalid transcript"))?;
+    save_srs(args.required("--inner-srs")?, &inner_srs)?;
+    save_srs(args.required("--outer-srs")?, &outer_srs)?;
+    println!("Universal parameters written");
+    Ok(())
+}
+
+fn cmd_import(args: &Args) -> CliResult<()> {
+    let inner_powers =
+        fs::read(args.required("--inner-powers")?).map_err(err("Cannot read inner powers"))?;
+    let inner_srs = load_inner_srs(&inner_powers[..], args.parse("--inner-degree")?, &mut OsRng)
+        .map_err(err("Invalid inner powers"))?;
+    let outer_powers =
+        fs::read(args.required("--outer-powers")?).map_err(err("Cannot read outer powers"))?;
+    let outer_srs = load_outer_srs(&outer_powers[..], args.parse("--outer-degree")?, &mut OsRng)
+        .map_err(err("Invalid outer powers"))?;
+    save_srs(args.required("--inner-srs")?, &inner_srs)?;
+    save_srs(args.required("--outer-srs")?, &outer_srs)?;
+    println!("Universal parameters written");
+    Ok(())
+}
+
+struct Args {
+    command: String,
+    options: BTreeMap<String, String>,
+}
+
+impl Args {
+    fn from_env() -> CliResult<Self> {
+        let mut args = env::args().skip(1);
+        let command = args.next().ok_or("Missing command")?;
+        let mut options = BTreeMap::new();
+        while let Some(key) = args.next() {
+            if !key.starts_with("--") {
+                return Err(format!("Unexpected argument {}", key));
+            }
+            let value = args.next().ok_or(format!("Missing value for {}", key))?;
+            options.insert(key, value);
+        }
+        Ok(Self { command, options })
+    }
+
+    fn required(&self, key: &str) -> CliResult<&str> {
+        self.options
+            .get(key)
+            .map(String::as_str)
+            .ok_or(format!("Missing {}", key))
+    }
+
+    fn parse<T: std::str::FromStr>(&self, key: &str) -> CliResult<T>
+    where
+        T::Err: ToString,
+    {
+        self.required(key)?
+            .parse()
+            .map_err(|e: T::Err| format!("Invalid {}: {}", key, e.to_string()))
+    }
+}
+
+fn main() {
+    let result = Args::from_env().and_then(|args| match args.command.as_str() {
+        "init" => cmd_init(&args),
+        "contribute" => cmd_contribute(&args),
+        "verify" => cmd_verify(&args),
+        "finalize" => cmd_finalize(&args),
+        "import" => cmd_import(&args),
+        command => Err(format!("Unknown command {}", command)),
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,486 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Proof-of-concept DPC node.
+//!
+//! The node loads DPC verifying keys from files, keeps a `LedgerState`
+//! together with the receiver memos of all accumulated records, and serves a
+//! JSON-RPC 2.0 interface on a localhost TCP socket. Each request and each
+//! response is a single line of JSON.
+//!
+//! Usage:
+//!
+//! ```text
+//! veri-zexe-node --verifying-key <FILE>... [--genesis <FILE>] [--port <PORT>]
+//!                [--check-memo-time]
+//! ```
+//!
+//! - `--verifying-key`: canonical serialization of a `DPCVerifyingKey`, as
+//!   produced by `preprocess`. Verification only requires the verifying key,
+//!   the SRS it was derived from is not needed by the node. The option can be
+//!   repeated to accept notes of several shapes, each note is verified with
+//!   the key of its shape. All keys must share the same parameters.
+//! - `--genesis`: JSON array of `REC_OPENING~...` record openings whose
+//!   commitments are inserted into the ledger at startup, e.g. to fund the
+//!   first fee inputs.
+//! - `--port`: port to listen on at 127.0.0.1, 8899 by default.
+//! - `--check-memo-time`: reject notes whose memo states a time after the
+//!   current time of the ledger, for applications such as time locks. Off by
+//!   default, as other applications may put any data in their memo.
+//!
+//! Methods:
+//! - `submit_note [note]`: verify and apply a `DPC_NOTE~...` transaction note,
+//!   return the uids of its output records. The current time of the ledger,
+//!   checked against the time stated in the memo of the note with
+//!   `--check-memo-time`, is the UNIX time in seconds.
+//! - `get_root []`: current Merkle root of the records accumulator.
+//! - `get_num_records []`: number of accumulated records.
+//! - `get_membership_witness [uid]`: membership witness of the record `uid`
+//!   w.r.t. the current root.
+//! - `get_memos [from_uid]`: receiver memos of the records with uid at least
+//!   `from_uid`, as a list of `{uid, memo}` objects.
+//! - `get_notes [from_index]`: applied transaction notes starting at the
+//!   `from_index`-th one, as a list of `{index, first_uid, note}` objects
+//!   where `first_uid` is the uid of the first output record of the note.
+//! - `is_spent [nullifier]`: whether a `NULLIFIER~...` was published.
+
+use ark_serialize::CanonicalDeserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use veri_zexe::{
+    errors::DPCApiError,
+    ledger::{LedgerState, DEFAULT_ROOT_HISTORY_LEN},
+    proofs::{registry::KeyRegistry, transaction::DPCVerifyingKey},
+    structs::{Nullifier, ReceiverMemo, RecordOpening},
+    transaction::DPCTxnNote,
+};
+
+const DEFAULT_PORT: u16 = 8899;
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const APPLICATION_ERROR: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl ToString) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<DPCApiError> for RpcError {
+    fn from(e: DPCApiError) -> Self {
+        Self {
+            code: APPLICATION_ERROR,
+            message: e.to_string(),
+        }
+    }
+}
+
+struct Node {
+    keys: KeyRegistry<'static>,
+    ledger: LedgerState,
+    memos: BTreeMap<u64, ReceiverMemo>,
+    // applied notes together with the uid of their first output
+    notes: Vec<(u64, DPCTxnNote)>,
+}
+
+impl Node {
+    fn new(keys: KeyRegistry<'static>, genesis: &[RecordOpening]) -> Result<Self, DPCApiError> {
+        let mut ledger = LedgerState::new_with_params(DEFAULT_ROOT_HISTORY_LEN, keys.params())?;
+        for ro in genesis.iter() {
+            ledger.insert_record_commitment(ro.derive_record_commitment()?)?;
+        }
+        Ok(Self {
+            keys,
+            ledger,
+            memos: BTreeMap::new(),
+            notes: vec![],
+        })
+    }
+
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "submit_note" => {
+                let note: DPCTxnNote = parse_param(params, 0)?;
+                let verifying_key = self.keys.verifying_key(note.body.shape())?;
+                self.ledger.advance_time(unix_time());
+                let uids = self.ledger.apply_note(&note, verifying_key)?;
+                for (uid, memo) in uids.iter().zip(note.body.receiver_memos.iter()) {
+                    self.memos.insert(*uid, memo.clone());
+                }
+                let first_uid = uids.first().copied().unwrap_or(self.ledger.num_records());
+                self.notes.push((first_uid, note));
+                Ok(json!(uids))
+            },
+            "get_root" => to_json(&self.ledger.root()),
+            "get_num_records" => Ok(json!(self.ledger.num_records())),
+            "get_membership_witness" => {
+                let uid: u64 = parse_param(params, 0)?;
+                to_json(&self.ledger.membership_witness(uid)?)
+            },
+            "get_memos" => {
+                let from_uid: u64 = parse_param(params, 0)?;
+                let memos = self
+                    .memos
+                    .range(from_uid..)
+                    .map(|(uid, memo)| Ok(json!({ "uid": uid, "memo": to_json(memo)? })))
+                    .collect::<Result<Vec<_>, RpcError>>()?;
+                Ok(Value::Array(memos))
+            },
+            "get_notes" => {
+                let from_index: usize = parse_param(params, 0)?;
+                let notes = self
+                    .notes
+                    .iter()
+                    .enumerate()
+                    .skip(from_index)
+                    .map(|(index, (first_uid, note))| {
+                        Ok(json!({ "index": index, "first_uid": first_uid, "note": to_json(note)? }))
+                    })
+                    .collect::<Result<Vec<_>, RpcError>>()?;
+                Ok(Value::Array(notes))
+            },
+            "is_spent" => {
+                let nullifier: Nullifier = parse_param(params, 0)?;
+                Ok(json!(self.ledger.is_spent(&nullifier)))
+            },
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method {}", method),
+            }),
+        }
+    }
+
+    // Process a single JSON-RPC request line and return the response.
+    fn process_request(&mut self, line: &str) -> Value {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return error_response(Value::Null, PARSE_ERROR, e.to_string()),
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = match request.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => {
+                return error_response(id, INVALID_REQUEST, "Missing method".to_string());
+            },
+        };
+        let params = match request.get("params") {
+            None | Some(Value::Null) => vec![],
+            Some(Value::Array(params)) => params.clone(),
+            Some(_) => {
+                return error_response(id, INVALID_PARAMS, "Params must be an array".to_string())
+            },
+        };
+        match self.handle(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(e) => error_response(id, e.code, e.message),
+        }
+    }
+
+    fn serve_connection(&mut self, stream: TcpStream) -> std::io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = self.process_request(&line);
+            writeln!(writer, "{}", response)?;
+        }
+        Ok(())
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn parse_param<T: serde::de::DeserializeOwned>(
+    params: &[Value],
+    index: usize,
+) -> Result<T, RpcError> {
+    let param = params
+        .get(index)
+        .ok_or_else(|| RpcError::invalid_params(format!("Missing parameter {}", index)))?;
+    serde_json::from_value(param.clone())
+        .map_err(|e| RpcError::invalid_params(format!("Invalid parameter {}: {}", index, e)))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError {
+        code: APPLICATION_ERROR,
+        message: e.to_string(),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+}
+
+struct Args {
+    verifying_keys: Vec<String>,
+    genesis: Option<String>,
+    port: u16,
+    check_memo_time: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut verifying_keys = vec![];
+    let mut genesis = None;
+    let mut port = DEFAULT_PORT;
+    let mut check_memo_time = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--verifying-key" => verifying_keys.push(value()?),
+            "--genesis" => genesis = Some(value()?),
+            "--port" => {
+                port = value()?
+                    .parse()
+                    .map_err(|e| format!("Invalid port: {}", e))?
+            },
+            "--check-memo-time" => check_memo_time = true,
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    if verifying_keys.is_empty() {
+        return Err("Missing --verifying-key".to_string());
+    }
+    Ok(Args {
+        verifying_keys,
+        genesis,
+        port,
+        check_memo_time,
+    })
+}
+
+fn load_node(args: &Args) -> Result<Node, String> {
+    let mut keys: Option<KeyRegistry<'static>> = None;
+    for path in args.verifying_keys.iter() {
+        let bytes = fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let verifying_key = DPCVerifyingKey::deserialize(&bytes[..])
+            .map_err(|e| format!("Invalid verifying key {}: {}", path, e))?;
+        keys.get_or_insert_with(|| KeyRegistry::new(*verifying_key.params()))
+            .insert(verifying_key)
+            .map_err(|e| format!("Invalid verifying key {}: {}", path, e))?;
+    }
+    let keys = keys.ok_or("Missing --verifying-key")?;
+    let genesis: Vec<RecordOpening> = match &args.genesis {
+        Some(path) => {
+            let file =
+                fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            serde_json::from_str(&file).map_err(|e| format!("Invalid genesis records: {}", e))?
+        },
+        None => vec![],
+    };
+    let mut node = Node::new(keys, &genesis).map_err(|e| e.to_string())?;
+    node.ledger.set_memo_time_check(args.check_memo_time);
+    Ok(node)
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!(
+            "Usage: veri-zexe-node --verifying-key <FILE>... [--genesis <FILE>] [--port <PORT>] \
+             [--check-memo-time]"
+        );
+        process::exit(2);
+    });
+    let mut node = load_node(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, args.port)).unwrap_or_else(|e| {
+        eprintln!("Cannot listen on port {}: {}", args.port, e);
+        process::exit(1);
+    });
+    println!(
+        "veri-zexe-node listening on 127.0.0.1:{} with {} records",
+        args.port,
+        node.ledger.num_records()
+    );
+    // Requests are handled one at a time so that notes are applied in order.
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| node.serve_connection(stream));
+        if let Err(e) = result {
+            eprintln!("Connection error: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use veri_zexe::params::DPCParams;
+
+    fn request(node: &mut Node, method: &str, params: Value) -> Value {
+        let line = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+        node.process_request(&line.to_string())
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn test_process_request() -> Result<(), DPCApiError> {
+        let mut node = Node::new(KeyRegistry::new(DPCParams::default()), &[])?;
+
+        // malformed requests
+        let response = node.process_request("{ not json");
+        assert_eq!(error_code(&response), Some(PARSE_ERROR));
+        assert_eq!(response["id"], Value::Null);
+        let response = node.process_request(r#"{ "jsonrpc": "2.0", "id": 3 }"#);
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+        assert_eq!(response["id"], json!(3));
+        let response = request(&mut node, "get_root", json!({ "uid": 0 }));
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+        let response = request(&mut node, "get_balance", json!([]));
+        assert_eq!(error_code(&response), Some(METHOD_NOT_FOUND));
+
+        // bad parameters
+        let response = request(&mut node, "get_membership_witness", json!([]));
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+        let response = request(&mut node, "get_memos", json!(["zero"]));
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+        let response = request(&mut node, "submit_note", json!(["DPC_NOTE~invalid"]));
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+        let response = request(&mut node, "is_spent", json!([0]));
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+
+        // queries on an empty ledger
+        let response = request(&mut node, "get_root", json!([]));
+        assert_eq!(
+            response["result"],
+            serde_json::to_value(node.ledger.root()).unwrap()
+        );
+        assert_eq!(response["id"], json!(1));
+        let response = request(&mut node, "get_num_records", Value::Null);
+        assert_eq!(response["result"], json!(0));
+        let response = request(&mut node, "get_membership_witness", json!([0]));
+        assert_eq!(error_code(&response), Some(APPLICATION_ERROR));
+        let response = request(&mut node, "get_memos", json!([0]));
+        assert_eq!(response["result"], json!([]));
+        let response = request(&mut node, "get_notes", json!([0]));
+        assert_eq!(response["result"], json!([]));
+        let nullifier = serde_json::to_value(Nullifier::default()).unwrap();
+        let response = request(&mut node, "is_spent", json!([nullifier]));
+        assert_eq!(response["result"], json!(false));
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_submit_note() -> Result<(), DPCApiError> {
+        use std::borrow::Cow;
+        use veri_zexe::{
+            app::{InnerScalarField, PredicateOps},
+            constants::NATIVE_ASSET_CODE,
+            examples::zcash_example::ZcashPredicate,
+            keys::KeyChainMasterKey,
+            proofs::{universal_setup_inner, universal_setup_outer},
+            structs::{NoteInput, Payload},
+            transaction::TransactionBuilder,
+        };
+
+        let rng = &mut ark_std::test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let params = DPCParams::default();
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 2, &params)?;
+
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let payload = Payload::from_scalars(&[NATIVE_ASSET_CODE, InnerScalarField::from(40u64)])?;
+        let genesis = vec![
+            RecordOpening::new_native_asset(rng, addr.clone(), 300, 0, Nullifier::default()),
+            RecordOpening::new(
+                rng,
+                addr.clone(),
+                payload.clone(),
+                birth_pid.to_scalar(),
+                death_pid.to_scalar(),
+                0,
+                Nullifier::default(),
+            ),
+        ];
+        let mut keys = KeyRegistry::new(params);
+        keys.insert(dpc_vk)?;
+        let mut node = Node::new(keys, &genesis)?;
+
+        let mut inputs = vec![];
+        for (uid, ro) in genesis.into_iter().enumerate() {
+            inputs.push(NoteInput {
+                ro,
+                acc_member_witness: node.ledger.membership_witness(uid as u64)?,
+                proof_gen_key: Cow::Borrowed(&pgk),
+                authorization_randomizer: Default::default(),
+                diversifier_randomizer: rd.clone(),
+            });
+        }
+        let record_input = inputs.pop().unwrap();
+        let fee_input = inputs.pop().unwrap();
+        let note = TransactionBuilder::new(
+            &dpc_pk,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+        )
+        .fee_input(fee_input, &ak)
+        .add_input(record_input, &ak)
+        .add_recipient(addr, payload, None)
+        .fee(5)
+        .build(rng)?;
+        let note = serde_json::to_value(&note).unwrap();
+
+        // the note is applied once, its replay is rejected
+        let response = request(&mut node, "submit_note", json!([note]));
+        assert_eq!(response["result"], json!([2, 3]));
+        let response = request(&mut node, "get_notes", json!([0]));
+        assert_eq!(response["result"][0]["first_uid"], json!(2));
+        let response = request(&mut node, "get_memos", json!([0]));
+        assert_eq!(response["result"].as_array().map(Vec::len), Some(2));
+        let response = request(&mut node, "submit_note", json!([note]));
+        assert_eq!(error_code(&response), Some(APPLICATION_ERROR));
+        let response = request(&mut node, "get_num_records", json!([]));
+        assert_eq!(response["result"], json!(4));
+        Ok(())
+    }
+}
@@ -0,0 +1,613 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Proof-of-concept command-line wallet.
+//!
+//! Keys are stored in a keystore file encrypted under a password read from
+//! the `VERI_ZEXE_WALLET_PASSWORD` environment variable, or from the terminal
+//! without echo if it is not set. Transfers use the Zcash example application
+//! with one non-fee input and one non-fee output: a transfer spends one owned
+//! record entirely, and pays the fee with an owned native fee record whose
+//! change returns to the wallet.
+//!
+//! Commands:
+//!
+//! ```text
+//! veri-zexe-wallet setup --inner-srs <FILE> --outer-srs <FILE> --proving-key <FILE>
+//!     --verifying-key <FILE> --app <FILE>
+//! veri-zexe-wallet new --keystore <FILE>
+//! veri-zexe-wallet address --keystore <FILE> [--index <INDEX>]
+//! veri-zexe-wallet genesis-record --keystore <FILE> --amount <AMOUNT> [--app <FILE>]
+//! veri-zexe-wallet import --keystore <FILE> --record <REC_OPENING> --uid <UID>
+//! veri-zexe-wallet list --keystore <FILE> [--port <PORT>]
+//! veri-zexe-wallet transfer --keystore <FILE> --inner-srs <FILE> --proving-key <FILE>
+//!     --app <FILE> --uid <UID> --to <DIV-ADDR> --fee <FEE> [--out <FILE>] [--port <PORT>]
+//! ```
+//!
+//! - `setup` preprocesses the application keys from the SRS files written by
+//!   `veri-zexe-ceremony finalize`, writes the proving key for `transfer`,
+//!   the verifying key for `veri-zexe-node` and the application predicate
+//!   identifiers. The SRS files are fully checked when loaded.
+//! - `genesis-record` prints a record for the first address of the wallet,
+//!   to be listed in the genesis file of the node. Without `--app` it is a
+//!   native fee record, otherwise it is spendable by the application.
+//! - `import` tracks a genesis record at position `uid` in the keystore.
+//! - `list` scans the notes of the node at `127.0.0.1:<PORT>` and prints the
+//!   owned unspent records.
+//! - `transfer` builds and authorizes a note with the proving key written by
+//!   `setup`, then writes it to `--out` or submits it to the node. Only the
+//!   predicates of the application are set up again, from the inner SRS, as
+//!   they cannot be serialized.
+
+use argon2::Argon2;
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpStream},
+    path::Path,
+    process,
+};
+use veri_zexe::{
+    app::{
+        BirthPredicateCircuit, DeathPredicateCircuit, InnerScalarField, InnerUniversalParam,
+        OuterUniversalParam, Predicate, PredicateOps,
+    },
+    constants::NATIVE_ASSET_CODE,
+    examples::zcash_example::{ZcashPredicate, ZcashPredicateCircuit},
+    keys::{
+        AuthorizationKeyPair, DiversifiedAddress, DiversifierRandomizer, IncomingViewingKey,
+        KeyChainMasterKey, ProofGenerationKey,
+    },
+    params::DPCParams,
+    predicates::PredicateTrait,
+    proofs::transaction::DPCProvingKey,
+    structs::{NoteInput, Payload, PolicyIdentifier, RecordOpening},
+    transaction::{DPCTxnNote, TransactionBuilder},
+    wallet::{OwnedRecord, WalletScanner},
+};
+
+const DEFAULT_PORT: u16 = 8899;
+const PASSWORD_ENV: &str = "VERI_ZEXE_WALLET_PASSWORD";
+// Number of diversified addresses searched when spending a record
+const ADDRESS_LOOKAHEAD: u16 = 16;
+// Entire number of inputs (and outputs) of a transfer, including fee
+const TRANSFER_INPUT_SIZE: usize = 2;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+type CliResult<T> = Result<T, String>;
+
+fn err<E: ToString>(context: &str) -> impl FnOnce(E) -> String + '_ {
+    move |e| format!("{}: {}", context, e.to_string())
+}
+
+// ============================================================================
+// Keystore
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+struct ImportedRecord {
+    ro: RecordOpening,
+    uid: u64,
+}
+
+/// Plaintext content of the keystore
+#[derive(Serialize, Deserialize)]
+struct Keystore {
+    wallet_master_key: [u8; 32],
+    imported_records: Vec<ImportedRecord>,
+}
+
+impl Keystore {
+    fn master_key(&self) -> KeyChainMasterKey {
+        KeyChainMasterKey::generate(self.wallet_master_key, &[])
+    }
+}
+
+fn read_password() -> CliResult<String> {
+    if let Ok(password) = env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+    rpassword::prompt_password("Keystore password: ").map_err(err("Cannot read password"))
+}
+
+fn derive_cipher(password: &str, salt: &[u8]) -> CliResult<ChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(err("Cannot derive keystore key"))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+// Keystore file layout: salt || nonce || ciphertext of the JSON keystore
+fn save_keystore(path: &str, keystore: &Keystore, password: &str) -> CliResult<()> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let plaintext = serde_json::to_vec(keystore).map_err(err("Cannot serialize keystore"))?;
+    let ciphertext = derive_cipher(password, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), &plaintext[..])
+        .map_err(|_| "Cannot encrypt keystore".to_string())?;
+    let bytes = [&salt[..], &nonce[..], &ciphertext[..]].concat();
+    fs::write(path, bytes).map_err(err("Cannot write keystore"))
+}
+
+fn load_keystore(path: &str, password: &str) -> CliResult<Keystore> {
+    let bytes = fs::read(path).map_err(err("Cannot read keystore"))?;
+    if bytes.len() < SALT_LEN + NONCE_LEN {
+        return Err("Keystore file is truncated".to_string());
+    }
+    let (salt, rest) = bytes.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let plaintext = derive_cipher(password, salt)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong password or corrupted keystore".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(err("Invalid keystore"))
+}
+
+// ============================================================================
+// Node client
+// ============================================================================
+
+fn rpc_call(port: u16, method: &str, params: Value) -> CliResult<Value> {
+    let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+        .map_err(err("Cannot connect to the node"))?;
+    let mut writer = stream.try_clone().map_err(err("Connection error"))?;
+    let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 0 });
+    writeln!(writer, "{}", request).map_err(err("Cannot send request"))?;
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(err("Cannot read response"))?;
+    let mut response: Value = serde_json::from_str(&line).map_err(err("Invalid response"))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("Node error on {}: {}", method, error));
+    }
+    Ok(response["result"].take())
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(value: Value) -> CliResult<T> {
+    serde_json::from_value(value).map_err(err("Invalid node response"))
+}
+
+// ============================================================================
+// Wallet state
+// ============================================================================
+
+struct Wallet {
+    keystore: Keystore,
+    ak: AuthorizationKeyPair,
+    pgk: ProofGenerationKey,
+    ivk: IncomingViewingKey,
+    // diversified addresses of the wallet with their randomizers
+    addresses: Vec<(DiversifiedAddress, DiversifierRandomizer)>,
+}
+
+impl Wallet {
+    fn new(keystore: Keystore) -> CliResult<Self> {
+        let msk = keystore.master_key();
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let addresses = (0..ADDRESS_LOOKAHEAD)
+            .map(|j| msk.derive_diversified_address(&pgk, &ivk, j))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(err("Cannot derive addresses"))?;
+        Ok(Self {
+            keystore,
+            ak,
+            pgk,
+            ivk,
+            addresses,
+        })
+    }
+
+    // Scan the notes of the node, return the owned unspent records
+    fn unspent_records(&self, port: u16) -> CliResult<Vec<OwnedRecord>> {
+        let mut scanner = WalletScanner::new(&self.pgk, self.ivk.clone(), None);
+        for imported in self.keystore.imported_records.iter() {
+            scanner
+                .insert_record(imported.ro.clone(), imported.uid)
+                .map_err(err("Cannot track imported record"))?;
+        }
+        let notes: Vec<Value> = from_json(rpc_call(port, "get_notes", json!([0]))?)?;
+        for mut entry in notes {
+            let first_uid: u64 = from_json(entry["first_uid"].take())?;
+            let note: DPCTxnNote = from_json(entry["note"].take())?;
+            scanner
+                .scan_note(&note, first_uid)
+                .map_err(err("Cannot scan note"))?;
+        }
+        // imported records may have been spent by notes scanned before they
+        // were imported, double check with the node
+        let mut records = vec![];
+        for record in scanner.unspent_records() {
+            let spent: bool = from_json(rpc_call(port, "is_spent", json!([record.nullifier]))?)?;
+            if !spent {
+                records.push(record.clone());
+            }
+        }
+        Ok(records)
+    }
+
+    // Input note spending `owned`, against the current ledger root
+    fn note_input(&self, owned: &OwnedRecord, port: u16) -> CliResult<NoteInput> {
+        let witness = from_json(rpc_call(
+            port,
+            "get_membership_witness",
+            json!([owned.uid]),
+        )?)?;
+        Ok(NoteInput {
+            ro: owned.ro.clone(),
+            acc_member_witness: witness,
+            proof_gen_key: Cow::Borrowed(&self.pgk),
+            authorization_randomizer: Default::default(),
+            diversifier_randomizer: self.diversifier_randomizer(owned.ro.addr())?,
+        })
+    }
+
+    fn diversifier_randomizer(
+        &self,
+        addr: &DiversifiedAddress,
+    ) -> CliResult<DiversifierRandomizer> {
+        self.addresses
+            .iter()
+            .find(|(a, _)| a == addr)
+            .map(|(_, rd)| rd.clone())
+            .ok_or_else(|| "Record is not owned by any known address".to_string())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct AppInfo {
+    birth_pid: PolicyIdentifier,
+    death_pid: PolicyIdentifier,
+}
+
+fn load_app(path: &str) -> CliResult<AppInfo> {
+    serde_json::from_str(&fs::read_to_string(path).map_err(err("Cannot read app"))?)
+        .map_err(err("Invalid app"))
+}
+
+fn scalar_to_u64(x: &InnerScalarField) -> Option<u64> {
+    let bytes = x.into_repr().to_bytes_le();
+    if bytes[8..].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let mut le = [0u8; 8];
+    le.copy_from_slice(&bytes[..8]);
+    Some(u64::from_le_bytes(le))
+}
+
+// Native asset value of a record, `None` for other assets
+fn native_value(ro: &RecordOpening) -> Option<u64> {
+    let data = ro.payload().data();
+    if ro.payload().is_dummy || data[0] != NATIVE_ASSET_CODE {
+        return None;
+    }
+    scalar_to_u64(&data[1])
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+// SRS files are written uncompressed by `veri-zexe-ceremony finalize`, the
+// points are checked to be on the curve and in the prime order subgroup.
+fn load_srs<T: CanonicalDeserialize>(path: &str) -> CliResult<T> {
+    let bytes = fs::read(path).map_err(err("Cannot read SRS"))?;
+    T::deserialize_uncompressed(&bytes[..]).map_err(err("Invalid SRS"))
+}
+
+// Predicates of the transfer application for the record layout of `params`
+fn transfer_predicates(
+    inner_srs: &InnerUniversalParam,
+    params: &DPCParams,
+) -> CliResult<(ZcashPredicate, ZcashPredicate)> {
+    let birth_circuit =
+        ZcashPredicateCircuit::preprocessed_birth_circuit(TRANSFER_INPUT_SIZE, params)
+            .map_err(err("Cannot build birth circuit"))?;
+    let death_circuit =
+        ZcashPredicateCircuit::preprocessed_death_circuit(TRANSFER_INPUT_SIZE, params)
+            .map_err(err("Cannot build death circuit"))?;
+    let birth_predicate = Predicate::new(inner_srs, &birth_circuit.0, true)
+        .map_err(err("Cannot set up birth predicate"))?;
+    let death_predicate = Predicate::new(inner_srs, &death_circuit.0, false)
+        .map_err(err("Cannot set up death predicate"))?;
+    Ok((
+        ZcashPredicate::from(birth_predicate),
+        ZcashPredicate::from(death_predicate),
+    ))
+}
+
+fn cmd_setup(args: &Args) -> CliResult<()> {
+    let inner_srs: InnerUniversalParam = load_srs(args.required("--inner-srs")?)?;
+    let outer_srs: OuterUniversalParam = load_srs(args.required("--outer-srs")?)?;
+    let (dpc_pk, dpc_vk, _, birth_pid, _, death_pid) = ZcashPredicate::preprocess(
+        &inner_srs,
+        &outer_srs,
+        TRANSFER_INPUT_SIZE,
+        &DPCParams::default(),
+    )
+    .map_err(err("Cannot preprocess keys"))?;
+    let mut bytes = vec![];
+    dpc_pk
+        .serialize(&mut bytes)
+        .map_err(err("Cannot serialize proving key"))?;
+    fs::write(args.required("--proving-key")?, bytes).map_err(err("Cannot write proving key"))?;
+    let mut bytes = vec![];
+    dpc_vk
+        .serialize(&mut bytes)
+        .map_err(err("Cannot serialize verifying key"))?;
+    fs::write(args.required("--verifying-key")?, bytes)
+        .map_err(err("Cannot write verifying key"))?;
+    let app = AppInfo {
+        birth_pid,
+        death_pid,
+    };
+    let app = serde_json::to_string_pretty(&app).map_err(err("Cannot serialize app"))?;
+    fs::write(args.required("--app")?, app).map_err(err("Cannot write app"))
+}
+
+fn cmd_new(args: &Args) -> CliResult<()> {
+    let path = args.required("--keystore")?;
+    if Path::new(path).exists() {
+        return Err(format!("Keystore {} already exists", path));
+    }
+    let keystore = Keystore {
+        wallet_master_key: OsRng.gen(),
+        imported_records: vec![],
+    };
+    save_keystore(path, &keystore, &read_password()?)?;
+    let wallet = Wallet::new(keystore)?;
+    println!("{}", wallet.addresses[0].0);
+    Ok(())
+}
+
+fn cmd_address(args: &Args) -> CliResult<()> {
+    let wallet = Wallet::new(load_keystore(
+        args.required("--keystore")?,
+        &read_password()?,
+    )?)?;
+    let index: usize = args.parse_or("--index", 0)?;
+    let (addr, _) = wallet
+        .addresses
+        .get(index)
+        .ok_or_else(|| format!("Address index must be less than {}", ADDRESS_LOOKAHEAD))?;
+    println!("{}", addr);
+    Ok(())
+}
+
+fn cmd_genesis_record(args: &Args) -> CliResult<()> {
+    let wallet = Wallet::new(load_keystore(
+        args.required("--keystore")?,
+        &read_password()?,
+    )?)?;
+    let amount: u64 = args.parse("--amount")?;
+    let addr = wallet.addresses[0].0.clone();
+    let ro = match args.get("--app") {
+        None => {
+            RecordOpening::new_native_asset(&mut OsRng, addr, amount as u128, 0, Default::default())
+        },
+        Some(path) => {
+            let app = load_app(path)?;
+            let payload =
+                Payload::from_scalars(&[NATIVE_ASSET_CODE, InnerScalarField::from(amount)])
+                    .map_err(err("Cannot build payload"))?;
+            RecordOpening::new(
+                &mut OsRng,
+                addr,
+                payload,
+                app.birth_pid.to_scalar(),
+                app.death_pid.to_scalar(),
+                0,
+                Default::default(),
+            )
+        },
+    };
+    println!("{}", ro);
+    Ok(())
+}
+
+fn cmd_import(args: &Args) -> CliResult<()> {
+    let path = args.required("--keystore")?;
+    let password = read_password()?;
+    let mut keystore = load_keystore(path, &password)?;
+    let ro: RecordOpening = args.parse("--record")?;
+    let uid: u64 = args.parse("--uid")?;
+    keystore.imported_records.push(ImportedRecord { ro, uid });
+    save_keystore(path, &keystore, &password)
+}
+
+fn cmd_list(args: &Args) -> CliResult<()> {
+    let wallet = Wallet::new(load_keystore(
+        args.required("--keystore")?,
+        &read_password()?,
+    )?)?;
+    let port = args.parse_or("--port", DEFAULT_PORT)?;
+    let mut balance = 0u128;
+    for record in wallet.unspent_records(port)? {
+        let kind = if record.ro.pid_death() == PolicyIdentifier::default() {
+            "fee"
+        } else {
+            "app"
+        };
+        match native_value(&record.ro) {
+            Some(value) => {
+                balance += value as u128;
+                println!("uid {:>6}  {:>4}  {:>20}", record.uid, kind, value);
+            },
+            None => println!("uid {:>6}  {:>4}  non-native asset", record.uid, kind),
+        }
+    }
+    println!("native balance: {}", balance);
+    Ok(())
+}
+
+fn cmd_transfer(args: &Args) -> CliResult<()> {
+    let wallet = Wallet::new(load_keystore(
+        args.required("--keystore")?,
+        &read_password()?,
+    )?)?;
+    let port = args.parse_or("--port", DEFAULT_PORT)?;
+    let uid: u64 = args.parse("--uid")?;
+    let receiver: DiversifiedAddress = args.parse("--to")?;
+    let fee: u64 = args.parse("--fee")?;
+    let rng = &mut OsRng;
+
+    let bytes =
+        fs::read(args.required("--proving-key")?).map_err(err("Cannot read proving key"))?;
+    let dpc_pk = DPCProvingKey::deserialize(&bytes[..]).map_err(err("Invalid proving key"))?;
+    if dpc_pk.max_num_inputs() != TRANSFER_INPUT_SIZE {
+        return Err(format!(
+            "Proving key supports {} inputs, a transfer has {}",
+            dpc_pk.max_num_inputs(),
+            TRANSFER_INPUT_SIZE
+        ));
+    }
+    let inner_srs: InnerUniversalParam = load_srs(args.required("--inner-srs")?)?;
+    let (birth_predicate, death_predicate) = transfer_predicates(&inner_srs, dpc_pk.params())?;
+    let AppInfo {
+        birth_pid,
+        death_pid,
+    } = load_app(args.required("--app")?)?;
+
+    // select the spent record and a fee record
+    let unspent = wallet.unspent_records(port)?;
+    let record = unspent
+        .iter()
+        .find(|r| r.uid == uid)
+        .ok_or_else(|| format!("No owned unspent record with uid {}", uid))?;
+    if record.ro.pid_death() != death_pid {
+        return Err("Record is not spendable by the transfer application".to_string());
+    }
+    let value = native_value(&record.ro).ok_or("Record is not of native asset")?;
+    let fee_record = unspent
+        .iter()
+        .filter(|r| r.ro.pid_death() == PolicyIdentifier::default())
+        .find(|r| native_value(&r.ro).map_or(false, |v| v >= fee))
+        .ok_or("No fee record with enough value")?;
+
+    let fee_input = wallet.note_input(fee_record, port)?;
+    let record_input = wallet.note_input(record, port)?;
+
+    // fee change back to the wallet, full value of the record to the receiver
+    let payload = Payload::from_scalars_with_params(
+        &[NATIVE_ASSET_CODE, InnerScalarField::from(value)],
+        dpc_pk.params(),
+    )
+    .map_err(err("Cannot build payload"))?;
+    let note = TransactionBuilder::new(
+        &dpc_pk,
+        birth_predicate,
+        birth_pid,
+        death_predicate,
+        death_pid,
+    )
+    .fee_input(fee_input, &wallet.ak)
+    .add_input(record_input, &wallet.ak)
+    .add_recipient(receiver, payload, None)
+    .change_address(wallet.addresses[0].0.clone())
+    .fee(fee)
+    .build(rng)
+    .map_err(err("Cannot generate transaction"))?;
+
+    match args.get("--out") {
+        Some(path) => {
+            let note = serde_json::to_string(&note).map_err(err("Cannot serialize note"))?;
+            fs::write(path, note).map_err(err("Cannot write note"))
+        },
+        None => {
+            let uids = rpc_call(port, "submit_note", json!([note]))?;
+            println!("note applied, output uids: {}", uids);
+            Ok(())
+        },
+    }
+}
+
+// ============================================================================
+// Arguments
+// ============================================================================
+
+struct Args {
+    command: String,
+    options: BTreeMap<String, String>,
+}
+
+impl Args {
+    fn from_env() -> CliResult<Self> {
+        let mut args = env::args().skip(1);
+        let command = args.next().ok_or("Missing command")?;
+        let mut options = BTreeMap::new();
+        while let Some(key) = args.next() {
+            if !key.starts_with("--") {
+                return Err(format!("Unexpected argument {}", key));
+            }
+            let value = args.next().ok_or(format!("Missing value for {}", key))?;
+            options.insert(key, value);
+        }
+        Ok(Self { command, options })
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(String::as_str)
+    }
+
+    fn required(&self, key: &str) -> CliResult<&str> {
+        self.get(key).ok_or(format!("Missing {}", key))
+    }
+
+    fn parse<T: std::str::FromStr>(&self, key: &str) -> CliResult<T>
+    where
+        T::Err: ToString,
+    {
+        self.required(key)?
+            .parse()
+            .map_err(|e: T::Err| format!("Invalid {}: {}", key, e.to_string()))
+    }
+
+    fn parse_or<T: std::str::FromStr>(&self, key: &str, default: T) -> CliResult<T>
+    where
+        T::Err: ToString,
+    {
+        match self.get(key) {
+            Some(_) => self.parse(key),
+            None => Ok(default),
+        }
+    }
+}
+
+fn main() {
+    let result = Args::from_env().and_then(|args| match args.command.as_str() {
+        "setup" => cmd_setup(&args),
+        "new" => cmd_new(&args),
+        "address" => cmd_address(&args),
+        "genesis-record" => cmd_genesis_record(&args),
+        "import" => cmd_import(&args),
+        "list" => cmd_list(&args),
+        "transfer" => cmd_transfer(&args),
+        command => Err(format!("Unknown command {}", command)),
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -29,7 +29,7 @@ use jf_primitives::circuit::commitment::CommitmentGadget;
 /// - commitment
 /// The circuit constrains that the commitment is correct computed
 /// w.r.t. notes, records and memo, with the given blinding factor
-pub(crate) fn local_data_commitment_circuit(
+pub fn local_data_commitment_circuit(
     circuit: &mut PlonkCircuit<InnerScalarField>,
     entire_input_notes_vars: &[NoteInputVar],
     entire_output_records_vars: &[RecordOpeningVar],
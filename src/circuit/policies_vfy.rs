@@ -15,9 +15,11 @@ use crate::{
     errors::DPCApiError,
     proofs::policies_vfy::*,
     types::{
-        InnerBaseField, InnerG1Group, InnerPairingEngine, InnerScalarField, InnerUniversalParam,
+        InnerBaseField, InnerG1Affine, InnerG1Group, InnerPairingEngine, InnerScalarField,
+        InnerUniversalParam,
     },
 };
+use ark_ec::{AffineCurve, ProjectiveCurve};
 use ark_ff::{Field, PrimeField, Zero};
 use ark_std::{string::ToString, vec, vec::Vec};
 use jf_plonk::{
@@ -46,12 +48,38 @@ impl PoliciesVfyCircuit {
         srs: &InnerUniversalParam,
         num_input: usize,
         inner_policy_domain_size: usize,
+    ) -> Result<(Self, usize), DPCApiError> {
+        Self::build_dummy(
+            srs.powers_of_g_ref()[1],
+            srs.powers_of_g_ref()[0],
+            num_input,
+            inner_policy_domain_size,
+        )
+    }
+
+    /// Build the circuit `build_for_preprocessing` would build, without
+    /// access to the inner circuit SRS. The group elements of the SRS are only
+    /// constants of the circuit, so that the number of constraints is the same.
+    pub(crate) fn build_for_stats(
+        num_input: usize,
+        inner_policy_domain_size: usize,
+    ) -> Result<(Self, usize), DPCApiError> {
+        let generator_g = InnerG1Affine::prime_subgroup_generator();
+        let beta_g = generator_g.into_projective().double().into_affine();
+        Self::build_dummy(beta_g, generator_g, num_input, inner_policy_domain_size)
+    }
+
+    fn build_dummy(
+        beta_g: InnerG1Affine,
+        generator_g: InnerG1Affine,
+        num_input: usize,
+        inner_policy_domain_size: usize,
     ) -> Result<(Self, usize), DPCApiError> {
         let dummy_witness = PoliciesVfyWitness::dummy(num_input, inner_policy_domain_size);
         let comm_local_data = InnerScalarField::zero();
         let params = PoliciesVfyParams {
-            beta_g: srs.powers_of_g_ref()[1],
-            generator_g: srs.powers_of_g_ref()[0],
+            beta_g,
+            generator_g,
             m: NONNATIVE_FIELD_M,
             two_power_m: Some(InnerBaseField::from(2u8).pow(&[NONNATIVE_FIELD_M as u64])),
             range_bit_len: RANGE_BIT_LEN,
@@ -69,12 +97,12 @@ impl PoliciesVfyCircuit {
         params: &PoliciesVfyParams,
     ) -> Result<(Self, usize), DPCApiError> {
         if witness.input_death_vks.len() != witness.output_birth_vks.len() {
-            return Err(DPCApiError::InvalidParameters(
+            return Err(DPCApiError::InvalidParameter(
                 "input death predicates and output birth predicates length mismatch".to_string(),
             ));
         }
         if witness.input_death_vks.len() != witness.batch_proof.len() {
-            return Err(DPCApiError::InvalidParameters(
+            return Err(DPCApiError::InvalidParameter(
                 "the number of inputs/outputs mismatches the number of instances in batch proof"
                     .to_string(),
             ));
@@ -141,13 +169,6 @@ impl PoliciesVfyCircuit {
         circuit.point_equal_gate(&pub_input.partial_plonk_proof.0, &expected_partial_proof.0)?;
         circuit.point_equal_gate(&pub_input.partial_plonk_proof.1, &expected_partial_proof.1)?;
         let n_constraints = circuit.num_gates();
-        #[cfg(test)]
-        {
-            ark_std::println!(
-                "ℹ️ num_constraint of (unpadded) outer circuit: {}",
-                circuit.num_gates(),
-            );
-        }
         circuit.finalize_for_arithmetization()?;
         Ok((Self(circuit), n_constraints))
     }
@@ -397,11 +418,15 @@ pub(crate) mod tests {
         let srs = PlonkKzgSnark::<InnerPairingEngine>::universal_setup(max_degree, rng)?;
 
         for num_input in 2..5 {
-            let (preproc_cs, _) = PoliciesVfyCircuit::build_for_preprocessing(
+            let (preproc_cs, n_constraints) = PoliciesVfyCircuit::build_for_preprocessing(
                 &srs,
                 num_input,
                 INNER_DOMAIN_SIZE_FOR_TEST,
             )?;
+            let (stats_cs, stats_n_constraints) =
+                PoliciesVfyCircuit::build_for_stats(num_input, INNER_DOMAIN_SIZE_FOR_TEST)?;
+            assert_eq!(stats_n_constraints, n_constraints);
+            check_preprocessed_circuit_consistency(&preproc_cs, &stats_cs)?;
             let (witness, pub_input, params) =
                 build_policies_vfy_circuit_params(rng, &srs, num_input)?;
             let (cs, _) = PoliciesVfyCircuit::build(&witness, &pub_input, &params)?;
@@ -26,18 +26,26 @@ use jf_primitives::circuit::{
     commitment::CommitmentGadget, merkle_tree::AccMemberWitnessVar, prf::PrfGadget,
 };
 
+/// Circuit variables of a `RecordOpening`
 #[derive(Clone)]
-pub(crate) struct RecordOpeningVar {
-    pub(crate) addr: (Variable, PointVariable),
-    pub(crate) payload: PayloadVariable,
-    pub(crate) pid_birth: Variable,
-    pub(crate) pid_death: Variable,
-    pub(crate) nonce: Variable,
-    pub(crate) blinding: Variable,
+pub struct RecordOpeningVar {
+    /// diversifier and diversified public key of the owner address
+    pub addr: (Variable, PointVariable),
+    /// record payload
+    pub payload: PayloadVariable,
+    /// birth predicate identifier
+    pub pid_birth: Variable,
+    /// death predicate identifier
+    pub pid_death: Variable,
+    /// record nonce
+    pub nonce: Variable,
+    /// commitment blinding factor
+    pub blinding: Variable,
 }
 
 impl RecordOpeningVar {
-    pub(crate) fn new(
+    /// Create variables for the record opening `ro` in `circuit`
+    pub fn new(
         circuit: &mut PlonkCircuit<InnerScalarField>,
         ro: &RecordOpening,
     ) -> Result<Self, PlonkError> {
@@ -70,7 +78,8 @@ impl RecordOpeningVar {
         })
     }
 
-    pub(crate) fn derive_record_commitment_var(
+    /// Compute the record commitment in the circuit
+    pub fn derive_record_commitment_var(
         &self,
         circuit: &mut PlonkCircuit<InnerScalarField>,
     ) -> Result<Variable, PlonkError> {
@@ -97,21 +106,29 @@ impl RecordOpeningVar {
     }
 }
 
+/// Circuit variables of a record `Payload`
 #[derive(Clone)]
-pub(crate) struct PayloadVariable {
-    pub(crate) is_dummy: Variable,
-    pub(crate) data: Vec<Variable>,
+pub struct PayloadVariable {
+    /// boolean flag of dummy records
+    pub is_dummy: Variable,
+    /// payload data
+    pub data: Vec<Variable>,
 }
-pub(crate) struct NoteInputVar {
+
+/// Circuit variables of a `NoteInput`. Only the record opening is exposed to
+/// predicates, the spending key material is for the UTXO circuit.
+pub struct NoteInputVar {
     pub(crate) proof_generation_key_var: ProofGenerationKeyVar,
-    pub(crate) record_opening_var: RecordOpeningVar,
+    /// opening of the spent record
+    pub record_opening_var: RecordOpeningVar,
     pub(crate) acc_member_witness_var: AccMemberWitnessVar,
     pub(crate) authorization_randomizer_var: PointVariable,
     pub(crate) diversifier_randomizer_var: Variable,
 }
 
 impl NoteInputVar {
-    pub(crate) fn new(
+    /// Create variables for the note input `input` in `circuit`
+    pub fn new(
         circuit: &mut PlonkCircuit<InnerScalarField>,
         input: &NoteInput,
     ) -> Result<Self, PlonkError> {
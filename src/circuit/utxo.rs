@@ -11,9 +11,9 @@
 
 use crate::{
     circuit::structs::{NoteInputVar, RecordOpeningVar},
-    constants::{MEMO_LEN, NATIVE_ASSET_CODE},
     errors::DPCApiError,
     keys::KeyChainMasterKey,
+    params::DPCParams,
     proofs::utxo::{DPCUtxoPublicInput, DPCUtxoWitness},
     types::{InnerEmbeddedGroup, InnerScalarField},
 };
@@ -72,6 +72,7 @@ pub(crate) struct DPCUtxoPubInputVar {
     pub(crate) local_data_commitment: Variable,
     pub(crate) root: Variable,
     pub(crate) fee: Variable,
+    pub(crate) fee_asset: Variable,
     pub(crate) memo: Vec<Variable>,
     pub(crate) authorization_verification_key: PointVariable,
 }
@@ -97,6 +98,7 @@ impl DPCUtxoPubInputVar {
             circuit.create_public_variable(public_input.commitment_local_data)?;
         let root = circuit.create_public_variable(public_input.root.to_scalar())?;
         let fee = circuit.create_public_variable(InnerScalarField::from(public_input.fee))?;
+        let fee_asset = circuit.create_public_variable(public_input.fee_asset)?;
         let memo = public_input
             .memo
             .iter()
@@ -111,6 +113,7 @@ impl DPCUtxoPubInputVar {
             local_data_commitment,
             root,
             fee,
+            fee_asset,
             memo,
             authorization_verification_key,
         })
@@ -123,12 +126,15 @@ impl DPCUtxoCircuit {
     /// Build a pre-processed circuit for `non_fee_input_size` number of non-fee
     /// notes/records. The actual number of notes/records will be
     /// `non_fee_input_size + 1` where `1` comes from the fees.
-    pub(crate) fn build_for_preprocessing(non_fee_input_size: usize) -> Result<Self, DPCApiError> {
-        let memo = vec![InnerScalarField::zero(); MEMO_LEN];
+    pub(crate) fn build_for_preprocessing(
+        non_fee_input_size: usize,
+        params: &DPCParams,
+    ) -> Result<(Self, usize), DPCApiError> {
+        let memo = vec![InnerScalarField::zero(); params.memo_len];
         let wallet_key = [0u8; 32];
         let msk = KeyChainMasterKey::generate(wallet_key, &[]);
         let (_, pgk, _) = msk.derive_key_chain_single_consumer();
-        let dummy_witness = DPCUtxoWitness::dummy(non_fee_input_size, &pgk);
+        let dummy_witness = DPCUtxoWitness::dummy(non_fee_input_size, &pgk, params);
         let pub_input = DPCUtxoPublicInput::from_witness(&dummy_witness, 0, memo)?;
         Self::build(&dummy_witness, &pub_input)
         .map_err(|_| DPCApiError::InternalError(format!(
@@ -137,17 +143,18 @@ impl DPCUtxoCircuit {
             )))
     }
 
+    /// Build the circuit and return it with its number of constraints before
+    /// padding.
     pub(crate) fn build(
         witness: &DPCUtxoWitness,
         public_input: &DPCUtxoPublicInput,
-    ) -> Result<Self, PlonkError> {
+    ) -> Result<(Self, usize), PlonkError> {
         let mut circuit = PlonkCircuit::default();
         let witness_var = DPCUtxoWitnessVar::new(&mut circuit, witness)?;
         let public_input_var = DPCUtxoPubInputVar::new(&mut circuit, public_input)?;
 
         let mut compressed_local_data = vec![];
         let mut derived_authorization_key = circuit.neutral_point_variable();
-        let native_asset_type_var = circuit.create_constant_variable(NATIVE_ASSET_CODE)?;
         let mut is_fee_ro = true;
         // check input
         for (input, input_nullifier) in witness_var
@@ -161,7 +168,7 @@ impl DPCUtxoCircuit {
                 *input_nullifier,
                 public_input_var.root,
                 is_fee_ro,
-                native_asset_type_var,
+                public_input_var.fee_asset,
             )?;
             is_fee_ro = false;
 
@@ -191,7 +198,7 @@ impl DPCUtxoCircuit {
                 output_ro,
                 *output_rc,
                 is_fee_chg_ro,
-                native_asset_type_var,
+                public_input_var.fee_asset,
                 i,
                 public_input_var.nullifiers[0],
             )?;
@@ -243,15 +250,9 @@ impl DPCUtxoCircuit {
                 public_input_var.predicates_commitment,
             )?;
         }
-        #[cfg(test)]
-        {
-            ark_std::println!(
-                "ℹ️ num_constraint of (unpadded) UTXO circuit: {}",
-                circuit.num_gates(),
-            );
-        }
+        let n_constraints = circuit.num_gates();
         circuit.finalize_for_arithmetization()?;
-        Ok(DPCUtxoCircuit(circuit))
+        Ok((DPCUtxoCircuit(circuit), n_constraints))
     }
 
     fn prove_spend(
@@ -260,7 +261,7 @@ impl DPCUtxoCircuit {
         public_nullifier: Variable,
         public_root: Variable,
         is_fee_input: bool,
-        native_asset_type: Variable,
+        fee_asset: Variable,
     ) -> Result<(Variable, PointVariable), PlonkError> {
         circuit.bool_gate(input.record_opening_var.payload.is_dummy)?;
         let is_dummy = input.record_opening_var.payload.is_dummy;
@@ -278,10 +279,12 @@ impl DPCUtxoCircuit {
         )?;
 
         // check record is dummy or commitment is accumulated
-        // but first record must be the fee input and cannot be dummy
+        // but first record must be the fee input and cannot be dummy, it is of
+        // the fee asset and has no death predicate since predicates skip it
         if is_fee_input {
             circuit.equal_gate(is_dummy, circuit.zero())?;
-            circuit.equal_gate(input.record_opening_var.payload.data[0], native_asset_type)?;
+            circuit.equal_gate(input.record_opening_var.payload.data[0], fee_asset)?;
+            circuit.equal_gate(input.record_opening_var.pid_death, circuit.zero())?;
         }
         let is_in_acc = circuit.check_equal(derived_root, public_root)?;
         circuit.logic_or_gate(is_dummy, is_in_acc)?;
@@ -315,7 +318,7 @@ impl DPCUtxoCircuit {
         output: &RecordOpeningVar,
         output_rc_var: Variable,
         is_fee_chg: bool,
-        native_asset_type: Variable,
+        fee_asset: Variable,
         position_in_note: usize,
         first_nullifier: Variable,
     ) -> Result<(), PlonkError> {
@@ -333,7 +336,9 @@ impl DPCUtxoCircuit {
 
         if is_fee_chg {
             circuit.equal_gate(output.payload.is_dummy, circuit.zero())?;
-            circuit.equal_gate(output.payload.data[0], native_asset_type)?;
+            circuit.equal_gate(output.payload.data[0], fee_asset)?;
+            circuit.equal_gate(output.pid_birth, circuit.zero())?;
+            circuit.equal_gate(output.pid_death, circuit.zero())?;
         }
 
         Ok(())
@@ -348,13 +353,18 @@ mod test {
             utxo::DPCUtxoCircuit,
         },
         constants::NATIVE_ASSET_CODE,
-        keys::KeyChainMasterKey,
+        keys::{
+            run_multi_consumer_key_exchange, DiversifierRandomizer, KeyChainMasterKey,
+            ProofGenerationKey,
+        },
+        params::DPCParams,
         proofs::utxo::{DPCUtxoPublicInput, DPCUtxoWitness},
         structs::{NoteInput, Nullifier, Payload, RecordOpening},
         types::InnerScalarField,
     };
     use ark_ff::{One, UniformRand, Zero};
     use ark_std::{
+        borrow::Cow,
         rand::{CryptoRng, Rng, RngCore},
         vec,
     };
@@ -411,6 +421,95 @@ mod test {
         do_spend_test(rng, &msk, &ro, false, true, false, &mut merkle_tree);
     }
 
+    #[test]
+    fn test_spend_multi_consumer() {
+        let rng = &mut ark_std::test_rng();
+        let mut merkle_tree = MerkleTree::new(crate::constants::TREE_DEPTH).unwrap();
+        let owner_msk = KeyChainMasterKey::generate([0u8; 32], b"owner");
+        let freezer_msk = KeyChainMasterKey::generate([1u8; 32], b"freezer");
+        let nks =
+            run_multi_consumer_key_exchange(&[owner_msk.clone(), freezer_msk.clone()], b"app")
+                .unwrap();
+        let (_ask, pgk, ivk) = owner_msk.derive_key_chain_multi_consumer(nks[0].clone());
+        let (address, diversifier) = owner_msk.derive_diversified_address(&pgk, &ivk, 0).unwrap();
+        let ro = RecordOpening {
+            addr: address,
+            payload: Default::default(),
+            pid_birth: InnerScalarField::rand(rng),
+            pid_death: InnerScalarField::rand(rng),
+            nonce: InnerScalarField::rand(rng),
+            blinding: InnerScalarField::rand(rng),
+        };
+        // the freezer derives the same nullifier deriving key from its own
+        // key-chain, and nullifies the record exactly like the owner
+        let (_ask, freezer_pgk, _ivk) = freezer_msk.derive_key_chain_multi_consumer(nks[1].clone());
+        assert_ne!(pgk.ak, freezer_pgk.ak);
+        assert_eq!(pgk.nk, freezer_pgk.nk);
+        assert_eq!(
+            ro.nullify(&pgk.nk).unwrap(),
+            ro.nullify(&freezer_pgk.nk).unwrap()
+        );
+        do_spend_test_with_keys(
+            rng,
+            &pgk,
+            &diversifier,
+            &ro,
+            false,
+            true,
+            false,
+            &mut merkle_tree,
+        );
+        do_spend_test_with_keys(
+            rng,
+            &pgk,
+            &diversifier,
+            &ro,
+            true,
+            false,
+            false,
+            &mut merkle_tree,
+        );
+        // the freezer spends with the owner's public authorization key and
+        // its own copy of the nullifier deriving key
+        let freezer_spend_pgk = ProofGenerationKey {
+            ak: pgk.ak.clone(),
+            nk: freezer_pgk.nk,
+        };
+        do_spend_test_with_keys(
+            rng,
+            &freezer_spend_pgk,
+            &diversifier,
+            &ro,
+            false,
+            true,
+            false,
+            &mut merkle_tree,
+        );
+
+        // a key-chain that did not take part in the key exchange cannot spend
+        let outsider_msk = KeyChainMasterKey::generate([2u8; 32], b"outsider");
+        let (_ask, outsider_pgk, _ivk) = outsider_msk.derive_key_chain_single_consumer();
+        assert_ne!(pgk.nk, outsider_pgk.nk);
+        assert_ne!(
+            ro.nullify(&pgk.nk).unwrap(),
+            ro.nullify(&outsider_pgk.nk).unwrap()
+        );
+        let outsider_spend_pgk = ProofGenerationKey {
+            ak: pgk.ak.clone(),
+            nk: outsider_pgk.nk,
+        };
+        do_spend_test_with_keys(
+            rng,
+            &outsider_spend_pgk,
+            &diversifier,
+            &ro,
+            false,
+            false,
+            false,
+            &mut merkle_tree,
+        );
+    }
+
     fn do_spend_test<R: CryptoRng + RngCore>(
         rng: &mut R,
         msk: &KeyChainMasterKey,
@@ -423,6 +522,29 @@ mod test {
         // recompute addresses and keys from msk to avoid more parameters
         let (_ask, pgk, ivk) = msk.derive_key_chain_single_consumer();
         let (_address, diversifier) = msk.derive_diversified_address(&pgk, &ivk, 0).unwrap();
+        do_spend_test_with_keys(
+            rng,
+            &pgk,
+            &diversifier,
+            ro,
+            bad_root,
+            should_pass,
+            is_fee,
+            merkle_tree,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn do_spend_test_with_keys<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        pgk: &ProofGenerationKey,
+        diversifier: &DiversifierRandomizer,
+        ro: &RecordOpening,
+        bad_root: bool,
+        should_pass: bool,
+        is_fee: bool,
+        merkle_tree: &mut MerkleTree<InnerScalarField>,
+    ) {
         let nullifier = ro.nullify(&pgk.nk).unwrap();
         let rc = ro.derive_record_commitment().unwrap();
         merkle_tree.push(rc);
@@ -436,7 +558,7 @@ mod test {
         let input = NoteInput {
             ro: ro.clone(),
             acc_member_witness: acc_witness.clone(),
-            proof_gen_key: &pgk,
+            proof_gen_key: Cow::Borrowed(pgk),
             authorization_randomizer: Default::default(),
             diversifier_randomizer: diversifier.clone(),
         };
@@ -518,6 +640,24 @@ mod test {
         do_test_prove_output(&ro, position_in_note, first_nullifier, false, true);
         do_test_prove_output(&ro, position_in_note, first_nullifier, true, true);
 
+        // fee change cannot have predicates
+        let mut ro_with_predicate = ro.clone();
+        ro_with_predicate.pid_birth = InnerScalarField::rand(rng);
+        do_test_prove_output(
+            &ro_with_predicate,
+            position_in_note,
+            first_nullifier,
+            false,
+            true,
+        );
+        do_test_prove_output(
+            &ro_with_predicate,
+            position_in_note,
+            first_nullifier,
+            true,
+            false,
+        );
+
         ro.payload.data[0] = InnerScalarField::rand(rng); // not native asset for fee input should fail
         do_test_prove_output(&ro, position_in_note, first_nullifier, true, false);
 
@@ -562,6 +702,12 @@ mod test {
 
     #[test]
     fn test_circuit_build() {
+        // fees are paid in the native asset or in any other asset
+        do_test_circuit_build(NATIVE_ASSET_CODE);
+        do_test_circuit_build(InnerScalarField::from(2u64));
+    }
+
+    fn do_test_circuit_build(fee_asset: InnerScalarField) {
         let rng = &mut ark_std::test_rng();
         let fee = 10;
         let mut seed = [0u8; 32];
@@ -575,12 +721,14 @@ mod test {
             msk.derive_diversified_address(&pgk, &ivk, 1).unwrap();
         let (addr_dest, _) = msk.derive_diversified_address(&pgk, &ivk, 2).unwrap();
 
-        let ro_fee = RecordOpening::new_native_asset(
+        let ro_fee = RecordOpening::new_fee_asset_with_params(
             rng,
             addr_fee_input.clone(),
+            fee_asset,
             15,
             0,
             Nullifier(InnerScalarField::zero()),
+            &DPCParams::default(),
         );
         let rc_fee = ro_fee.derive_record_commitment().unwrap();
         let ro_source = RecordOpening::new(
@@ -603,7 +751,7 @@ mod test {
         let fee_input = NoteInput {
             ro: ro_fee.clone(),
             acc_member_witness: fee_acc_witness,
-            proof_gen_key: &pgk,
+            proof_gen_key: Cow::Borrowed(&pgk),
             authorization_randomizer: Default::default(),
             diversifier_randomizer: diversifier_fee_input.clone(),
         };
@@ -614,7 +762,7 @@ mod test {
         let source_input = NoteInput {
             ro: ro_source,
             acc_member_witness: acc_witness,
-            proof_gen_key: &pgk,
+            proof_gen_key: Cow::Borrowed(&pgk),
             authorization_randomizer: Default::default(),
             diversifier_randomizer: diversifier_source.clone(),
         };
@@ -624,8 +772,15 @@ mod test {
         let inputs = vec![fee_input, source_input, dummy_input];
 
         let first_nullifier = ro_fee.nullify(&pgk.nk).unwrap();
-        let ro_fee_chng =
-            RecordOpening::new_native_asset(rng, addr_fee_input, 5, 0, first_nullifier.clone());
+        let ro_fee_chng = RecordOpening::new_fee_asset_with_params(
+            rng,
+            addr_fee_input,
+            fee_asset,
+            5,
+            0,
+            first_nullifier.clone(),
+            &DPCParams::default(),
+        );
         let ro_dest1 = RecordOpening::new(
             rng,
             addr_source,
@@ -651,7 +806,8 @@ mod test {
         let witness = DPCUtxoWitness::new_unchecked(rng, inputs, outputs, blinding_local_data);
         let public_input = DPCUtxoPublicInput::from_witness(&witness, fee, vec![]).unwrap();
 
-        let circuit = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        assert_eq!(public_input.fee_asset, fee_asset);
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
 
         circuit
             .0
@@ -662,7 +818,7 @@ mod test {
         // 1. larger fee
         let bad_fee = 11;
         let public_input = DPCUtxoPublicInput::from_witness(&witness, bad_fee, vec![]).unwrap();
-        let circuit = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
         assert!(circuit
             .0
             .check_circuit_satisfiability(&public_input.to_scalars())
@@ -671,7 +827,7 @@ mod test {
         // 2.  bad authorization verification key
         let mut public_input = DPCUtxoPublicInput::from_witness(&witness, fee, vec![]).unwrap();
         public_input.authorization_verification_key = Default::default();
-        let circuit = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
         assert!(circuit
             .0
             .check_circuit_satisfiability(&public_input.to_scalars())
@@ -680,7 +836,7 @@ mod test {
         // 3.  bad commitment local data
         let mut public_input = DPCUtxoPublicInput::from_witness(&witness, fee, vec![]).unwrap();
         public_input.commitment_local_data = Default::default();
-        let circuit = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
         assert!(circuit
             .0
             .check_circuit_satisfiability(&public_input.to_scalars())
@@ -689,7 +845,16 @@ mod test {
         // 4.  bad commitment predicates
         let mut public_input = DPCUtxoPublicInput::from_witness(&witness, fee, vec![]).unwrap();
         public_input.commitment_predicates = Default::default();
-        let circuit = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        assert!(circuit
+            .0
+            .check_circuit_satisfiability(&public_input.to_scalars())
+            .is_err());
+
+        // 5.  fee asset other than that of the fee records
+        let mut public_input = DPCUtxoPublicInput::from_witness(&witness, fee, vec![]).unwrap();
+        public_input.fee_asset += InnerScalarField::one();
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
         assert!(circuit
             .0
             .check_circuit_satisfiability(&public_input.to_scalars())
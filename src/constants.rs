@@ -20,6 +20,11 @@ pub(crate) mod dom_sep {
     pub const NULLIFYING_KEY_DOM_SEP: &str = "DPC Key-Chain Nullifiying Deriving Key";
     pub const DIVERSIFIER_RAND_DOM_SEP: &str = "DPC Key-Chain Diversifier Randomizer";
     pub const INCOMING_VIEWING_KEY_DOM_SEP: &str = "DPC Key-Chain Incoming Viewing Key";
+    pub const MULTI_CONSUMER_KDH_SECRET_DOM_SEP: &str = "DPC Key-Chain Multi-Consumer k-DH Secret";
+    pub const MULTI_CONSUMER_NULLIFYING_KEY_DOM_SEP: &str =
+        "DPC Key-Chain Multi-Consumer Nullifiying Deriving Key";
+    pub const BATCH_DECIDE_DOM_SEP: &str = "DPC Inner Partial Verification Batch Decide";
+    pub const ASSET_DESCRIPTION_DOM_SEP: &str = "DPC Asset Description";
     // pub const PARTICIPATION_KEY_DOM_SEP: &str = "DPC Key-Chain Participation
     // Key";
 }
@@ -38,11 +43,11 @@ pub(crate) const NONNATIVE_FIELD_M: usize = 128;
 /// Native asset code
 pub const NATIVE_ASSET_CODE: InnerScalarField = ark_ff::field_new!(InnerScalarField, "1");
 
-/// RecordsCommitment merkle tree depth
+/// Default RecordsCommitment merkle tree depth, see `DPCParams`
 pub const TREE_DEPTH: u8 = 26;
 
-/// Length of payload data
+/// Default length of payload data, see `DPCParams`
 pub const PAYLOAD_DATA_LEN: usize = 8;
 
-/// Length of transaction MEMO  in InnerFieldElements
+/// Default length of transaction MEMO in InnerFieldElements, see `DPCParams`
 pub const MEMO_LEN: usize = 8;
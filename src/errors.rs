@@ -11,7 +11,12 @@
 
 //! Error types related to DPC
 
-use ark_std::{convert::From, format, string::String};
+use crate::{
+    params::KeyShape,
+    structs::PolicyIdentifier,
+    types::{InnerScalarField, NodeValue},
+};
+use ark_std::{convert::From, format, string::String, vec::Vec};
 use displaydoc::Display;
 use jf_plonk::errors::PlonkError;
 use jf_primitives::errors::PrimitivesError;
@@ -29,14 +34,14 @@ pub enum DPCApiError {
     OverOrUnderFlow(String),
     /// General error: {0}.
     GeneralError(String),
-    /// Invalid parameters: {0}.
-    InvalidParameters(String),
     /// Failed ReceiverMemo Signature: {0}
     FailedReceiverMemoSignature(PrimitivesError),
-    /// Failed Authorization Signature: {0}
-    FailedAuthorizationSignature(PrimitivesError),
-    /// Failed Authorization Signature: {0}
-    FailedTransactionVerification(String),
+    /// Failed transaction verification: {0}
+    FailedTransactionVerification(TxnVerificationError),
+    /// Invalid transaction parameters: {0}
+    InvalidTransactionParameter(TxnParameterError),
+    /// Batch verification failed for transaction notes at indices: {0:?}
+    FailedBatchVerification(Vec<usize>),
     /// I/O failure: {0}
     IoError(String),
     /// Invalid parameters: {0}
@@ -52,6 +57,169 @@ pub enum DPCApiError {
     InternalError(String),
 }
 
+/// Reasons for a transaction note to fail verification
+#[derive(Display, Debug, Clone, PartialEq, Eq)]
+pub enum TxnVerificationError {
+    /// Merkle root of the note {actual:?} does not match the expected root {expected:?}
+    WrongMerkleRoot {
+        /// Root the note is verified against
+        expected: NodeValue,
+        /// Root of the note
+        actual: NodeValue,
+    },
+    /// Merkle root of the note {0:?} is not among the recent ledger roots
+    UnknownMerkleRoot(NodeValue),
+    /// Note of shape {actual:?} cannot be verified with a key of shape {expected:?}
+    WrongShape {
+        /// Shape of the verifying key
+        expected: KeyShape,
+        /// Shape of the note
+        actual: KeyShape,
+    },
+    /// Wrong number of output commitments, expected {expected}, actual {actual}
+    WrongNumOutputs {
+        /// Number of outputs of the verifying key
+        expected: usize,
+        /// Number of outputs of the note
+        actual: usize,
+    },
+    /// Number of receiver memos {actual} does not match number of outputs {expected}
+    WrongNumReceiverMemos {
+        /// Number of outputs of the note
+        expected: usize,
+        /// Number of receiver memos of the note
+        actual: usize,
+    },
+    /// Duplicate nullifier at input {0}
+    DuplicateNullifier(usize),
+    /// Double spending: nullifier of input {0} already published
+    DoubleSpend(usize),
+    /// Fee is paid in asset {0}, which is not approved by the ledger
+    UnapprovedFeeAsset(InnerScalarField),
+    /// Memo time {time} is after the current time {current_time} of the ledger
+    FutureMemoTime {
+        /// Time stated in the memo of the note
+        time: InnerScalarField,
+        /// Current time of the ledger
+        current_time: u64,
+    },
+    /// Invalid authorization signature
+    InvalidAuthorizationSignature,
+    /// Invalid UTXO proof
+    InvalidUtxoProof,
+    /// Invalid outer policies verification proof
+    InvalidOuterProof,
+    /// Inner partial verification proof does not pass the final pairing check
+    FailedPairingCheck,
+}
+
+/// Reasons for transaction parameters to be rejected before proof generation.
+/// Inputs and outputs are numbered among all of them, the fee input and the
+/// fee change being number 0.
+#[derive(Display, Debug, Clone, PartialEq, Eq)]
+pub enum TxnParameterError {
+    /// Transaction has no input
+    NoInput,
+    /// Transaction has no output
+    NoOutput,
+    /// Wrong number of inputs, expected {expected}, actual {actual}
+    WrongNumInputs {
+        /// Number of inputs of the proving key
+        expected: usize,
+        /// Number of inputs of the transaction
+        actual: usize,
+    },
+    /// Wrong number of outputs, expected {expected}, actual {actual}
+    WrongNumOutputs {
+        /// Number of outputs of the proving key
+        expected: usize,
+        /// Number of outputs of the transaction
+        actual: usize,
+    },
+    /// Number of inputs {inputs} does not match number of outputs {outputs}
+    InputsOutputsMismatch {
+        /// Number of inputs of the transaction
+        inputs: usize,
+        /// Number of outputs of the transaction
+        outputs: usize,
+    },
+    /// Wrong memo length, expected {expected}, actual {actual}
+    WrongMemoLength {
+        /// Memo length of the parameters
+        expected: usize,
+        /// Memo length of the transaction
+        actual: usize,
+    },
+    /// Wrong payload length for input {input}, expected {expected}, actual {actual}
+    WrongInputPayloadLength {
+        /// Input number
+        input: usize,
+        /// Payload length of the parameters
+        expected: usize,
+        /// Payload length of the input
+        actual: usize,
+    },
+    /// Wrong payload length for output {output}, expected {expected}, actual {actual}
+    WrongOutputPayloadLength {
+        /// Output number
+        output: usize,
+        /// Payload length of the parameters
+        expected: usize,
+        /// Payload length of the output
+        actual: usize,
+    },
+    /// Fee input is dummy
+    DummyFeeInput,
+    /// Fee input payload is not an asset code and an amount followed by zeros
+    WrongFeeInputPayload,
+    /// Fee input has a death predicate, but fee records are exempt from predicates
+    WrongFeeInputPolicy,
+    /// Fee change is dummy
+    DummyFeeChange,
+    /// Fee change payload is not an asset code and an amount followed by zeros
+    WrongFeeChangePayload,
+    /// Fee change has a predicate, but fee records are exempt from predicates
+    WrongFeeChangePolicy,
+    /// Fee change is not of the asset type of the fee input
+    WrongFeeChangeAsset,
+    /// Fee input and fee change do not differ by the fee {0}
+    WrongFee(u64),
+    /// Input {0} must have a dummy membership witness if and only if it is dummy
+    WrongInputWitness(usize),
+    /// Membership witness of input {0} is not for the same root as the fee input
+    WrongInputRoot(usize),
+    /// Wrong death predicate for input {input}, expected {expected:?}, actual {actual:?}
+    WrongInputDeathPredicate {
+        /// Input number
+        input: usize,
+        /// Identifier of the death predicate of the transaction
+        expected: PolicyIdentifier,
+        /// Identifier of the death predicate of the input
+        actual: PolicyIdentifier,
+    },
+    /// Wrong birth predicate for output {output}, expected {expected:?}, actual {actual:?}
+    WrongOutputBirthPredicate {
+        /// Output number
+        output: usize,
+        /// Identifier of the birth predicate of the transaction
+        expected: PolicyIdentifier,
+        /// Identifier of the birth predicate of the output
+        actual: PolicyIdentifier,
+    },
+}
+
+impl From<TxnVerificationError> for DPCApiError {
+    fn from(e: TxnVerificationError) -> Self {
+        DPCApiError::FailedTransactionVerification(e)
+    }
+}
+
+impl From<TxnParameterError> for DPCApiError {
+    fn from(e: TxnParameterError) -> Self {
+        DPCApiError::InvalidTransactionParameter(e)
+    }
+}
+
 impl From<PrimitivesError> for DPCApiError {
     fn from(e: PrimitivesError) -> Self {
         DPCApiError::FailedPrimitives(e)
@@ -0,0 +1,695 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! This module implements time-locked records, which cannot be spent before
+//! a given ledger height or timestamp.
+//!
+//! Records of the application have payloads formatted as
+//! `[ asset_id | asset_value | lock_time | 0 | ... | 0 ]`, and transactions
+//! spending them state the current time in the first element of their memo.
+//! The memo is public and bound to the proof via the local data commitment.
+//! Ledgers accepting notes of the application enable
+//! `LedgerState::set_memo_time_check`, so that `LedgerState::validate_note`
+//! rejects notes whose memo time is after the current time of the ledger, see
+//! `ledger::check_memo_time`.
+//!
+//! As in the Zcash example, we implement the following traits
+//! - BirthPredicateCircuit
+//! - DeathPredicateCircuit
+//! - PredicateOps
+//!
+//! and the predicates argue for the following knowledge
+//!
+//! - a death predicate that checks:
+//!     - the values are correctly committed via the `common_local_data`
+//!     - the lock time of every non-dummy input record is at most the time in
+//!       the memo, which is less than 2^LOCK_TIME_BIT_LEN
+//!
+//! - a birth predicate that checks:
+//!     - the values are correctly committed via the `common_local_data`
+//!     - the sum of input records' value matches the sum of output records'
+//!       value
+//!     - all non-dummy input/output records shares a same asset id
+//!     - the lock time of every non-dummy output record is less than
+//!       2^LOCK_TIME_BIT_LEN, so that it cannot wrap around at spending
+//!
+//! The lock time of the output records is freely chosen by the sender.
+
+use crate::{
+    app::{
+        local_data_commitment_circuit, BirthPredicateCircuit, DeathPredicateCircuit, NoteInputVar,
+        PredicateOps, RecordOpeningVar,
+    },
+    errors::DPCApiError,
+    params::DPCParams,
+    predicates::PredicateTrait,
+    proofs::{
+        predicates::{Predicate, PredicateCircuit},
+        transaction::{DPCProvingKey, DPCVerifyingKey},
+    },
+    structs::{NoteInput, PolicyIdentifier, RecordOpening},
+    types::{InnerScalarField, InnerUniversalParam, OuterUniversalParam},
+};
+use ark_std::{format, string::ToString, vec::Vec, Zero};
+use jf_plonk::circuit::{Arithmetization, Circuit, PlonkCircuit};
+
+/// Bit length of lock times and of the time stated in the memo
+pub const LOCK_TIME_BIT_LEN: usize = 32;
+
+// Time-locked records hold the asset id, the value and the lock time.
+fn check_payload_data_len(payload_data_len: usize) -> Result<(), DPCApiError> {
+    if payload_data_len < 3 {
+        return Err(DPCApiError::InvalidParameter(format!(
+            "Payload data length {} is too short for time-locked records, at least 3 are needed",
+            payload_data_len
+        )));
+    }
+    Ok(())
+}
+
+/// A simple wrapper of predicate circuit
+pub struct TimeLockPredicateCircuit(pub PredicateCircuit);
+
+impl From<PredicateCircuit> for TimeLockPredicateCircuit {
+    fn from(circuit: PredicateCircuit) -> Self {
+        Self(circuit)
+    }
+}
+
+/// A simple wrapper of predicate
+pub struct TimeLockPredicate<'a>(pub Predicate<'a>);
+
+impl<'a> From<Predicate<'a>> for TimeLockPredicate<'a> {
+    fn from(predicate: Predicate<'a>) -> Self {
+        Self(predicate)
+    }
+}
+
+impl BirthPredicateCircuit for TimeLockPredicateCircuit {
+    // Our code requires that #gates in a birth circuit to be greater
+    // than that of a death circuit. If birth circuit has smaller size,
+    // we need to pad the birth circuit to make it larger.
+    //
+    // Our death circuit performs a range check per input which will
+    // not exceed 1024 constraints
+    const PAD_GATES: usize = 1024;
+
+    // Same as the default birth circuit, which argues
+    // 1. all the inputs are correctly w.r.t. commitment
+    // 2. all asset_ids match, dummy records have no value
+    // 3. sum inputs = sum outputs
+    // and in addition
+    // 4. lock times of non-dummy outputs are less than 2^LOCK_TIME_BIT_LEN
+    fn gen_birth_circuit_core(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        for output in entire_output_records.iter() {
+            check_payload_data_len(output.payload.data.len())?;
+        }
+        let mut birth_circuit = PlonkCircuit::new_turbo_plonk();
+
+        // build all the variables
+        let comm_local_data_var = birth_circuit.create_public_variable(comm_local_data)?;
+        let blinding_local_data_var = birth_circuit.create_variable(blinding_local_data)?;
+
+        let entire_input_notes_vars = entire_input_notes
+            .iter()
+            .map(|x| NoteInputVar::new(&mut birth_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entire_outputs_vars = entire_output_records
+            .iter()
+            .map(|x| RecordOpeningVar::new(&mut birth_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let memo_vars = memo
+            .iter()
+            .map(|x| birth_circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 1. argue that the local data is correct w.r.t. to the commitment of local
+        // data
+        local_data_commitment_circuit(
+            &mut birth_circuit,
+            &entire_input_notes_vars,
+            &entire_outputs_vars,
+            &memo_vars,
+            &blinding_local_data_var,
+            &comm_local_data_var,
+        )?;
+
+        // 2. all asset_ids of non-dummy records match, and dummy records carry no
+        // value
+        let asset_id = entire_input_notes
+            .iter()
+            .skip(1)
+            .map(|note| &note.ro.payload)
+            .chain(entire_output_records.iter().skip(1).map(|ro| &ro.payload))
+            .find(|payload| !payload.is_dummy)
+            .map_or_else(InnerScalarField::zero, |payload| payload.data[0]);
+        let asset_id_var = birth_circuit.create_variable(asset_id)?;
+        let zero_var = birth_circuit.zero();
+        for payload in entire_input_notes_vars
+            .iter()
+            .skip(1)
+            .map(|note| &note.record_opening_var.payload)
+            .chain(
+                entire_outputs_vars
+                    .iter()
+                    .skip(1)
+                    .map(|record| &record.payload),
+            )
+        {
+            let is_not_dummy = birth_circuit.check_is_zero(payload.is_dummy)?;
+            let is_dummy = birth_circuit.logic_neg(is_not_dummy)?;
+            let asset_id_diff = birth_circuit.sub(payload.data[0], asset_id_var)?;
+            birth_circuit.mul_gate(asset_id_diff, is_not_dummy, zero_var)?;
+            birth_circuit.mul_gate(payload.data[1], is_dummy, zero_var)?;
+        }
+
+        // 3. sum inputs = sum outputs
+        let mut sum_input_var = entire_input_notes_vars[1].record_opening_var.payload.data[1];
+        for note in entire_input_notes_vars.iter().skip(2) {
+            sum_input_var =
+                birth_circuit.add(sum_input_var, note.record_opening_var.payload.data[1])?;
+        }
+        let mut sum_output_var = entire_outputs_vars[1].payload.data[1];
+        for record in entire_outputs_vars.iter().skip(2) {
+            sum_output_var = birth_circuit.add(sum_output_var, record.payload.data[1])?;
+        }
+        birth_circuit.equal_gate(sum_input_var, sum_output_var)?;
+
+        // 4. a lock time close to the field modulus would pass the death
+        // predicate range check on time - lock_time at any time
+        for record in entire_outputs_vars.iter().skip(1) {
+            let is_not_dummy = birth_circuit.check_is_zero(record.payload.is_dummy)?;
+            let lock_time_var = birth_circuit.mul(record.payload.data[2], is_not_dummy)?;
+            birth_circuit.range_gate(lock_time_var, LOCK_TIME_BIT_LEN)?;
+        }
+
+        // pad the birth circuit with dummy gates so that it will always be greater
+        // than the supported death ones
+        birth_circuit.pad_gate(Self::PAD_GATES);
+
+        Ok(Self::from(PredicateCircuit(birth_circuit)))
+    }
+}
+
+// Extra, application dependent logics are defined in this circuit.
+impl DeathPredicateCircuit for TimeLockPredicateCircuit {
+    // we want to check:
+    //  - it uses a same local data commitment as the birth predicate
+    //  - the lock times of the inputs are not after the memo time
+    fn gen_death_circuit_core(
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+    ) -> Result<Self, DPCApiError> {
+        if memo.is_empty() {
+            return Err(DPCApiError::InvalidParameter(
+                "Time-locked records require a memo stating the current time".to_string(),
+            ));
+        }
+        for note in entire_input_notes.iter() {
+            check_payload_data_len(note.ro.payload.data.len())?;
+        }
+        let mut death_circuit = PlonkCircuit::new_turbo_plonk();
+
+        // build all the variables
+        let comm_local_data_var = death_circuit.create_public_variable(comm_local_data)?;
+        let blinding_local_data_var = death_circuit.create_variable(blinding_local_data)?;
+
+        let entire_input_notes_vars = entire_input_notes
+            .iter()
+            .map(|x| NoteInputVar::new(&mut death_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entire_outputs_vars = entire_output_records
+            .iter()
+            .map(|x| RecordOpeningVar::new(&mut death_circuit, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let memo_vars = memo
+            .iter()
+            .map(|x| death_circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // argue that the local data is correct w.r.t. to the commitment of local data
+        local_data_commitment_circuit(
+            &mut death_circuit,
+            &entire_input_notes_vars,
+            &entire_outputs_vars,
+            &memo_vars,
+            &blinding_local_data_var,
+            &comm_local_data_var,
+        )?;
+
+        // argue that lock_time <= time for all non-dummy inputs: the time is
+        // less than 2^LOCK_TIME_BIT_LEN, and so is time - lock_time only if it
+        // does not wrap around; dummy inputs are not locked
+        let time_var = memo_vars[0];
+        death_circuit.range_gate(time_var, LOCK_TIME_BIT_LEN)?;
+        for note in entire_input_notes_vars.iter().skip(1) {
+            let payload = &note.record_opening_var.payload;
+            let is_not_dummy = death_circuit.check_is_zero(payload.is_dummy)?;
+            let lock_time_var = death_circuit.mul(payload.data[2], is_not_dummy)?;
+            let remaining_var = death_circuit.sub(time_var, lock_time_var)?;
+            death_circuit.range_gate(remaining_var, LOCK_TIME_BIT_LEN)?;
+        }
+
+        // pad the death circuit with dummy gates
+        let current_gate_count = death_circuit.num_gates();
+        let target_gate_count = Self::gen_birth_circuit_core(
+            entire_input_notes,
+            entire_output_records,
+            memo,
+            blinding_local_data,
+            comm_local_data,
+        )?
+        .0
+         .0
+        .num_gates();
+        if current_gate_count > target_gate_count {
+            return Err(DPCApiError::InternalError(format!(
+                "Death circuit ({} gates) is larger than birth circuit ({} gates)",
+                current_gate_count, target_gate_count
+            )));
+        }
+
+        death_circuit.pad_gate(target_gate_count - current_gate_count);
+
+        Ok(TimeLockPredicateCircuit(PredicateCircuit(death_circuit)))
+    }
+}
+
+impl<'a> PredicateOps<'a> for TimeLockPredicate<'a> {
+    /// Setup the circuit and related parameters
+    ///
+    /// Inputs:
+    /// - rng
+    /// - inner SRS
+    /// - outer SRS
+    /// - total number of inputs (including fee record)
+    /// - record layout and accumulator parameters
+    ///
+    /// Outputs:
+    /// - DPC proving key
+    /// - DPC verification key
+    /// - Birth predicate (with dummy local commitment)
+    /// - Birth predicate PIDs
+    /// - Death predicate (with dummy local commitment)
+    /// - Death predicate PIDs
+    fn preprocess(
+        inner_srs: &'a InnerUniversalParam,
+        outer_srs: &'a OuterUniversalParam,
+        entire_input_size: usize,
+        params: &DPCParams,
+    ) -> Result<
+        (
+            DPCProvingKey,
+            DPCVerifyingKey,
+            Self,
+            PolicyIdentifier,
+            Self,
+            PolicyIdentifier,
+        ),
+        DPCApiError,
+    > {
+        check_payload_data_len(params.payload_data_len)?;
+
+        // setup the dummy circuit/predicate/pid
+        let death_predicate_circuit =
+            TimeLockPredicateCircuit::preprocessed_death_circuit(entire_input_size, params)?;
+        let death_predicate = Predicate::new(inner_srs, &death_predicate_circuit.0, false)?;
+        let death_pid = PolicyIdentifier::from_verifying_key(death_predicate.verifying_key());
+
+        let mut birth_predicate_circuit =
+            TimeLockPredicateCircuit::preprocessed_birth_circuit(entire_input_size, params)?;
+        let birth_predicate = Predicate::new(inner_srs, &birth_predicate_circuit.0, true)?;
+        let birth_pid = PolicyIdentifier::from_verifying_key(birth_predicate.verifying_key());
+
+        birth_predicate_circuit
+            .0
+             .0
+            .finalize_for_mergeable_circuit(jf_plonk::MergeableCircuitType::TypeA)?;
+
+        // the inner domain size is the birth (or death) circuit's domain size
+        let unmerged_inner_domain_size = birth_predicate_circuit.0 .0.eval_domain_size()?;
+
+        let (dpc_pk, dpc_vk, (..)) = crate::proofs::transaction::preprocess(
+            outer_srs,
+            inner_srs,
+            entire_input_size - 1,
+            unmerged_inner_domain_size,
+            params,
+        )?;
+        Ok((
+            dpc_pk,
+            dpc_vk,
+            Self::from(birth_predicate),
+            birth_pid,
+            Self::from(death_predicate),
+            death_pid,
+        ))
+    }
+
+    /// Finalize a predicate circuit.
+    ///
+    /// This function will need to be called to prepare
+    /// the circuit for proof generation.
+    /// When a predicate circuit was initialized, it does not have the
+    /// correct commitment to the local data (and thus cannot generate)
+    /// a correct proof.
+    fn finalize_for_proving(
+        &mut self,
+        entire_input_notes: &[NoteInput],
+        entire_output_records: &[RecordOpening],
+        memo: &[InnerScalarField],
+        blinding_local_data: InnerScalarField,
+        comm_local_data: InnerScalarField,
+        is_birth_predicate: bool,
+    ) -> Result<(), DPCApiError> {
+        let mut final_circuit = if is_birth_predicate {
+            TimeLockPredicateCircuit::gen_birth_circuit(
+                entire_input_notes,
+                entire_output_records,
+                memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+        } else {
+            TimeLockPredicateCircuit::gen_death_circuit(
+                entire_input_notes,
+                entire_output_records,
+                memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+        };
+
+        // sanity check: circuit is satisfied
+        final_circuit
+            .0
+             .0
+            .check_circuit_satisfiability(&[comm_local_data])?;
+
+        // finalize the circuit, and update the witness accordingly
+        let circuit_type = if is_birth_predicate {
+            jf_plonk::MergeableCircuitType::TypeA
+        } else {
+            jf_plonk::MergeableCircuitType::TypeB
+        };
+
+        final_circuit
+            .0
+             .0
+            .finalize_for_mergeable_circuit(circuit_type)?;
+
+        self.0.update_witness(final_circuit.0)?;
+        Ok(())
+    }
+
+    fn as_predicate(&self) -> &Predicate<'a> {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        constants::MEMO_LEN,
+        examples::tests::{build_notes, build_notes_and_records},
+        keys::{aggregate_authorization_signing_keypairs, KeyChainMasterKey},
+        ledger::check_memo_time,
+        proofs::{universal_setup_inner, universal_setup_outer},
+        structs::compress_local_data,
+        transaction::{pad_records, DPCTxnBody},
+    };
+    use ark_ff::{UniformRand, Zero};
+    use ark_std::{rand::Rng, test_rng, vec};
+
+    const NON_NATIVE_ASSET_ID: u64 = 2u64;
+
+    fn time_memo(time: u64) -> Vec<InnerScalarField> {
+        let mut memo = vec![InnerScalarField::zero(); MEMO_LEN];
+        memo[0] = InnerScalarField::from(time);
+        memo
+    }
+
+    #[test]
+    fn test_time_lock_death_circuit() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let params = DPCParams::default();
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (pid_birth, pid_death) = (PolicyIdentifier::default(), PolicyIdentifier::default());
+
+        // 2 inputs locked until 10 and 20, padded to 3 inputs
+        let (mut input_records, mut output_records) = build_notes_and_records(
+            rng,
+            &addr,
+            &pgk,
+            300,
+            295,
+            NON_NATIVE_ASSET_ID,
+            &[10, 30],
+            &[40],
+            pid_birth,
+            pid_death,
+        )?;
+        input_records[1].payload.data[2] = InnerScalarField::from(10u64);
+        input_records[2].payload.data[2] = InnerScalarField::from(20u64);
+        let mut input_notes = build_notes(&input_records, &pgk, &rd)?;
+        pad_records(
+            rng,
+            4,
+            &params,
+            &mut input_notes,
+            &mut output_records,
+            pid_birth,
+            pid_death,
+        )?;
+
+        let check_death_circuit = |memo: &[InnerScalarField]| {
+            let blinding_local_data = InnerScalarField::rand(&mut test_rng());
+            let comm_local_data =
+                compress_local_data(&input_notes, &output_records, memo.to_vec())?
+                    .commit(blinding_local_data)?;
+            let death_circuit = TimeLockPredicateCircuit::gen_death_circuit(
+                &input_notes,
+                &output_records,
+                memo,
+                blinding_local_data,
+                comm_local_data,
+            )?;
+            death_circuit
+                .0
+                 .0
+                .check_circuit_satisfiability(&[comm_local_data])?;
+            Ok::<_, DPCApiError>(death_circuit.0 .0.num_gates())
+        };
+
+        // the death circuit is padded to the size of the birth circuit
+        let birth_circuit = TimeLockPredicateCircuit::preprocessed_birth_circuit(4, &params)?;
+        assert_eq!(
+            check_death_circuit(&time_memo(20))?,
+            birth_circuit.0 .0.num_gates()
+        );
+        check_death_circuit(&time_memo(1000))?;
+
+        // records cannot be spent before their lock time
+        assert!(check_death_circuit(&time_memo(19)).is_err());
+        assert!(check_death_circuit(&time_memo(0)).is_err());
+        assert!(check_death_circuit(&time_memo(1 << LOCK_TIME_BIT_LEN)).is_err());
+        assert!(check_death_circuit(&[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_lock_birth_circuit() -> Result<(), DPCApiError> {
+        let params = DPCParams::default();
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (pid_birth, pid_death) = (PolicyIdentifier::default(), PolicyIdentifier::default());
+        let memo = time_memo(0);
+
+        let check_birth_circuit = |lock_time: InnerScalarField| {
+            let rng = &mut test_rng();
+            let (input_records, mut output_records) = build_notes_and_records(
+                rng,
+                &addr,
+                &pgk,
+                300,
+                295,
+                NON_NATIVE_ASSET_ID,
+                &[40],
+                &[10, 30],
+                pid_birth,
+                pid_death,
+            )?;
+            output_records[1].payload.data[2] = lock_time;
+            let mut input_notes = build_notes(&input_records, &pgk, &rd)?;
+            pad_records(
+                rng,
+                4,
+                &params,
+                &mut input_notes,
+                &mut output_records,
+                pid_birth,
+                pid_death,
+            )?;
+            let blinding_local_data = InnerScalarField::rand(rng);
+            let comm_local_data = compress_local_data(&input_notes, &output_records, memo.clone())?
+                .commit(blinding_local_data)?;
+            TimeLockPredicateCircuit::gen_birth_circuit(
+                &input_notes,
+                &output_records,
+                &memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+            .0
+             .0
+            .check_circuit_satisfiability(&[comm_local_data])
+            .map_err(DPCApiError::from)
+        };
+
+        check_birth_circuit(InnerScalarField::from(30u64))?;
+        check_birth_circuit(InnerScalarField::from((1u64 << LOCK_TIME_BIT_LEN) - 1))?;
+
+        // lock times that would wrap around at spending are rejected
+        assert!(check_birth_circuit(InnerScalarField::from(1u64 << LOCK_TIME_BIT_LEN)).is_err());
+        assert!(check_birth_circuit(-InnerScalarField::from(5u64)).is_err());
+
+        // the payload must hold the lock time
+        let short_params = DPCParams::new(params.tree_depth(), 2, MEMO_LEN)?;
+        assert!(TimeLockPredicateCircuit::preprocessed_birth_circuit(4, &short_params).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_time_lock_example_transaction() -> Result<(), DPCApiError> {
+        // universal setup
+        let rng = &mut test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let num_non_fee_inputs = 2;
+        let (dpc_pk, dpc_vk, mut birth_predicate, birth_pid, mut death_predicate, death_pid) =
+            TimeLockPredicate::preprocess(
+                &inner_srs,
+                &outer_srs,
+                num_non_fee_inputs + 1,
+                &DPCParams::default(),
+            )?;
+
+        // generate proof generation key and addresses
+        let mut wsk = [0u8; 32];
+        rng.fill(&mut wsk[..]);
+        let msk = KeyChainMasterKey::generate(wsk, &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+
+        // 2 inputs locked until 10 and 20, the outputs are locked until 30
+        let (mut input_records, mut output_records) = build_notes_and_records(
+            rng,
+            &addr,
+            &pgk,
+            300,
+            295,
+            NON_NATIVE_ASSET_ID,
+            &[10, 30],
+            &[22, 18],
+            birth_pid,
+            death_pid,
+        )?;
+        input_records[1].payload.data[2] = InnerScalarField::from(10u64);
+        input_records[2].payload.data[2] = InnerScalarField::from(20u64);
+        output_records[1].payload.data[2] = InnerScalarField::from(30u64);
+        output_records[2].payload.data[2] = InnerScalarField::from(30u64);
+        let input_notes = build_notes(&input_records, &pgk, &rd)?;
+        let merkle_root = input_notes[0].acc_member_witness.root;
+
+        // bad path: the inputs are still locked
+        let memo = time_memo(15);
+        let blinding_local_data = InnerScalarField::rand(rng);
+        let comm_local_data = compress_local_data(&input_notes, &output_records, memo.clone())?
+            .commit(blinding_local_data)?;
+        assert!(death_predicate
+            .finalize_for_proving(
+                &input_notes,
+                &output_records,
+                &memo,
+                blinding_local_data,
+                comm_local_data,
+                false,
+            )
+            .is_err());
+
+        // good path: spend the inputs at time 25
+        let memo = time_memo(25);
+        let blinding_local_data = InnerScalarField::rand(rng);
+        let comm_local_data = compress_local_data(&input_notes, &output_records, memo.clone())?
+            .commit(blinding_local_data)?;
+        birth_predicate.finalize_for_proving(
+            &input_notes,
+            &output_records,
+            &memo,
+            blinding_local_data,
+            comm_local_data,
+            true,
+        )?;
+        death_predicate.finalize_for_proving(
+            &input_notes,
+            &output_records,
+            &memo,
+            blinding_local_data,
+            comm_local_data,
+            false,
+        )?;
+
+        let input_death_predicates = vec![death_predicate.0; num_non_fee_inputs];
+        let output_birth_predicates = vec![birth_predicate.0; num_non_fee_inputs];
+        let txn_body = DPCTxnBody::generate(
+            rng,
+            &dpc_pk,
+            input_notes,
+            output_records,
+            &input_death_predicates,
+            &output_birth_predicates,
+            5,
+            memo,
+            blinding_local_data,
+        )?;
+        let auth_keys = vec![ak.0; num_non_fee_inputs + 1];
+        let randomizers = vec![Default::default(); num_non_fee_inputs + 1];
+        let aggregate_auth_key =
+            aggregate_authorization_signing_keypairs(&auth_keys, &randomizers)?;
+        let txn_note = txn_body.authorize(&aggregate_auth_key)?;
+        txn_note.verify(&dpc_vk, merkle_root)?;
+
+        // validators only accept the note once the time has come
+        check_memo_time(&txn_note.body.memo, 25)?;
+        assert!(check_memo_time(&txn_note.body.memo, 24).is_err());
+
+        // the memo time cannot be changed after proving
+        let mut bad_note = txn_note;
+        bad_note.body.memo[0] = InnerScalarField::from(30u64);
+        assert!(bad_note.verify(&dpc_vk, merkle_root).is_err());
+        Ok(())
+    }
+}
@@ -67,21 +67,20 @@
 //!     - the values are correctly committed via the `common_local_data`
 //!     - the sum of input records' value matches the sum of output records'
 //!       value
-//!     - all input/output records shares a same asset id
+//!     - all non-dummy input/output records shares a same asset id
 //!     - the associated death pid is permitted (not implemented)
 //!
 //! Note that the birth predicate is identical for all three examples; and is
 //! implemented via the default implementation.
 //! So here we only need to write the logic for the death predicate.
 
-use super::{BirthPredicateCircuit, DeathPredicateCircuit, PredicateOps};
 use crate::{
-    circuit::{
-        local_data::local_data_commitment_circuit,
-        structs::{NoteInputVar, RecordOpeningVar},
+    app::{
+        local_data_commitment_circuit, BirthPredicateCircuit, DeathPredicateCircuit, NoteInputVar,
+        PredicateOps, RecordOpeningVar,
     },
-    constants::MEMO_LEN,
     errors::DPCApiError,
+    params::DPCParams,
     predicates::PredicateTrait,
     proofs::{
         predicates::{Predicate, PredicateCircuit},
@@ -132,7 +131,7 @@ impl DeathPredicateCircuit for TcashPredicateCircuit {
     fn gen_death_circuit_core(
         entire_input_notes: &[NoteInput],
         entire_output_records: &[RecordOpening],
-        memo: &[InnerScalarField; MEMO_LEN],
+        memo: &[InnerScalarField],
         blinding_local_data: InnerScalarField,
         comm_local_data: InnerScalarField,
     ) -> Result<Self, DPCApiError> {
@@ -193,10 +192,16 @@ impl DeathPredicateCircuit for TcashPredicateCircuit {
 
         // pad the death circuit with dummy gates
         let current_gate_count = death_circuit.num_gates();
-        let target_gate_count = Self::preprocessed_birth_circuit(entire_input_notes.len())?
-            .0
-             .0
-            .num_gates();
+        let target_gate_count = Self::gen_birth_circuit_core(
+            entire_input_notes,
+            entire_output_records,
+            memo,
+            blinding_local_data,
+            comm_local_data,
+        )?
+        .0
+         .0
+        .num_gates();
         death_circuit.pad_gate(target_gate_count - current_gate_count);
 
         Ok(TcashPredicateCircuit(PredicateCircuit(death_circuit)))
@@ -211,6 +216,7 @@ impl<'a> PredicateOps<'a> for TcashPredicate<'a> {
     /// - inner SRS
     /// - outer SRS
     /// - total number of inputs (including fee record)
+    /// - record layout and accumulator parameters
     ///
     /// Outputs:
     /// - DPC proving key
@@ -223,9 +229,10 @@ impl<'a> PredicateOps<'a> for TcashPredicate<'a> {
         inner_srs: &'a InnerUniversalParam,
         outer_srs: &'a OuterUniversalParam,
         entire_input_size: usize,
+        params: &DPCParams,
     ) -> Result<
         (
-            DPCProvingKey<'a>,
+            DPCProvingKey,
             DPCVerifyingKey,
             Self,
             PolicyIdentifier,
@@ -236,9 +243,9 @@ impl<'a> PredicateOps<'a> for TcashPredicate<'a> {
     > {
         // setup the dummy circuit/predicate/pid
         let mut birth_predicate_circuit =
-            TcashPredicateCircuit::preprocessed_birth_circuit(entire_input_size)?;
+            TcashPredicateCircuit::preprocessed_birth_circuit(entire_input_size, params)?;
         let death_predicate_circuit =
-            TcashPredicateCircuit::preprocessed_death_circuit(entire_input_size)?;
+            TcashPredicateCircuit::preprocessed_death_circuit(entire_input_size, params)?;
         let birth_predicate = Predicate::new(inner_srs, &birth_predicate_circuit.0, true)?;
         let death_predicate = Predicate::new(inner_srs, &death_predicate_circuit.0, false)?;
         let birth_pid = PolicyIdentifier::from_verifying_key(birth_predicate.verifying_key());
@@ -257,6 +264,7 @@ impl<'a> PredicateOps<'a> for TcashPredicate<'a> {
             inner_srs,
             entire_input_size - 1,
             inner_domain_size,
+            params,
         )?;
         Ok((
             dpc_pk,
@@ -279,7 +287,7 @@ impl<'a> PredicateOps<'a> for TcashPredicate<'a> {
         &mut self,
         entire_input_notes: &[NoteInput],
         entire_output_records: &[RecordOpening],
-        memo: &[InnerScalarField; MEMO_LEN],
+        memo: &[InnerScalarField],
         blinding_local_data: InnerScalarField,
         comm_local_data: InnerScalarField,
         is_birth_predicate: bool,
@@ -324,6 +332,10 @@ impl<'a> PredicateOps<'a> for TcashPredicate<'a> {
         self.0.update_witness(final_circuit.0)?;
         Ok(())
     }
+
+    fn as_predicate(&self) -> &Predicate<'a> {
+        &self.0
+    }
 }
 
 // argue that a variable is of one of the following values
@@ -453,7 +465,12 @@ mod test {
         let rng = &mut test_rng();
 
         let (dpc_pk, dpc_vk, mut birth_predicate, birth_pid, mut death_predicate, death_pid) =
-            TcashPredicate::preprocess(&inner_srs, &outer_srs, num_non_fee_inputs + 1)?;
+            TcashPredicate::preprocess(
+                &inner_srs,
+                &outer_srs,
+                num_non_fee_inputs + 1,
+                &DPCParams::default(),
+            )?;
 
         // generate proof generation key and addresses
         let mut wsk = [0u8; 32];
@@ -522,24 +539,18 @@ mod test {
         let input_death_predicates = vec![death_predicate.0; num_non_fee_inputs];
         let output_birth_predicates = vec![birth_predicate.0; num_non_fee_inputs];
 
-        let witness = DPCWitness::new_unchecked(
+        // generate the proof and verify it
+        let (pub_input, dpc_proof, _) = generate_proof(
             rng,
+            &dpc_pk,
             entire_input_notes,
             entire_output_records,
             &input_death_predicates,
             &output_birth_predicates,
             blinding_local_data,
-        )?;
-
-        let pub_input = DPCPublicInput::from_witness(
-            &witness,
             fee as u64,
             dummy_memo.to_vec(),
-            inner_srs.powers_of_g_ref()[1],
         )?;
-
-        // generate the proof and verify it
-        let dpc_proof = prove(rng, &dpc_pk, &witness, &pub_input)?;
         verify(&dpc_proof, &dpc_vk, &pub_input)
     }
 }
@@ -60,21 +60,20 @@
 //!     - the values are correctly committed via the `common_local_data`
 //!     - the sum of input records' value matches the sum of output records'
 //!       value
-//!     - all input/output records shares a same asset id
+//!     - all non-dummy input/output records shares a same asset id
 //!     - the associated death pid is permitted (not implemented)
 //!
 //! Note that the birth predicate is identical for all three examples; and is
 //! implemented via the default implementation.
 //! So here we only need to write the logic for the death predicate.
 
-use super::{BirthPredicateCircuit, DeathPredicateCircuit, PredicateOps};
 use crate::{
-    circuit::{
-        local_data::local_data_commitment_circuit,
-        structs::{NoteInputVar, RecordOpeningVar},
+    app::{
+        local_data_commitment_circuit, BirthPredicateCircuit, DeathPredicateCircuit, NoteInputVar,
+        PredicateOps, RecordOpeningVar,
     },
-    constants::MEMO_LEN,
     errors::DPCApiError,
+    params::DPCParams,
     predicates::PredicateTrait,
     proofs::{
         predicates::{Predicate, PredicateCircuit},
@@ -86,8 +85,8 @@ use crate::{
 use ark_std::vec::Vec;
 use jf_plonk::circuit::{Arithmetization, Circuit, PlonkCircuit};
 
-// A simple wrapper of predicate circuit
-pub(crate) struct ZcashPredicateCircuit(pub(crate) PredicateCircuit);
+/// A simple wrapper of predicate circuit
+pub struct ZcashPredicateCircuit(pub PredicateCircuit);
 
 impl From<PredicateCircuit> for ZcashPredicateCircuit {
     fn from(circuit: PredicateCircuit) -> Self {
@@ -95,8 +94,8 @@ impl From<PredicateCircuit> for ZcashPredicateCircuit {
     }
 }
 
-// A simple wrapper of predicate
-pub(crate) struct ZcashPredicate<'a>(pub(crate) Predicate<'a>);
+/// A simple wrapper of predicate
+pub struct ZcashPredicate<'a>(pub Predicate<'a>);
 
 impl<'a> From<Predicate<'a>> for ZcashPredicate<'a> {
     fn from(predicate: Predicate<'a>) -> Self {
@@ -125,7 +124,7 @@ impl DeathPredicateCircuit for ZcashPredicateCircuit {
     fn gen_death_circuit_core(
         entire_input_notes: &[NoteInput],
         entire_output_records: &[RecordOpening],
-        memo: &[InnerScalarField; MEMO_LEN],
+        memo: &[InnerScalarField],
         blinding_local_data: InnerScalarField,
         comm_local_data: InnerScalarField,
     ) -> Result<Self, DPCApiError> {
@@ -160,10 +159,16 @@ impl DeathPredicateCircuit for ZcashPredicateCircuit {
 
         // pad the death circuit with dummy gates
         let current_gate_count = death_circuit.num_gates();
-        let target_gate_count = Self::preprocessed_birth_circuit(entire_input_notes.len())?
-            .0
-             .0
-            .num_gates();
+        let target_gate_count = Self::gen_birth_circuit_core(
+            entire_input_notes,
+            entire_output_records,
+            memo,
+            blinding_local_data,
+            comm_local_data,
+        )?
+        .0
+         .0
+        .num_gates();
 
         death_circuit.pad_gate(target_gate_count - current_gate_count);
 
@@ -179,6 +184,7 @@ impl<'a> PredicateOps<'a> for ZcashPredicate<'a> {
     /// - inner SRS
     /// - outer SRS
     /// - total number of inputs (including fee record)
+    /// - record layout and accumulator parameters
     ///
     /// Outputs:
     /// - DPC proving key
@@ -191,9 +197,10 @@ impl<'a> PredicateOps<'a> for ZcashPredicate<'a> {
         inner_srs: &'a InnerUniversalParam,
         outer_srs: &'a OuterUniversalParam,
         entire_input_size: usize,
+        params: &DPCParams,
     ) -> Result<
         (
-            DPCProvingKey<'a>,
+            DPCProvingKey,
             DPCVerifyingKey,
             Self,
             PolicyIdentifier,
@@ -204,12 +211,12 @@ impl<'a> PredicateOps<'a> for ZcashPredicate<'a> {
     > {
         // setup the dummy circuit/predicate/pid
         let death_predicate_circuit =
-            ZcashPredicateCircuit::preprocessed_death_circuit(entire_input_size)?;
+            ZcashPredicateCircuit::preprocessed_death_circuit(entire_input_size, params)?;
         let death_predicate = Predicate::new(inner_srs, &death_predicate_circuit.0, false)?;
         let death_pid = PolicyIdentifier::from_verifying_key(death_predicate.verifying_key());
 
         let mut birth_predicate_circuit =
-            ZcashPredicateCircuit::preprocessed_birth_circuit(entire_input_size)?;
+            ZcashPredicateCircuit::preprocessed_birth_circuit(entire_input_size, params)?;
         let birth_predicate = Predicate::new(inner_srs, &birth_predicate_circuit.0, true)?;
         let birth_pid = PolicyIdentifier::from_verifying_key(birth_predicate.verifying_key());
 
@@ -226,6 +233,7 @@ impl<'a> PredicateOps<'a> for ZcashPredicate<'a> {
             inner_srs,
             entire_input_size - 1,
             unmerged_inner_domain_size,
+            params,
         )?;
         Ok((
             dpc_pk,
@@ -248,7 +256,7 @@ impl<'a> PredicateOps<'a> for ZcashPredicate<'a> {
         &mut self,
         entire_input_notes: &[NoteInput],
         entire_output_records: &[RecordOpening],
-        memo: &[InnerScalarField; MEMO_LEN],
+        memo: &[InnerScalarField],
         blinding_local_data: InnerScalarField,
         comm_local_data: InnerScalarField,
         is_birth_predicate: bool,
@@ -292,6 +300,10 @@ impl<'a> PredicateOps<'a> for ZcashPredicate<'a> {
         self.0.update_witness(final_circuit.0)?;
         Ok(())
     }
+
+    fn as_predicate(&self) -> &Predicate<'a> {
+        &self.0
+    }
 }
 
 #[cfg(test)]
@@ -299,11 +311,12 @@ mod test {
     use super::*;
     use crate::{
         constants::MEMO_LEN,
-        errors::DPCApiError,
+        errors::{DPCApiError, TxnParameterError},
         examples::tests::{build_notes, build_notes_and_records},
         keys::KeyChainMasterKey,
         proofs::{transaction::*, universal_setup_inner, universal_setup_outer},
-        structs::compress_local_data,
+        structs::{compress_local_data, Payload},
+        transaction::{pad_records, pad_with_dummy_records, TransactionBuilder},
         types::InnerScalarField,
     };
     use ark_ff::{UniformRand, Zero};
@@ -311,6 +324,105 @@ mod test {
 
     const NON_NATIVE_ASSET_ID: u64 = 2u64;
 
+    #[test]
+    fn test_birth_circuit_with_padding() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let params = DPCParams::default();
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (pid_birth, pid_death) = (PolicyIdentifier::default(), PolicyIdentifier::default());
+
+        // 1 input and 3 outputs, padded to 3 inputs
+        let (input_records, mut output_records) = build_notes_and_records(
+            rng,
+            &addr,
+            &pgk,
+            300,
+            295,
+            NON_NATIVE_ASSET_ID,
+            &[40],
+            &[10, 20, 10],
+            pid_birth,
+            pid_death,
+        )?;
+        let mut input_notes = build_notes(&input_records, &pgk, &rd)?;
+        pad_records(
+            rng,
+            4,
+            &params,
+            &mut input_notes,
+            &mut output_records,
+            pid_birth,
+            pid_death,
+        )?;
+        assert_eq!(input_notes.len(), 4);
+        assert_eq!(output_records.len(), 4);
+        assert!(input_notes[2].ro.payload.is_dummy && input_notes[3].ro.payload.is_dummy);
+        assert_ne!(
+            input_notes[2]
+                .ro
+                .nullify(&input_notes[2].proof_gen_key.nk)?,
+            input_notes[3]
+                .ro
+                .nullify(&input_notes[3].proof_gen_key.nk)?
+        );
+        assert!(pad_records(
+            rng,
+            3,
+            &params,
+            &mut input_notes,
+            &mut output_records,
+            pid_birth,
+            pid_death,
+        )
+        .is_err());
+
+        let memo = vec![InnerScalarField::zero(); MEMO_LEN];
+        let check_birth_circuit = |input_notes: &[NoteInput], output_records: &[RecordOpening]| {
+            let blinding_local_data = InnerScalarField::rand(&mut test_rng());
+            let comm_local_data = compress_local_data(input_notes, output_records, memo.clone())?
+                .commit(blinding_local_data)?;
+            ZcashPredicateCircuit::gen_birth_circuit(
+                input_notes,
+                output_records,
+                &memo,
+                blinding_local_data,
+                comm_local_data,
+            )?
+            .0
+             .0
+            .check_circuit_satisfiability(&[comm_local_data])
+            .map_err(DPCApiError::from)
+        };
+        check_birth_circuit(&input_notes, &output_records)?;
+
+        // dummy records cannot carry value
+        let mut bad_input_notes = input_notes.clone();
+        bad_input_notes[3].ro.payload.data[1] = InnerScalarField::from(5u64);
+        let mut bad_output_records = output_records.clone();
+        bad_output_records[1].payload.data[1] = InnerScalarField::from(15u64);
+        assert!(check_birth_circuit(&bad_input_notes, &bad_output_records).is_err());
+
+        // inputs and outputs must come in equal numbers
+        assert!(matches!(
+            ZcashPredicateCircuit::gen_birth_circuit(
+                &input_notes,
+                &output_records[..3],
+                &memo,
+                InnerScalarField::zero(),
+                InnerScalarField::zero(),
+            ),
+            Err(DPCApiError::InvalidTransactionParameter(
+                TxnParameterError::InputsOutputsMismatch {
+                    inputs: 4,
+                    outputs: 3
+                }
+            ))
+        ));
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     fn test_zcash_example_transaction() -> Result<(), DPCApiError> {
@@ -355,6 +467,23 @@ mod test {
             output_note_values.as_ref(),
         )?;
 
+        // good path: 1 input, 3 outputs, padded to 3 inputs
+        let fee_in = 300;
+        let fee = 5;
+        let fee_out = 295;
+        let input_note_values = [40];
+        let output_note_values = [10, 20, 10];
+
+        test_example_transaction_helper(
+            &inner_srs,
+            &outer_srs,
+            fee_in,
+            fee,
+            fee_out,
+            input_note_values.as_ref(),
+            output_note_values.as_ref(),
+        )?;
+
         // bad path: input sum != output sum
         let fee_in = 300;
         let fee = 5;
@@ -375,6 +504,185 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[ignore]
+    fn test_zcash_example_transaction_builder() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 3, &DPCParams::default())?;
+
+        let mut wsk = [0u8; 32];
+        rng.fill(&mut wsk[..]);
+        let msk = KeyChainMasterKey::generate(wsk, &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (receiver, _) = msk.derive_diversified_address(&pgk, &ivk, 1)?;
+
+        // one fee record and one record of the application, the fee change
+        // output of `build_notes_and_records` is unused
+        let (input_records, _) = build_notes_and_records(
+            rng,
+            &addr,
+            &pgk,
+            300,
+            295,
+            NON_NATIVE_ASSET_ID,
+            &[40],
+            &[],
+            birth_pid,
+            death_pid,
+        )?;
+        let mut input_notes = build_notes(&input_records, &pgk, &rd)?;
+        let merkle_root = input_notes[0].acc_member_witness.root;
+        let record_input = input_notes.pop().unwrap();
+        let fee_input = input_notes.pop().unwrap();
+        let payload = |value: u64| {
+            Payload::from_scalars(&[
+                InnerScalarField::from(NON_NATIVE_ASSET_ID),
+                InnerScalarField::from(value),
+            ])
+        };
+
+        // bad path: the fee cannot exceed the fee input value
+        assert!(TransactionBuilder::new(
+            &dpc_pk,
+            ZcashPredicate(birth_predicate.0.clone()),
+            birth_pid,
+            ZcashPredicate(death_predicate.0.clone()),
+            death_pid,
+        )
+        .fee_input(fee_input.clone(), &ak)
+        .add_input(record_input.clone(), &ak)
+        .add_recipient(receiver.clone(), payload(40)?, None)
+        .fee(301)
+        .build(rng)
+        .is_err());
+
+        // good path: 1 input and 2 outputs, padded to 3 inputs and 3 outputs
+        let note = TransactionBuilder::new(
+            &dpc_pk,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+        )
+        .fee_input(fee_input, &ak)
+        .add_input(record_input, &ak)
+        .add_recipient(receiver, payload(25)?, None)
+        .add_recipient(addr, payload(15)?, None)
+        .fee(5)
+        .build(rng)?;
+        note.verify(&dpc_vk, merkle_root)?;
+        assert_eq!(note.body.input_nullifiers.len(), 3);
+        assert_eq!(note.body.receiver_memos.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_zcash_example_with_params() -> Result<(), DPCApiError> {
+        use crate::{
+            ledger::{LedgerState, DEFAULT_ROOT_HISTORY_LEN},
+            structs::Nullifier,
+        };
+        use ark_std::borrow::Cow;
+
+        // a smaller records tree, shorter payloads and a longer memo than
+        // the default parameters
+        let rng = &mut test_rng();
+        let params = DPCParams::new(16, 4, MEMO_LEN + 2)?;
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 2, &params)?;
+        assert_eq!(dpc_pk.params(), &params);
+
+        let msk = KeyChainMasterKey::generate([3u8; 32], &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (receiver, _) = msk.derive_diversified_address(&pgk, &ivk, 1)?;
+        let payload = |value: u64| {
+            Payload::from_scalars_with_params(
+                &[
+                    InnerScalarField::from(NON_NATIVE_ASSET_ID),
+                    InnerScalarField::from(value),
+                ],
+                &params,
+            )
+        };
+
+        // a fee record and a record of the application in a ledger of depth 16
+        let fee_record = RecordOpening::new_native_asset_with_params(
+            rng,
+            addr.clone(),
+            300,
+            0,
+            Nullifier::default(),
+            &params,
+        );
+        let app_record = RecordOpening::new(
+            rng,
+            addr,
+            payload(40)?,
+            birth_pid.0,
+            death_pid.0,
+            0,
+            Nullifier::default(),
+        );
+        let mut ledger = LedgerState::new_with_params(DEFAULT_ROOT_HISTORY_LEN, &params)?;
+        let mut inputs = vec![];
+        for ro in [fee_record, app_record] {
+            let uid = ledger.insert_record_commitment(ro.derive_record_commitment()?)?;
+            inputs.push(NoteInput {
+                ro,
+                acc_member_witness: ledger.membership_witness(uid)?,
+                proof_gen_key: Cow::Borrowed(&pgk),
+                authorization_randomizer: Default::default(),
+                diversifier_randomizer: rd.clone(),
+            });
+        }
+        let app_input = inputs.pop().unwrap();
+        let fee_input = inputs.pop().unwrap();
+        let builder = || {
+            TransactionBuilder::new(
+                &dpc_pk,
+                ZcashPredicate(birth_predicate.0.clone()),
+                birth_pid,
+                ZcashPredicate(death_predicate.0.clone()),
+                death_pid,
+            )
+            .fee_input(fee_input.clone(), &ak)
+            .add_input(app_input.clone(), &ak)
+            .fee(5)
+        };
+
+        // bad path: payloads and memos of the default layout
+        let default_payload = Payload::from_scalars(&[
+            InnerScalarField::from(NON_NATIVE_ASSET_ID),
+            InnerScalarField::from(40u64),
+        ])?;
+        assert!(builder()
+            .add_recipient(receiver.clone(), default_payload, None)
+            .build(rng)
+            .is_err());
+        assert!(builder()
+            .add_recipient(receiver.clone(), payload(40)?, None)
+            .memo(vec![InnerScalarField::zero(); MEMO_LEN])
+            .build(rng)
+            .is_err());
+
+        // good path
+        let note = builder()
+            .add_recipient(receiver, payload(40)?, None)
+            .build(rng)?;
+        assert_eq!(note.body.memo.len(), MEMO_LEN + 2);
+        note.verify(&dpc_vk, ledger.root())?;
+        assert_eq!(ledger.apply_note(&note, &dpc_vk)?, vec![2, 3]);
+        Ok(())
+    }
+
     // TODO: use the consolidated API for testing
     fn test_example_transaction_helper(
         inner_srs: &InnerUniversalParam,
@@ -385,13 +693,18 @@ mod test {
         input_note_values: &[u64],
         output_note_values: &[u64],
     ) -> Result<(), DPCApiError> {
-        let num_non_fee_inputs = input_note_values.len();
-        assert_eq!(num_non_fee_inputs, output_note_values.len());
+        // inputs and outputs are padded with dummy records
+        let num_non_fee_inputs = input_note_values.len().max(output_note_values.len());
 
         let rng = &mut test_rng();
 
         let (dpc_pk, dpc_vk, mut birth_predicate, birth_pid, mut death_predicate, death_pid) =
-            ZcashPredicate::preprocess(&inner_srs, &outer_srs, num_non_fee_inputs + 1)?;
+            ZcashPredicate::preprocess(
+                &inner_srs,
+                &outer_srs,
+                num_non_fee_inputs + 1,
+                &DPCParams::default(),
+            )?;
 
         // generate proof generation key and addresses
         let mut wsk = [0u8; 32];
@@ -421,7 +734,16 @@ mod test {
             death_pid,
         )?;
 
-        let entire_input_notes = build_notes(&entire_input_records, &pgk, &rd)?;
+        let mut entire_input_notes = build_notes(&entire_input_records, &pgk, &rd)?;
+        let mut entire_output_records = entire_output_records;
+        pad_with_dummy_records(
+            rng,
+            &dpc_pk,
+            &mut entire_input_notes,
+            &mut entire_output_records,
+            birth_pid,
+            death_pid,
+        )?;
 
         // prepare memo
         let dummy_memo = [InnerScalarField::zero(); MEMO_LEN];
@@ -460,24 +782,18 @@ mod test {
         let input_death_predicates = vec![death_predicate.0; num_non_fee_inputs];
         let output_birth_predicates = vec![birth_predicate.0; num_non_fee_inputs];
 
-        let witness = DPCWitness::new_unchecked(
+        // generate the proof and verify it
+        let (pub_input, dpc_proof, _) = generate_proof(
             rng,
+            &dpc_pk,
             entire_input_notes,
             entire_output_records,
             &input_death_predicates,
             &output_birth_predicates,
             blinding_local_data,
-        )?;
-
-        let pub_input = DPCPublicInput::from_witness(
-            &witness,
             fee as u64,
             dummy_memo.to_vec(),
-            inner_srs.powers_of_g_ref()[1],
         )?;
-
-        // generate the proof and verify it
-        let dpc_proof = prove(rng, &dpc_pk, &witness, &pub_input)?;
         verify(&dpc_proof, &dpc_vk, &pub_input)
     }
 }
@@ -16,6 +16,7 @@ use ark_ec::{
 use ark_ff::{BigInteger, PrimeField};
 use ark_serialize::*;
 use ark_std::{
+    format,
     ops::{Add, AddAssign},
     rand::{CryptoRng, RngCore},
     string::ToString,
@@ -44,6 +45,12 @@ impl AuthorizationKeyPair {
         AuthorizationPubKey(self.0.ver_key())
     }
 
+    /// Getter for the signing key pair, e.g. to aggregate with
+    /// `aggregate_authorization_signing_keypairs`
+    pub fn signing_key_pair(&self) -> &SigKeyPair {
+        &self.0
+    }
+
     /// Randomize the key pair with the `randomizer`, returns the randomized key
     /// pair.
     pub fn randomize_with<R>(&self, randomizer: &InnerEmbeddedScalarField) -> Self
@@ -177,6 +184,57 @@ impl KeyChainMasterKey {
         Ok((addr, diversifier))
     }
 
+    /// Key Chain derivation for a key-chain with multi-consumer support (i.e.
+    /// "freezer support"), where `nk` is the `NullifierDerivingKey` jointly
+    /// derived with the other consumers via `MultiConsumerKeyExchange`.
+    pub fn derive_key_chain_multi_consumer(
+        &self,
+        nk: NullifierDerivingKey,
+    ) -> (AuthorizationKeyPair, ProofGenerationKey, IncomingViewingKey) {
+        let ask = self.derive_authorization_key_pair();
+        let ivk = self.derive_incoming_viewing_key();
+        let ak = ask.public_key();
+        let pgk = ProofGenerationKey { ak, nk };
+        (ask, pgk, ivk)
+    }
+
+    /// Start the interactive k-DH protocol generating the app-specific
+    /// `NullifierDerivingKey` shared by `num_parties` consumers, where this
+    /// key-chain acts as the `index`-th party of the ring. `aux` identifies
+    /// the application so that distinct apps derive independent keys.
+    pub fn start_multi_consumer_key_exchange(
+        &self,
+        aux: &[u8],
+        index: usize,
+        num_parties: usize,
+    ) -> Result<MultiConsumerKeyExchange, DPCApiError> {
+        if num_parties < 2 {
+            return Err(DPCApiError::InvalidParameter(
+                "Multi-consumer key exchange requires at least 2 parties".to_string(),
+            ));
+        }
+        if index >= num_parties {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Party index {} out of range, number of parties {}",
+                index, num_parties
+            )));
+        }
+        // deterministically calculate: H('DomSep' || aux || wsk)
+        let bytes = [
+            MULTI_CONSUMER_KDH_SECRET_DOM_SEP.as_bytes(),
+            aux,
+            &self.as_bytes(),
+        ]
+        .concat();
+        let secret: InnerEmbeddedScalarField = jf_utils::hash_to_field(&bytes);
+        Ok(MultiConsumerKeyExchange {
+            secret,
+            index,
+            num_parties,
+            round: 0,
+        })
+    }
+
     /// Get byte representation of key
     pub fn as_bytes(&self) -> Vec<u8> {
         self.0.into_repr().to_bytes_le()
@@ -201,19 +259,6 @@ impl KeyChainMasterKey {
         NullifierDerivingKey(key.into())
     }
 
-    /// Generate the app-specific `NullifierDerivingKey` of a key-chain with
-    /// multi-consumer support (i.e. "freezer support") via k-DH.
-    // TODO: implement interactive k-DH here with (k-1) `others`
-    pub(crate) fn generate_multi_consumer_nullifier_deriving_key(
-        &self,
-        _others: &[GroupProjective<InnerEmbeddedGroup>],
-    ) -> NullifierDerivingKey {
-        // deterministically calculate: H('DomSep' || wsk)
-        let bytes = [NULLIFYING_KEY_DOM_SEP.as_bytes(), &self.as_bytes()].concat();
-        let _key: InnerEmbeddedScalarField = jf_utils::hash_to_field(&bytes);
-        unimplemented!();
-    }
-
     /// Deterministically generate the `IncomingViewingKey` of the key-chain.
     pub(crate) fn derive_incoming_viewing_key(&self) -> IncomingViewingKey {
         let bytes = [INCOMING_VIEWING_KEY_DOM_SEP.as_bytes(), &self.as_bytes()].concat();
@@ -249,6 +294,145 @@ impl KeyChainMasterKey {
     }
 }
 
+/// Message sent from the `sender`-th party to its successor in `round` of the
+/// multi-consumer k-DH protocol.
+#[tagged_blob("KDH-MSG")]
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KeyExchangeMessage {
+    /// index of the sending party in the ring
+    pub sender: usize,
+    /// protocol round, starting from 0
+    pub round: usize,
+    /// partial Diffie-Hellman value
+    pub point: GroupProjective<InnerEmbeddedGroup>,
+}
+
+/// Output of a party after processing an incoming `KeyExchangeMessage`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyExchangeOutput {
+    /// Message to forward to the successor party in the next round
+    Message(KeyExchangeMessage),
+    /// The protocol is completed, with the jointly derived key
+    Finished(NullifierDerivingKey),
+}
+
+/// State of a party running the interactive k-DH protocol that derives a
+/// `NullifierDerivingKey` shared among k consumers.
+///
+/// The k parties are arranged in a ring, party `i` only sends to party
+/// `i+1 mod k`. In round 0 each party sends `g^{x_i}`, in every following
+/// round it raises the point received from its predecessor to its own secret
+/// `x_i` and forwards the result. After receiving k-1 messages, party `i`
+/// holds `g^{prod_{j != i} x_j}` and computes `S = g^{prod_j x_j}`, the
+/// shared key is `nk = H('DomSep' || S)`.
+#[derive(Clone, Debug)]
+pub struct MultiConsumerKeyExchange {
+    secret: InnerEmbeddedScalarField,
+    index: usize,
+    num_parties: usize,
+    // number of messages processed so far
+    round: usize,
+}
+
+impl MultiConsumerKeyExchange {
+    /// Index of the party expected to send messages to this party
+    pub fn predecessor(&self) -> usize {
+        (self.index + self.num_parties - 1) % self.num_parties
+    }
+
+    /// Index of the party this party sends messages to
+    pub fn successor(&self) -> usize {
+        (self.index + 1) % self.num_parties
+    }
+
+    /// First message of the protocol, to be sent to the successor party
+    pub fn first_message(&self) -> KeyExchangeMessage {
+        KeyExchangeMessage {
+            sender: self.index,
+            round: 0,
+            point: Group::mul(
+                &GroupProjective::<InnerEmbeddedGroup>::prime_subgroup_generator(),
+                &self.secret,
+            ),
+        }
+    }
+
+    /// Process the message received from the predecessor party, return
+    /// either the message for the successor or the derived key once the
+    /// protocol is completed.
+    pub fn process_message(
+        &mut self,
+        msg: &KeyExchangeMessage,
+    ) -> Result<KeyExchangeOutput, DPCApiError> {
+        if self.round == self.num_parties - 1 {
+            return Err(DPCApiError::InvalidParameter(
+                "Key exchange is already completed".to_string(),
+            ));
+        }
+        if msg.sender != self.predecessor() || msg.round != self.round {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Unexpected message from party {} in round {}, expecting party {} in round {}",
+                msg.sender,
+                msg.round,
+                self.predecessor(),
+                self.round
+            )));
+        }
+        if msg.point.is_zero() {
+            return Err(DPCApiError::InvalidParameter(
+                "Key exchange message contains the identity point".to_string(),
+            ));
+        }
+        let point = Group::mul(&msg.point, &self.secret);
+        self.round += 1;
+        if self.round < self.num_parties - 1 {
+            Ok(KeyExchangeOutput::Message(KeyExchangeMessage {
+                sender: self.index,
+                round: self.round,
+                point,
+            }))
+        } else {
+            let mut bytes = MULTI_CONSUMER_NULLIFYING_KEY_DOM_SEP.as_bytes().to_vec();
+            point.into_affine().serialize(&mut bytes)?;
+            let key: InnerScalarField = jf_utils::hash_to_field(&bytes);
+            Ok(KeyExchangeOutput::Finished(NullifierDerivingKey(
+                key.into(),
+            )))
+        }
+    }
+}
+
+/// Run the multi-consumer k-DH protocol locally among all `master_keys`,
+/// return the `NullifierDerivingKey` derived by each party. Parties running on
+/// separate machines should instead drive their `MultiConsumerKeyExchange`
+/// by exchanging `KeyExchangeMessage`s.
+pub fn run_multi_consumer_key_exchange(
+    master_keys: &[KeyChainMasterKey],
+    aux: &[u8],
+) -> Result<Vec<NullifierDerivingKey>, DPCApiError> {
+    let num_parties = master_keys.len();
+    let mut parties = master_keys
+        .iter()
+        .enumerate()
+        .map(|(index, msk)| msk.start_multi_consumer_key_exchange(aux, index, num_parties))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut messages: Vec<KeyExchangeMessage> =
+        parties.iter().map(|party| party.first_message()).collect();
+    let mut keys = vec![];
+    for _ in 1..num_parties {
+        // message of the `i`-th party is delivered to the `i+1`-th party
+        let mut next_messages = vec![];
+        for party in parties.iter_mut() {
+            match party.process_message(&messages[party.predecessor()])? {
+                KeyExchangeOutput::Message(msg) => next_messages.push(msg),
+                KeyExchangeOutput::Finished(nk) => keys.push(nk),
+            }
+        }
+        messages = next_messages;
+    }
+    Ok(keys)
+}
+
 /// Public key to give to senders to generate detection tag
 #[tagged_blob("DET-PUBKEY")]
 #[derive(Clone, Default, Debug, PartialEq)]
@@ -425,6 +609,42 @@ mod test {
         test_serde_default!(DetectionPubKey);
         test_serde_default!(DetectionKeyPair);
     }
+
+    #[test]
+    fn test_multi_consumer_key_exchange() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        for num_parties in 2..6 {
+            let master_keys: Vec<KeyChainMasterKey> = (0..num_parties)
+                .map(|_| KeyChainMasterKey::test_rand(rng))
+                .collect();
+            let keys = run_multi_consumer_key_exchange(&master_keys, b"app")?;
+            assert_eq!(keys.len(), num_parties);
+            assert!(keys.iter().all(|nk| *nk == keys[0]));
+
+            // distinct apps derive independent keys
+            let other_keys = run_multi_consumer_key_exchange(&master_keys, b"other app")?;
+            assert_ne!(keys[0], other_keys[0]);
+        }
+
+        // bad parameters
+        let msk = KeyChainMasterKey::test_rand(rng);
+        assert!(msk.start_multi_consumer_key_exchange(b"app", 0, 1).is_err());
+        assert!(msk.start_multi_consumer_key_exchange(b"app", 3, 3).is_err());
+
+        // unexpected messages are rejected
+        let other_msk = KeyChainMasterKey::test_rand(rng);
+        let mut party = msk.start_multi_consumer_key_exchange(b"app", 0, 3)?;
+        let other = other_msk.start_multi_consumer_key_exchange(b"app", 1, 3)?;
+        let mut msg = other.first_message();
+        assert!(party.process_message(&msg).is_err());
+        msg.sender = 2;
+        msg.round = 1;
+        assert!(party.process_message(&msg).is_err());
+        msg.round = 0;
+        msg.point = GroupProjective::<InnerEmbeddedGroup>::zero();
+        assert!(party.process_message(&msg).is_err());
+        Ok(())
+    }
 }
 
 // Commented out: Participation Key until https://gitlab.com/translucence/crypto/jellyfish/-/issues/180
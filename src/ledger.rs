@@ -0,0 +1,843 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Ledger state: the record commitments accumulator and the set of spent
+//! nullifiers, updated by applying verified transaction notes.
+//!
+//! The ledger also decides which assets fees are paid in. The UTXO circuit
+//! only proves that the fee input and the fee change are of the public fee
+//! asset of the note and differ by the fee, the native asset is always
+//! accepted and other assets must be approved with a public exchange rate.
+//!
+//! Applications such as time locks state the time (e.g. a ledger height or a
+//! timestamp) at which a note is submitted in the first element of its memo,
+//! and their predicates rely on it. Other applications are free to put any
+//! data in their memo, so the ledger only checks the memo time when
+//! `LedgerState::set_memo_time_check` enables it: notes stating a time after
+//! the current time of the ledger are then rejected, notes leaving it at zero
+//! are always accepted.
+
+use crate::{
+    constants::NATIVE_ASSET_CODE,
+    errors::{DPCApiError, TxnVerificationError},
+    params::DPCParams,
+    proofs::transaction::DPCVerifyingKey,
+    structs::Nullifier,
+    transaction::{DPCNoteAuxInfo, DPCTxnNote},
+    types::{CommitmentValue, InnerScalarField, NodeValue},
+};
+use ark_std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    format,
+    string::ToString,
+    vec::Vec,
+};
+use jf_primitives::merkle_tree::{AccMemberWitness, MerkleTree};
+
+/// Default number of most recent Merkle roots a `LedgerState` accepts as
+/// valid transaction anchors.
+pub const DEFAULT_ROOT_HISTORY_LEN: usize = 64;
+
+/// Public exchange rate of an asset fees are paid in: `asset_amount` units of
+/// the asset are worth `native_amount` units of the native asset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeRate {
+    /// Amount of the native asset
+    pub native_amount: u64,
+    /// Amount of the fee asset
+    pub asset_amount: u64,
+}
+
+impl FeeRate {
+    /// Create a new exchange rate
+    pub fn new(native_amount: u64, asset_amount: u64) -> Self {
+        Self {
+            native_amount,
+            asset_amount,
+        }
+    }
+}
+
+/// Ledger state of the DPC scheme, consisting of the Merkle tree of all
+/// record commitments, the set of spent nullifiers and a window of recent
+/// Merkle roots that transaction notes may refer to.
+#[derive(Clone, Debug)]
+pub struct LedgerState {
+    records_merkle_tree: MerkleTree<InnerScalarField>,
+    nullifiers: BTreeSet<Nullifier>,
+    recent_roots: VecDeque<NodeValue>,
+    root_history_len: usize,
+    tree_depth: u8,
+    fee_rates: BTreeMap<InnerScalarField, FeeRate>,
+    current_time: u64,
+    memo_time_check: bool,
+}
+
+impl LedgerState {
+    /// Create an empty ledger state that accepts the `root_history_len` most
+    /// recent Merkle roots.
+    pub fn new(root_history_len: usize) -> Result<Self, DPCApiError> {
+        Self::new_with_params(root_history_len, &DPCParams::default())
+    }
+
+    /// Create an empty ledger state that accepts the `root_history_len` most
+    /// recent Merkle roots, with a records Merkle tree of the depth given by
+    /// `params`.
+    pub fn new_with_params(
+        root_history_len: usize,
+        params: &DPCParams,
+    ) -> Result<Self, DPCApiError> {
+        if root_history_len == 0 {
+            return Err(DPCApiError::InvalidParameter(
+                "Ledger state must accept at least one merkle root".to_string(),
+            ));
+        }
+        let records_merkle_tree = MerkleTree::new(params.tree_depth).ok_or_else(|| {
+            DPCApiError::InternalError("Unable to create records merkle tree".to_string())
+        })?;
+        let mut ledger = Self {
+            records_merkle_tree,
+            nullifiers: BTreeSet::new(),
+            recent_roots: VecDeque::with_capacity(root_history_len),
+            root_history_len,
+            tree_depth: params.tree_depth,
+            fee_rates: BTreeMap::new(),
+            current_time: 0,
+            memo_time_check: false,
+        };
+        ledger
+            .fee_rates
+            .insert(NATIVE_ASSET_CODE, FeeRate::new(1, 1));
+        ledger.record_current_root();
+        Ok(ledger)
+    }
+
+    /// Current Merkle root of the record commitments accumulator
+    pub fn root(&self) -> NodeValue {
+        self.records_merkle_tree.commitment().root_value
+    }
+
+    /// Number of records accumulated so far
+    pub fn num_records(&self) -> u64 {
+        self.records_merkle_tree.num_leaves()
+    }
+
+    /// Return true if `root` is among the recent roots accepted by the ledger
+    pub fn is_valid_root(&self, root: &NodeValue) -> bool {
+        self.recent_roots.contains(root)
+    }
+
+    /// Return true if `nullifier` has already been published
+    pub fn is_spent(&self, nullifier: &Nullifier) -> bool {
+        self.nullifiers.contains(nullifier)
+    }
+
+    /// Enable or disable the check of the time stated in the first element of
+    /// the memo of notes against the current time, see `check_memo_time`.
+    /// The check is disabled by default.
+    pub fn set_memo_time_check(&mut self, enabled: bool) {
+        self.memo_time_check = enabled;
+    }
+
+    /// Whether the memo time of notes is checked against the current time
+    pub fn memo_time_check(&self) -> bool {
+        self.memo_time_check
+    }
+
+    /// Current time of the ledger, zero for a new ledger
+    pub fn current_time(&self) -> u64 {
+        self.current_time
+    }
+
+    /// Set the current time of the ledger to `time`, unless it is before the
+    /// current time: the time of the ledger never goes backward.
+    pub fn advance_time(&mut self, time: u64) {
+        self.current_time = self.current_time.max(time);
+    }
+
+    /// Accept fees paid in `asset_code` at the exchange rate `rate`, replacing
+    /// the rate of an already approved asset. The rate of the native asset
+    /// cannot be changed.
+    pub fn approve_fee_asset(
+        &mut self,
+        asset_code: InnerScalarField,
+        rate: FeeRate,
+    ) -> Result<(), DPCApiError> {
+        if asset_code == NATIVE_ASSET_CODE {
+            return Err(DPCApiError::InvalidParameter(
+                "Cannot change the fee rate of the native asset".to_string(),
+            ));
+        }
+        if rate.asset_amount == 0 {
+            return Err(DPCApiError::InvalidParameter(
+                "Fee rate must have a non-zero asset amount".to_string(),
+            ));
+        }
+        self.fee_rates.insert(asset_code, rate);
+        Ok(())
+    }
+
+    /// Stop accepting fees paid in `asset_code`, return true if the asset was
+    /// approved. The native asset cannot be revoked.
+    pub fn revoke_fee_asset(&mut self, asset_code: &InnerScalarField) -> bool {
+        *asset_code != NATIVE_ASSET_CODE && self.fee_rates.remove(asset_code).is_some()
+    }
+
+    /// Exchange rate of `asset_code` if fees can be paid in it
+    pub fn fee_rate(&self, asset_code: &InnerScalarField) -> Option<FeeRate> {
+        self.fee_rates.get(asset_code).copied()
+    }
+
+    /// Value in native asset of the fee of a note, rounded down. Return an
+    /// error if the fee asset of the note is not approved.
+    pub fn native_fee(&self, aux_info: &DPCNoteAuxInfo) -> Result<u64, DPCApiError> {
+        let rate = self
+            .fee_rate(&aux_info.fee_asset)
+            .ok_or(TxnVerificationError::UnapprovedFeeAsset(aux_info.fee_asset))?;
+        let native_fee =
+            aux_info.fee as u128 * rate.native_amount as u128 / rate.asset_amount as u128;
+        u64::try_from(native_fee).map_err(|_| {
+            DPCApiError::OverOrUnderFlow(format!("Native fee {} overflows u64", native_fee))
+        })
+    }
+
+    /// Append a record commitment outside of any transaction (e.g. a genesis
+    /// record), return its position (uid) in the accumulator.
+    pub fn insert_record_commitment(
+        &mut self,
+        commitment: CommitmentValue,
+    ) -> Result<u64, DPCApiError> {
+        self.check_capacity(1)?;
+        let uid = self.num_records();
+        self.records_merkle_tree.push(commitment);
+        self.record_current_root();
+        Ok(uid)
+    }
+
+    /// Membership witness of the record at position `uid` w.r.t. the current
+    /// Merkle root.
+    pub fn membership_witness(
+        &self,
+        uid: u64,
+    ) -> Result<AccMemberWitness<InnerScalarField>, DPCApiError> {
+        AccMemberWitness::lookup_from_tree(&self.records_merkle_tree, uid)
+            .expect_ok()
+            .map(|(_, witness)| witness)
+            .map_err(|_| {
+                DPCApiError::InvalidParameter(format!(
+                    "No record with uid {} in the ledger, number of records {}",
+                    uid,
+                    self.num_records()
+                ))
+            })
+    }
+
+    /// Check that a transaction note can be applied to the current state:
+    /// it refers to a recent Merkle root, its nullifiers are unique and
+    /// unspent, its fee asset is approved, its memo time is not after the
+    /// current time if `memo_time_check` is enabled, and its proofs and
+    /// authorization signature are valid.
+    pub fn validate_note(
+        &self,
+        note: &DPCTxnNote,
+        verifying_key: &DPCVerifyingKey,
+    ) -> Result<(), DPCApiError> {
+        self.check_note_consistency(
+            &note.body.aux_info.merkle_root,
+            &note.body.input_nullifiers,
+            note.body.output_commitments.len(),
+        )?;
+        self.native_fee(&note.body.aux_info)?;
+        if self.memo_time_check {
+            check_memo_time(&note.body.memo, self.current_time)?;
+        }
+        note.verify(verifying_key, note.body.aux_info.merkle_root)
+    }
+
+    /// Validate and apply a transaction note atomically: either the state is
+    /// left untouched and an error is returned, or all nullifiers are marked
+    /// as spent and all output commitments are accumulated. Return the uids of
+    /// the new records, in the same order as `output_commitments`.
+    pub fn apply_note(
+        &mut self,
+        note: &DPCTxnNote,
+        verifying_key: &DPCVerifyingKey,
+    ) -> Result<Vec<u64>, DPCApiError> {
+        self.validate_note(note, verifying_key)?;
+        Ok(self.apply_unchecked(&note.body.input_nullifiers, &note.body.output_commitments))
+    }
+
+    // Update the state without any check, return the uids of the new records.
+    fn apply_unchecked(
+        &mut self,
+        nullifiers: &[Nullifier],
+        output_commitments: &[CommitmentValue],
+    ) -> Vec<u64> {
+        self.nullifiers.extend(nullifiers.iter().cloned());
+        let first_uid = self.num_records();
+        for commitment in output_commitments.iter() {
+            self.records_merkle_tree.push(*commitment);
+        }
+        self.record_current_root();
+        (first_uid..self.num_records()).collect()
+    }
+
+    // Check the public data of a note against the ledger state.
+    fn check_note_consistency(
+        &self,
+        merkle_root: &NodeValue,
+        nullifiers: &[Nullifier],
+        num_outputs: usize,
+    ) -> Result<(), DPCApiError> {
+        if !self.is_valid_root(merkle_root) {
+            return Err(TxnVerificationError::UnknownMerkleRoot(*merkle_root).into());
+        }
+        let mut note_nullifiers = BTreeSet::new();
+        for (index, nullifier) in nullifiers.iter().enumerate() {
+            if !note_nullifiers.insert(nullifier) {
+                return Err(TxnVerificationError::DuplicateNullifier(index).into());
+            }
+            if self.is_spent(nullifier) {
+                return Err(TxnVerificationError::DoubleSpend(index).into());
+            }
+        }
+        self.check_capacity(num_outputs)
+    }
+
+    fn check_capacity(&self, num_new_records: usize) -> Result<(), DPCApiError> {
+        let capacity = 3u64.pow(self.tree_depth as u32);
+        if self.num_records() + num_new_records as u64 > capacity {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Records merkle tree is full, capacity {}",
+                capacity
+            )));
+        }
+        Ok(())
+    }
+
+    fn record_current_root(&mut self) {
+        if self.recent_roots.len() == self.root_history_len {
+            self.recent_roots.pop_front();
+        }
+        self.recent_roots.push_back(self.root());
+    }
+}
+
+/// Check that the time stated in the first element of `memo` is not after
+/// `current_time`. An empty memo states no time.
+pub fn check_memo_time(memo: &[InnerScalarField], current_time: u64) -> Result<(), DPCApiError> {
+    match memo.first() {
+        Some(time) if *time > InnerScalarField::from(current_time) => {
+            Err(TxnVerificationError::FutureMemoTime {
+                time: *time,
+                current_time,
+            }
+            .into())
+        },
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::UniformRand;
+
+    #[test]
+    fn test_ledger_state() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        assert!(LedgerState::new(0).is_err());
+
+        let mut ledger = LedgerState::new(2)?;
+        let genesis_root = ledger.root();
+        assert!(ledger.is_valid_root(&genesis_root));
+
+        // accumulate records and check witnesses against current root
+        let rc = CommitmentValue::rand(rng);
+        assert_eq!(ledger.insert_record_commitment(rc)?, 0);
+        let witness = ledger.membership_witness(0)?;
+        assert_eq!(witness.root, ledger.root());
+        assert!(ledger.membership_witness(1).is_err());
+
+        // apply a note
+        let nullifiers = vec![
+            Nullifier(InnerScalarField::rand(rng)),
+            Nullifier(InnerScalarField::rand(rng)),
+        ];
+        let outputs = vec![CommitmentValue::rand(rng), CommitmentValue::rand(rng)];
+        let root = ledger.root();
+        ledger.check_note_consistency(&root, &nullifiers, outputs.len())?;
+        assert_eq!(ledger.apply_unchecked(&nullifiers, &outputs), vec![1, 2]);
+        assert!(nullifiers.iter().all(|nf| ledger.is_spent(nf)));
+        assert_eq!(ledger.num_records(), 3);
+
+        // only the 2 most recent roots are valid
+        assert!(ledger.is_valid_root(&root));
+        assert!(!ledger.is_valid_root(&genesis_root));
+        assert!(matches!(
+            ledger.check_note_consistency(&genesis_root, &[], 1),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::UnknownMerkleRoot(_)
+            ))
+        ));
+
+        // double spending
+        let root = ledger.root();
+        assert!(matches!(
+            ledger.check_note_consistency(&root, &nullifiers[1..], 1),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::DoubleSpend(0)
+            ))
+        ));
+
+        // duplicate nullifiers within a note
+        let fresh = Nullifier(InnerScalarField::rand(rng));
+        assert!(matches!(
+            ledger.check_note_consistency(&root, &[fresh.clone(), fresh.clone()], 1),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::DuplicateNullifier(1)
+            ))
+        ));
+        assert!(ledger.check_note_consistency(&root, &[fresh], 1).is_ok());
+
+        // a tree of depth 1 holds at most 3 records
+        let params = DPCParams::new(1, 2, 0)?;
+        let mut small_ledger = LedgerState::new_with_params(2, &params)?;
+        let root = small_ledger.root();
+        assert!(small_ledger.check_note_consistency(&root, &[], 4).is_err());
+        assert!(small_ledger.check_note_consistency(&root, &[], 3).is_ok());
+        small_ledger.insert_record_commitment(CommitmentValue::rand(rng))?;
+        assert!(small_ledger.check_note_consistency(&root, &[], 3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fee_assets() -> Result<(), DPCApiError> {
+        let mut ledger = LedgerState::new(2)?;
+        let asset_code = InnerScalarField::from(2u64);
+        let mut aux_info = DPCNoteAuxInfo {
+            fee: 10,
+            ..Default::default()
+        };
+
+        // only the native asset is approved by default
+        aux_info.fee_asset = NATIVE_ASSET_CODE;
+        assert_eq!(ledger.native_fee(&aux_info)?, 10);
+        aux_info.fee_asset = asset_code;
+        assert!(matches!(
+            ledger.native_fee(&aux_info),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::UnapprovedFeeAsset(code)
+            )) if code == asset_code
+        ));
+
+        // 4 units of the asset are worth 3 native units
+        assert!(ledger
+            .approve_fee_asset(NATIVE_ASSET_CODE, FeeRate::new(2, 1))
+            .is_err());
+        assert!(ledger
+            .approve_fee_asset(asset_code, FeeRate::new(3, 0))
+            .is_err());
+        ledger.approve_fee_asset(asset_code, FeeRate::new(3, 4))?;
+        assert_eq!(ledger.fee_rate(&asset_code), Some(FeeRate::new(3, 4)));
+        assert_eq!(ledger.native_fee(&aux_info)?, 7);
+        aux_info.fee = u64::MAX;
+        ledger.approve_fee_asset(asset_code, FeeRate::new(2, 1))?;
+        assert!(ledger.native_fee(&aux_info).is_err());
+
+        assert!(!ledger.revoke_fee_asset(&NATIVE_ASSET_CODE));
+        assert!(ledger.revoke_fee_asset(&asset_code));
+        assert!(!ledger.revoke_fee_asset(&asset_code));
+        assert!(ledger.native_fee(&aux_info).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_memo_time() -> Result<(), DPCApiError> {
+        let mut ledger = LedgerState::new(2)?;
+        assert!(!ledger.memo_time_check());
+        ledger.set_memo_time_check(true);
+        assert!(ledger.memo_time_check());
+        assert_eq!(ledger.current_time(), 0);
+        ledger.advance_time(20);
+        ledger.advance_time(10);
+        assert_eq!(ledger.current_time(), 20);
+
+        let memo = |time: u64| vec![InnerScalarField::from(time), InnerScalarField::from(7u64)];
+        check_memo_time(&memo(0), ledger.current_time())?;
+        check_memo_time(&memo(20), ledger.current_time())?;
+        check_memo_time(&[], ledger.current_time())?;
+        assert!(matches!(
+            check_memo_time(&memo(21), ledger.current_time()),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::FutureMemoTime {
+                    current_time: 20,
+                    ..
+                }
+            ))
+        ));
+        assert!(check_memo_time(&[-InnerScalarField::from(1u64)], u64::MAX).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "prover")]
+    fn test_sponsored_note_in_fee_asset() -> Result<(), DPCApiError> {
+        use crate::{
+            app::PredicateOps,
+            examples::zcash_example::ZcashPredicate,
+            keys::KeyChainMasterKey,
+            proofs::{universal_setup_inner, universal_setup_outer},
+            structs::{NoteInput, Payload, RecordOpening},
+            transaction::TransactionBuilder,
+        };
+        use ark_std::borrow::Cow;
+
+        let rng = &mut ark_std::test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let params = DPCParams::default();
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 2, &params)?;
+
+        let sponsor_msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (sponsor_ak, sponsor_pgk, sponsor_ivk) = sponsor_msk.derive_key_chain_single_consumer();
+        let (sponsor, sponsor_rd) =
+            sponsor_msk.derive_diversified_address(&sponsor_pgk, &sponsor_ivk, 0)?;
+        let user_msk = KeyChainMasterKey::generate([2u8; 32], &[]);
+        let (user_ak, user_pgk, user_ivk) = user_msk.derive_key_chain_single_consumer();
+        let (user, user_rd) = user_msk.derive_diversified_address(&user_pgk, &user_ivk, 0)?;
+
+        // the sponsor holds a record of a non-native fee asset, the user a
+        // record of the application
+        let fee_asset = InnerScalarField::from(7u64);
+        let payload = |value: u64| {
+            Payload::from_scalars(&[InnerScalarField::from(2u64), InnerScalarField::from(value)])
+        };
+        let fee_record = RecordOpening::new_fee_asset_with_params(
+            rng,
+            sponsor,
+            fee_asset,
+            100,
+            0,
+            Nullifier::default(),
+            &params,
+        );
+        let user_record = RecordOpening::new(
+            rng,
+            user.clone(),
+            payload(40)?,
+            birth_pid.0,
+            death_pid.0,
+            0,
+            Nullifier::default(),
+        );
+        let mut ledger = LedgerState::new_with_params(DEFAULT_ROOT_HISTORY_LEN, &params)?;
+        let fee_uid = ledger.insert_record_commitment(fee_record.derive_record_commitment()?)?;
+        let user_uid = ledger.insert_record_commitment(user_record.derive_record_commitment()?)?;
+        let fee_input = NoteInput {
+            ro: fee_record,
+            acc_member_witness: ledger.membership_witness(fee_uid)?,
+            proof_gen_key: Cow::Borrowed(&sponsor_pgk),
+            authorization_randomizer: Default::default(),
+            diversifier_randomizer: sponsor_rd,
+        };
+        let user_input = NoteInput {
+            ro: user_record,
+            acc_member_witness: ledger.membership_witness(user_uid)?,
+            proof_gen_key: Cow::Borrowed(&user_pgk),
+            authorization_randomizer: Default::default(),
+            diversifier_randomizer: user_rd,
+        };
+
+        // the sponsor pays the fee of the transfer of the user
+        let note = TransactionBuilder::new(
+            &dpc_pk,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+        )
+        .fee_input(fee_input, &sponsor_ak)
+        .add_input(user_input, &user_ak)
+        .add_recipient(user, payload(40)?, None)
+        .fee(10)
+        .build(rng)?;
+        assert_eq!(note.body.aux_info.fee_asset, fee_asset);
+
+        // the proofs accept any fee asset, the ledger only approved ones
+        note.verify(&dpc_vk, ledger.root())?;
+        assert!(matches!(
+            ledger.validate_note(&note, &dpc_vk),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::UnapprovedFeeAsset(code)
+            )) if code == fee_asset
+        ));
+        ledger.approve_fee_asset(fee_asset, FeeRate::new(1, 2))?;
+        assert_eq!(ledger.native_fee(&note.body.aux_info)?, 5);
+        assert_eq!(ledger.apply_note(&note, &dpc_vk)?, vec![2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "prover")]
+    fn test_memo_time_check() -> Result<(), DPCApiError> {
+        use crate::{
+            app::PredicateOps,
+            constants::MEMO_LEN,
+            examples::{
+                tests::{build_notes, build_notes_and_records},
+                zcash_example::ZcashPredicate,
+            },
+            keys::KeyChainMasterKey,
+            proofs::{universal_setup_inner, universal_setup_outer},
+            structs::Payload,
+            transaction::TransactionBuilder,
+        };
+        use ark_ff::Zero;
+
+        let rng = &mut ark_std::test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 2, &DPCParams::default())?;
+
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (input_records, _) = build_notes_and_records(
+            rng,
+            &addr,
+            &pgk,
+            300,
+            295,
+            2,
+            &[40],
+            &[],
+            birth_pid,
+            death_pid,
+        )?;
+        let mut inputs = build_notes(&input_records, &pgk, &rd)?;
+        let app_input = inputs.pop().unwrap();
+        let fee_input = inputs.pop().unwrap();
+
+        // a note of an application that puts other data than a time in its memo
+        let mut memo = vec![InnerScalarField::zero(); MEMO_LEN];
+        memo[0] = -InnerScalarField::from(1u64);
+        let note = TransactionBuilder::new(
+            &dpc_pk,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+        )
+        .fee_input(fee_input, &ak)
+        .add_input(app_input, &ak)
+        .add_recipient(
+            addr,
+            Payload::from_scalars(&[InnerScalarField::from(2u64), InnerScalarField::from(40u64)])?,
+            None,
+        )
+        .fee(5)
+        .memo(memo)
+        .build(rng)?;
+
+        // the ledgers share the accumulator the notes refer to
+        let mut ledger = LedgerState::new(2)?;
+        for ro in input_records.iter() {
+            ledger.insert_record_commitment(ro.derive_record_commitment()?)?;
+        }
+        assert_eq!(ledger.root(), note.body.aux_info.merkle_root);
+        let mut time_lock_ledger = ledger.clone();
+        time_lock_ledger.set_memo_time_check(true);
+        time_lock_ledger.advance_time(u64::MAX);
+
+        // the memo time is only checked by ledgers that enable it
+        assert!(matches!(
+            time_lock_ledger.validate_note(&note, &dpc_vk),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::FutureMemoTime { .. }
+            ))
+        ));
+        assert_eq!(ledger.apply_note(&note, &dpc_vk)?, vec![2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "prover")]
+    fn test_apply_proven_note() -> Result<(), DPCApiError> {
+        use crate::{
+            app::PredicateOps,
+            constants::TREE_DEPTH,
+            examples::zcash_example::ZcashPredicate,
+            keys::KeyChainMasterKey,
+            proofs::{universal_setup_inner, universal_setup_outer},
+            structs::{NoteInput, Payload, RecordOpening},
+            transaction::TransactionBuilder,
+        };
+        use ark_std::borrow::Cow;
+
+        let rng = &mut ark_std::test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 2, &DPCParams::default())?;
+
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let payload = |value: u64| {
+            Payload::from_scalars(&[InnerScalarField::from(2u64), InnerScalarField::from(value)])
+        };
+
+        // genesis records: a fee record and a record of the application
+        let fee_record =
+            RecordOpening::new_native_asset(rng, addr.clone(), 300, 0, Nullifier::default());
+        let app_record = RecordOpening::new(
+            rng,
+            addr.clone(),
+            payload(40)?,
+            birth_pid.0,
+            death_pid.0,
+            0,
+            Nullifier::default(),
+        );
+        let records = [fee_record, app_record];
+        let mut ledger = LedgerState::new(2)?;
+        let mut expected_tree = MerkleTree::new(TREE_DEPTH).unwrap();
+        for ro in records.iter() {
+            let commitment = ro.derive_record_commitment()?;
+            ledger.insert_record_commitment(commitment)?;
+            expected_tree.push(commitment);
+        }
+        let notes = records
+            .iter()
+            .enumerate()
+            .map(|(uid, ro)| {
+                Ok(NoteInput {
+                    ro: ro.clone(),
+                    acc_member_witness: ledger.membership_witness(uid as u64)?,
+                    proof_gen_key: Cow::Borrowed(&pgk),
+                    authorization_randomizer: Default::default(),
+                    diversifier_randomizer: rd.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, DPCApiError>>()?;
+
+        let note = TransactionBuilder::new(
+            &dpc_pk,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+        )
+        .fee_input(notes[0].clone(), &ak)
+        .add_input(notes[1].clone(), &ak)
+        .add_recipient(addr, payload(40)?, None)
+        .fee(5)
+        .build(rng)?;
+        let genesis_root = ledger.root();
+        assert_eq!(note.body.aux_info.merkle_root, genesis_root);
+        assert!(note
+            .body
+            .input_nullifiers
+            .iter()
+            .all(|nf| !ledger.is_spent(nf)));
+
+        // the outputs are appended after the genesis records
+        assert_eq!(ledger.apply_note(&note, &dpc_vk)?, vec![2, 3]);
+        for commitment in note.body.output_commitments.iter() {
+            expected_tree.push(*commitment);
+        }
+        assert_eq!(ledger.num_records(), 4);
+        assert_eq!(ledger.root(), expected_tree.commitment().root_value);
+        assert_ne!(ledger.root(), genesis_root);
+        assert!(ledger.is_valid_root(&genesis_root));
+        assert!(note
+            .body
+            .input_nullifiers
+            .iter()
+            .all(|nf| ledger.is_spent(nf)));
+
+        // replaying the note is rejected and leaves the state untouched
+        let root = ledger.root();
+        assert!(matches!(
+            ledger.apply_note(&note, &dpc_vk),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::DoubleSpend(0)
+            ))
+        ));
+        assert_eq!(ledger.root(), root);
+        assert_eq!(ledger.num_records(), 4);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_padded_notes() -> Result<(), DPCApiError> {
+        use crate::{
+            examples::tests::{build_notes, build_notes_and_records},
+            keys::KeyChainMasterKey,
+            structs::PolicyIdentifier,
+            transaction::pad_records,
+        };
+
+        let rng = &mut ark_std::test_rng();
+        let params = DPCParams::default();
+        let mut ledger = LedgerState::new(2)?;
+
+        // two notes spending only a fee input, each padded with 2 dummy inputs
+        for seed in 1..3u8 {
+            let msk = KeyChainMasterKey::generate([seed; 32], &[]);
+            let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+            let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+            let (input_records, mut outputs) = build_notes_and_records(
+                rng,
+                &addr,
+                &pgk,
+                300,
+                295,
+                0,
+                &[],
+                &[],
+                PolicyIdentifier::default(),
+                PolicyIdentifier::default(),
+            )?;
+            let mut inputs = build_notes(&input_records, &pgk, &rd)?;
+            pad_records(
+                rng,
+                3,
+                &params,
+                &mut inputs,
+                &mut outputs,
+                PolicyIdentifier::default(),
+                PolicyIdentifier::default(),
+            )?;
+            let nullifiers = inputs
+                .iter()
+                .map(|input| input.ro.nullify(&input.proof_gen_key.nk))
+                .collect::<Result<Vec<_>, _>>()?;
+            let output_commitments = outputs
+                .iter()
+                .map(|output| output.derive_record_commitment())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let root = ledger.root();
+            ledger.check_note_consistency(&root, &nullifiers, output_commitments.len())?;
+            ledger.apply_unchecked(&nullifiers, &output_commitments);
+        }
+        assert_eq!(ledger.num_records(), 6);
+        Ok(())
+    }
+}
@@ -10,6 +10,10 @@
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! Library for Decentralized Private Computation (DPC) scheme
+//!
+//! Without the default `prover` feature, only the verification of
+//! transaction notes, key derivation and receiver memo decryption are
+//! available, e.g. for light clients.
 
 #![deny(missing_docs)]
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -17,16 +21,26 @@
 #[cfg(test)]
 extern crate std;
 
-#[cfg(test)]
+#[cfg(feature = "prover")]
+pub mod app;
+pub mod assets;
+#[cfg(all(test, feature = "prover"))]
 mod bench;
+#[cfg(feature = "prover")]
 mod circuit;
 pub mod constants;
 pub mod errors;
-mod examples;
+#[cfg(feature = "prover")]
+pub mod examples;
 pub mod keys;
+pub mod ledger;
+pub mod params;
+#[cfg(feature = "prover")]
 pub mod predicates;
 pub mod proofs;
 pub mod structs;
 pub mod transaction;
 mod types;
+#[cfg(feature = "prover")]
 mod utils;
+pub mod wallet;
@@ -0,0 +1,196 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Configurable record layout and accumulator parameters
+
+use crate::{
+    constants::{MEMO_LEN, PAYLOAD_DATA_LEN, TREE_DEPTH},
+    errors::DPCApiError,
+};
+use ark_serialize::*;
+use ark_std::{format, string::ToString};
+use jf_utils::tagged_blob;
+
+/// Parameters of a DPC instance: the depth of the records Merkle tree, the
+/// length of record payloads and the length of transaction memos.
+///
+/// Keys generated by `preprocess` are bound to one set of parameters, and
+/// records and notes must be built with the same parameters as the keys.
+/// `DPCParams::default()` uses `TREE_DEPTH`, `PAYLOAD_DATA_LEN` and
+/// `MEMO_LEN`.
+#[tagged_blob("DPC_PARAMS")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, CanonicalSerialize)]
+pub struct DPCParams {
+    pub(crate) tree_depth: u8,
+    pub(crate) payload_data_len: usize,
+    pub(crate) memo_len: usize,
+}
+
+// Same encoding as `DPCParams`, deserialized before the parameters are
+// validated by `DPCParams::new`.
+#[derive(CanonicalDeserialize)]
+struct RawDPCParams {
+    tree_depth: u8,
+    payload_data_len: usize,
+    memo_len: usize,
+}
+
+impl CanonicalDeserialize for DPCParams {
+    fn deserialize<R>(reader: R) -> Result<Self, SerializationError>
+    where
+        R: Read,
+    {
+        let raw = RawDPCParams::deserialize(reader)?;
+        Self::new(raw.tree_depth, raw.payload_data_len, raw.memo_len)
+            .map_err(|_| SerializationError::InvalidData)
+    }
+}
+
+impl Default for DPCParams {
+    fn default() -> Self {
+        Self {
+            tree_depth: TREE_DEPTH,
+            payload_data_len: PAYLOAD_DATA_LEN,
+            memo_len: MEMO_LEN,
+        }
+    }
+}
+
+impl DPCParams {
+    /// Create a new set of parameters. The payload must hold at least an
+    /// asset code and an amount, and the tree cannot be empty.
+    pub fn new(
+        tree_depth: u8,
+        payload_data_len: usize,
+        memo_len: usize,
+    ) -> Result<Self, DPCApiError> {
+        if tree_depth == 0 {
+            return Err(DPCApiError::InvalidParameter(
+                "Records merkle tree depth must be positive".to_string(),
+            ));
+        }
+        if payload_data_len < 2 {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Payload data length {} is too short, at least 2 are needed",
+                payload_data_len
+            )));
+        }
+        Ok(Self {
+            tree_depth,
+            payload_data_len,
+            memo_len,
+        })
+    }
+
+    /// Depth of the records Merkle tree
+    pub fn tree_depth(&self) -> u8 {
+        self.tree_depth
+    }
+
+    /// Length of record payload data
+    pub fn payload_data_len(&self) -> usize {
+        self.payload_data_len
+    }
+
+    /// Length of transaction memo
+    pub fn memo_len(&self) -> usize {
+        self.memo_len
+    }
+}
+
+/// Shape of the transactions supported by a set of keys: the number of
+/// non-fee inputs (and outputs) and the domain size of the unmerged inner
+/// predicate circuits.
+///
+/// Keys generated by `preprocess` only verify notes of their own shape, which
+/// is returned by `DPCTxnBody::shape`.
+#[tagged_blob("DPC_KEY_SHAPE")]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    CanonicalSerialize,
+    CanonicalDeserialize,
+)]
+pub struct KeyShape {
+    /// Number of inputs (and of outputs) excluding the fee
+    pub non_fee_input_size: usize,
+    /// Domain size of the unmerged inner predicate circuits
+    pub unmerged_inner_policy_domain_size: usize,
+}
+
+impl KeyShape {
+    /// Create a new key shape
+    pub fn new(non_fee_input_size: usize, unmerged_inner_policy_domain_size: usize) -> Self {
+        Self {
+            non_fee_input_size,
+            unmerged_inner_policy_domain_size,
+        }
+    }
+
+    /// Number of inputs (and of outputs) including the fee
+    pub fn entire_input_size(&self) -> usize {
+        self.non_fee_input_size + 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_std::vec::Vec;
+    use jf_utils::test_serde_default;
+
+    #[test]
+    fn test_params() {
+        assert!(DPCParams::new(0, 8, 8).is_err());
+        assert!(DPCParams::new(26, 1, 8).is_err());
+        let params = DPCParams::new(32, 16, 0).unwrap();
+        assert_eq!(params.tree_depth(), 32);
+        assert_eq!(params.payload_data_len(), 16);
+        assert_eq!(params.memo_len(), 0);
+        test_serde_default!(DPCParams);
+
+        // invalid parameters are rejected when deserialized
+        let mut bytes = Vec::new();
+        params.serialize(&mut bytes).unwrap();
+        assert_eq!(DPCParams::deserialize(&bytes[..]).unwrap(), params);
+        for invalid in [
+            DPCParams {
+                tree_depth: 0,
+                ..params
+            },
+            DPCParams {
+                payload_data_len: 1,
+                ..params
+            },
+        ] {
+            let mut bytes = Vec::new();
+            invalid.serialize(&mut bytes).unwrap();
+            assert!(DPCParams::deserialize(&bytes[..]).is_err());
+            assert!(DPCParams::deserialize_unchecked(&bytes[..]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_key_shape() {
+        let shape = KeyShape::new(2, 1 << 15);
+        assert_eq!(shape.entire_input_size(), 3);
+        assert!(shape < KeyShape::new(3, 1 << 14));
+        assert!(shape < KeyShape::new(2, 1 << 16));
+        test_serde_default!(KeyShape);
+    }
+}
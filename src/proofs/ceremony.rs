@@ -0,0 +1,403 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local multi-party ceremony generating the universal parameters.
+//!
+//! A ceremony transcript holds the current `PowersOfTau` of a curve. It starts
+//! from the trivial powers of `tau = 1`, then each participant multiplies
+//! `tau` by a secret `s` of their own, appends a `ContributionProof` to the
+//! transcript and discards `s`. The final `tau` stays unknown as long as a
+//! single participant is honest. Anyone can check the whole chain of
+//! contributions from the transcript alone.
+//!
+//! `DPCCeremony` runs the ceremonies for the inner (BLS12-377) and the outer
+//! (BW6-761) curves side by side, every participant contributing to both.
+
+use crate::{
+    errors::DPCApiError,
+    proofs::srs::PowersOfTau,
+    types::{InnerPairingEngine, InnerUniversalParam, OuterPairingEngine, OuterUniversalParam},
+};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_serialize::*;
+use ark_std::{
+    format,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec,
+    vec::Vec,
+};
+use jf_plonk::proof_system::structs::UniversalSrs;
+use sha2::{Digest, Sha256};
+
+const CEREMONY_DOMAIN_SEPARATOR: &[u8] = b"VERI-ZEXE SRS CEREMONY";
+
+/// Proof that a contribution multiplied the previous `tau` by a non-zero
+/// secret `s` known to the participant.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ContributionProof<E: PairingEngine> {
+    /// `[tau]_1` after the contribution
+    pub tau_g1: E::G1Affine,
+    /// `[s]_1`
+    pub s_g1: E::G1Affine,
+    /// `[s]_2`
+    pub s_g2: E::G2Affine,
+    /// Commitment of the Schnorr proof of knowledge of `s`
+    pub pok_commitment: E::G1Affine,
+    /// Response of the Schnorr proof of knowledge of `s`
+    pub pok_response: E::Fr,
+}
+
+impl<E: PairingEngine> ContributionProof<E> {
+    // Check the proof w.r.t. `[tau]_1` before the contribution.
+    fn verify(&self, prev_tau_g1: &E::G1Affine) -> Result<(), DPCApiError> {
+        let g1 = E::G1Affine::prime_subgroup_generator();
+        let g2 = E::G2Affine::prime_subgroup_generator();
+        if self.s_g1.is_zero() || self.tau_g1.is_zero() {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Contribution is degenerate".to_string(),
+            ));
+        }
+        let challenge = pok_challenge(
+            prev_tau_g1,
+            &self.tau_g1,
+            &self.s_g1,
+            &self.s_g2,
+            &self.pok_commitment,
+        )?;
+        if g1.mul(self.pok_response)
+            != self.pok_commitment.into_projective() + self.s_g1.mul(challenge)
+        {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Invalid proof of knowledge of the contribution secret".to_string(),
+            ));
+        }
+        if E::pairing(self.s_g1, g2) != E::pairing(g1, self.s_g2) {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Contribution secret differs in G1 and G2".to_string(),
+            ));
+        }
+        if E::pairing(self.tau_g1, g2) != E::pairing(*prev_tau_g1, self.s_g2) {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Contribution does not update the previous powers".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Fiat-Shamir challenge of the proof of knowledge, bound to the previous
+// powers so that a proof cannot be replayed in another position of the chain.
+fn pok_challenge<E: PairingEngine>(
+    prev_tau_g1: &E::G1Affine,
+    tau_g1: &E::G1Affine,
+    s_g1: &E::G1Affine,
+    s_g2: &E::G2Affine,
+    pok_commitment: &E::G1Affine,
+) -> Result<E::Fr, DPCApiError> {
+    let mut bytes = CEREMONY_DOMAIN_SEPARATOR.to_vec();
+    prev_tau_g1.serialize(&mut bytes)?;
+    tau_g1.serialize(&mut bytes)?;
+    s_g1.serialize(&mut bytes)?;
+    s_g2.serialize(&mut bytes)?;
+    pok_commitment.serialize(&mut bytes)?;
+    Ok(E::Fr::from_le_bytes_mod_order(&Sha256::digest(&bytes)))
+}
+
+/// Transcript of the ceremony of a single curve: the current powers of tau
+/// and the proofs of all contributions so far.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CeremonyTranscript<E: PairingEngine> {
+    /// Powers of tau after the last contribution
+    pub powers: PowersOfTau<E>,
+    /// Proofs of the contributions, in order
+    pub contributions: Vec<ContributionProof<E>>,
+}
+
+impl<E: PairingEngine> CeremonyTranscript<E> {
+    /// Start a ceremony for polynomials of degree up to `max_degree`
+    pub fn new(max_degree: usize) -> Result<Self, DPCApiError> {
+        if max_degree == 0 {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Ceremony max degree must be positive".to_string(),
+            ));
+        }
+        Ok(Self {
+            powers: PowersOfTau {
+                tau_powers_g1: vec![E::G1Affine::prime_subgroup_generator(); max_degree + 1],
+                tau_powers_g2: vec![E::G2Affine::prime_subgroup_generator(); 2],
+            },
+            contributions: vec![],
+        })
+    }
+
+    /// Multiply `tau` by a fresh secret sampled from `rng`, return the proof
+    /// of the contribution, also appended to the transcript.
+    ///
+    /// NOTE: the transcript is not verified, participants should call
+    /// `verify` before contributing.
+    pub fn contribute<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<ContributionProof<E>, DPCApiError> {
+        if self.powers.tau_powers_g1.len() < 2 {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Ceremony transcript is malformed".to_string(),
+            ));
+        }
+        let mut s = E::Fr::rand(rng);
+        while s.is_zero() {
+            s = E::Fr::rand(rng);
+        }
+        let prev_tau_g1 = self.powers.tau_powers_g1[1];
+        self.powers.tau_powers_g1 = scale_powers(&self.powers.tau_powers_g1, s);
+        self.powers.tau_powers_g2 = scale_powers(&self.powers.tau_powers_g2, s);
+
+        let g1 = E::G1Affine::prime_subgroup_generator();
+        let g2 = E::G2Affine::prime_subgroup_generator();
+        let tau_g1 = self.powers.tau_powers_g1[1];
+        let s_g1 = g1.mul(s).into_affine();
+        let s_g2 = g2.mul(s).into_affine();
+        let k = E::Fr::rand(rng);
+        let pok_commitment = g1.mul(k).into_affine();
+        let challenge = pok_challenge(&prev_tau_g1, &tau_g1, &s_g1, &s_g2, &pok_commitment)?;
+        let proof = ContributionProof {
+            tau_g1,
+            s_g1,
+            s_g2,
+            pok_commitment,
+            pok_response: k + challenge * s,
+        };
+        self.contributions.push(proof.clone());
+        Ok(proof)
+    }
+
+    /// Verify the chain of contributions: it has at least one contribution,
+    /// every contribution proof is valid, and the current powers are
+    /// consistent powers of the `tau` of the last contribution.
+    pub fn verify<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), DPCApiError> {
+        if self.contributions.is_empty() {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Ceremony has no contribution".to_string(),
+            ));
+        }
+        let g1 = E::G1Affine::prime_subgroup_generator();
+        let g2 = E::G2Affine::prime_subgroup_generator();
+        let mut tau_g1 = g1;
+        for (index, proof) in self.contributions.iter().enumerate() {
+            proof.verify(&tau_g1).map_err(|e| {
+                DPCApiError::ParametersGenerationError(format!(
+                    "Contribution {} is invalid: {}",
+                    index, e
+                ))
+            })?;
+            tau_g1 = proof.tau_g1;
+        }
+        if self.powers.tau_powers_g1.first() != Some(&g1)
+            || self.powers.tau_powers_g2.first() != Some(&g2)
+            || self.powers.tau_powers_g1.get(1) != Some(&tau_g1)
+        {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Powers of tau do not match the last contribution".to_string(),
+            ));
+        }
+        self.powers.verify(rng)
+    }
+
+    /// Verify the transcript and output universal parameters supporting
+    /// polynomials of degree up to the ceremony max degree.
+    pub fn into_universal_srs<R: RngCore + CryptoRng>(
+        self,
+        rng: &mut R,
+    ) -> Result<UniversalSrs<E>, DPCApiError> {
+        self.verify(rng)?;
+        let max_degree = self.powers.max_degree();
+        self.powers.into_universal_srs(max_degree, rng)
+    }
+}
+
+// Return `[s^i * x_i]` for the points `[x_i]`.
+fn scale_powers<G: AffineCurve>(points: &[G], s: G::ScalarField) -> Vec<G> {
+    let mut s_power = G::ScalarField::one();
+    let mut scaled: Vec<G::Projective> = points
+        .iter()
+        .map(|point| {
+            let scaled = point.mul(s_power);
+            s_power *= s;
+            scaled
+        })
+        .collect();
+    G::Projective::batch_normalization(&mut scaled);
+    scaled.iter().map(|point| point.into_affine()).collect()
+}
+
+/// Proof of a contribution to both ceremonies of a `DPCCeremony`
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DPCContributionProof {
+    /// Contribution to the inner parameters
+    pub inner: ContributionProof<InnerPairingEngine>,
+    /// Contribution to the outer parameters
+    pub outer: ContributionProof<OuterPairingEngine>,
+}
+
+/// Ceremony generating both the `InnerUniversalParam` and the
+/// `OuterUniversalParam` of the DPC scheme.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DPCCeremony {
+    /// Ceremony of the BLS12-377 powers
+    pub inner: CeremonyTranscript<InnerPairingEngine>,
+    /// Ceremony of the BW6-761 powers
+    pub outer: CeremonyTranscript<OuterPairingEngine>,
+}
+
+impl DPCCeremony {
+    /// Start a ceremony for the given max degrees of the inner and outer
+    /// universal parameters.
+    pub fn new(inner_max_degree: usize, outer_max_degree: usize) -> Result<Self, DPCApiError> {
+        Ok(Self {
+            inner: CeremonyTranscript::new(inner_max_degree)?,
+            outer: CeremonyTranscript::new(outer_max_degree)?,
+        })
+    }
+
+    /// Number of contributions so far
+    pub fn num_contributions(&self) -> usize {
+        self.inner.contributions.len()
+    }
+
+    /// Proof of the `index`-th contribution, if any
+    pub fn contribution_proof(&self, index: usize) -> Option<DPCContributionProof> {
+        Some(DPCContributionProof {
+            inner: self.inner.contributions.get(index)?.clone(),
+            outer: self.outer.contributions.get(index)?.clone(),
+        })
+    }
+
+    /// Contribute fresh randomness from `rng` to both ceremonies, return the
+    /// proof of the contribution.
+    pub fn contribute<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<DPCContributionProof, DPCApiError> {
+        Ok(DPCContributionProof {
+            inner: self.inner.contribute(rng)?,
+            outer: self.outer.contribute(rng)?,
+        })
+    }
+
+    /// Verify both chains of contributions, which must have been produced by
+    /// the same number of participants.
+    pub fn verify<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), DPCApiError> {
+        if self.inner.contributions.len() != self.outer.contributions.len() {
+            return Err(DPCApiError::ParametersGenerationError(format!(
+                "Inner and outer ceremonies have {} and {} contributions",
+                self.inner.contributions.len(),
+                self.outer.contributions.len()
+            )));
+        }
+        self.inner.verify(rng)?;
+        self.outer.verify(rng)
+    }
+
+    /// Verify the ceremony and output the inner and outer universal
+    /// parameters.
+    pub fn into_universal_params<R: RngCore + CryptoRng>(
+        self,
+        rng: &mut R,
+    ) -> Result<(InnerUniversalParam, OuterUniversalParam), DPCApiError> {
+        self.verify(rng)?;
+        Ok((
+            self.inner.into_universal_srs(rng)?,
+            self.outer.into_universal_srs(rng)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_ceremony_transcript_helper<E: PairingEngine>() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        let mut transcript = CeremonyTranscript::<E>::new(8)?;
+        assert!(CeremonyTranscript::<E>::new(0).is_err());
+        // the trivial powers are not an output
+        assert!(transcript.verify(rng).is_err());
+
+        let first = transcript.contribute(rng)?;
+        transcript.verify(rng)?;
+        transcript.contribute(rng)?;
+        transcript.verify(rng)?;
+        assert_eq!(transcript.contributions.len(), 2);
+        assert_eq!(transcript.contributions[0], first);
+
+        // transcript round trip
+        let mut bytes = Vec::new();
+        transcript.serialize(&mut bytes)?;
+        assert_eq!(
+            CeremonyTranscript::<E>::deserialize(&bytes[..])?,
+            transcript
+        );
+
+        // a contribution cannot be dropped or replayed
+        let mut bad = transcript.clone();
+        bad.contributions.remove(0);
+        assert!(bad.verify(rng).is_err());
+        let mut bad = transcript.clone();
+        bad.contributions[1] = first;
+        assert!(bad.verify(rng).is_err());
+
+        // the proof of knowledge must be valid
+        let mut bad = transcript.clone();
+        bad.contributions[1].pok_response += E::Fr::one();
+        assert!(bad.verify(rng).is_err());
+
+        // powers must match the last contribution
+        let mut bad = transcript.clone();
+        bad.powers = CeremonyTranscript::<E>::new(8)?.powers;
+        assert!(bad.verify(rng).is_err());
+        let mut bad = transcript.clone();
+        bad.powers.tau_powers_g1[3] = bad.powers.tau_powers_g1[2];
+        assert!(bad.verify(rng).is_err());
+
+        let srs = transcript.clone().into_universal_srs(rng)?;
+        assert_eq!(srs.powers_of_g_ref(), &transcript.powers.tau_powers_g1[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ceremony_transcript() -> Result<(), DPCApiError> {
+        test_ceremony_transcript_helper::<InnerPairingEngine>()?;
+        test_ceremony_transcript_helper::<OuterPairingEngine>()
+    }
+
+    #[test]
+    fn test_dpc_ceremony() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        let mut ceremony = DPCCeremony::new(8, 16)?;
+        let proof = ceremony.contribute(rng)?;
+        ceremony.contribute(rng)?;
+        assert_eq!(ceremony.num_contributions(), 2);
+        assert_eq!(ceremony.contribution_proof(0), Some(proof));
+        assert!(ceremony.contribution_proof(2).is_none());
+        ceremony.verify(rng)?;
+
+        let mut bad = ceremony.clone();
+        bad.outer.contribute(rng)?;
+        assert!(bad.verify(rng).is_err());
+
+        let (inner_srs, outer_srs) = ceremony.into_universal_params(rng)?;
+        assert_eq!(inner_srs.powers_of_g_ref().len(), 9);
+        assert_eq!(outer_srs.powers_of_g_ref().len(), 17);
+        Ok(())
+    }
+}
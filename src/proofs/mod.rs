@@ -11,21 +11,35 @@
 
 //! APIs for proof generations and verifications
 
+#[cfg(feature = "prover")]
 use crate::{
     errors::DPCApiError,
     types::{InnerPairingEngine, InnerUniversalParam, OuterPairingEngine, OuterUniversalParam},
 };
+#[cfg(feature = "prover")]
 use ark_std::rand::{CryptoRng, RngCore};
+#[cfg(feature = "prover")]
 use jf_plonk::proof_system::PlonkKzgSnark;
 
+#[cfg(feature = "prover")]
+pub mod ceremony;
 pub(crate) mod policies_vfy;
 pub(crate) mod predicates;
+pub mod registry;
+#[cfg(feature = "prover")]
+pub mod srs;
+#[cfg(feature = "prover")]
+pub mod stats;
 pub mod transaction;
 pub(crate) mod utxo;
 
 /// One-time universal setup for parameters to be used in proving utxo relation
-/// and inner predicate proofs
-#[allow(dead_code)] // TODO make it public or create upper layer api
+/// and inner predicate proofs.
+///
+/// NOTE: the secret is sampled from `rng`, use for testing only. Ceremony
+/// outputs are imported with `srs::load_inner_srs`, or generated with
+/// `ceremony::DPCCeremony`.
+#[cfg(feature = "prover")]
 pub fn universal_setup_inner<R: RngCore + CryptoRng>(
     max_degree: usize,
     rng: &mut R,
@@ -38,7 +52,11 @@ pub fn universal_setup_inner<R: RngCore + CryptoRng>(
 
 /// One-time universal setup for parameters to be used in the outer circuit that
 /// recursively verifies inner policy proofs.
-#[allow(dead_code)] // TODO make it public or create upper layer api
+///
+/// NOTE: the secret is sampled from `rng`, use for testing only. Ceremony
+/// outputs are imported with `srs::load_outer_srs`, or generated with
+/// `ceremony::DPCCeremony`.
+#[cfg(feature = "prover")]
 pub fn universal_setup_outer<R: RngCore + CryptoRng>(
     max_degree: usize,
     rng: &mut R,
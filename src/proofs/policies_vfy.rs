@@ -9,36 +9,50 @@
 // FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+#[cfg(feature = "prover")]
 use crate::{
     circuit::policies_vfy::PoliciesVfyCircuit,
-    errors::DPCApiError,
     structs::{derive_predicates_commitment, PolicyIdentifier},
-    types::{
-        CommitmentValue, InnerBaseField, InnerG1Affine, InnerG1Group, InnerPairingEngine,
-        InnerScalarField, InnerUniversalParam, OuterPairingEngine, OuterUniversalParam,
-    },
+    types::{InnerPairingEngine, InnerScalarField, OuterUniversalParam},
+};
+use crate::{
+    errors::{DPCApiError, TxnVerificationError},
+    types::{CommitmentValue, InnerBaseField, InnerG1Affine, InnerG1Group, OuterPairingEngine},
 };
+#[cfg(feature = "prover")]
 use ark_ec::ProjectiveCurve;
+#[cfg(feature = "prover")]
 use ark_ff::Zero;
+#[cfg(feature = "prover")]
+use ark_serialize::*;
+#[cfg(feature = "prover")]
 use ark_std::{
     format,
     rand::{CryptoRng, RngCore},
     string::ToString,
-    vec,
-    vec::Vec,
 };
+use ark_std::{vec, vec::Vec};
 use jf_plonk::{
-    circuit::{customized::ecc::Point, Circuit},
+    circuit::customized::ecc::Point,
     proof_system::{
-        batch_arg::BatchArgument,
-        structs::{BatchProof, Proof, ProvingKey, VerifyingKey},
+        structs::{Proof, VerifyingKey},
         PlonkKzgSnark, Snark,
     },
-    transcript::{RescueTranscript, StandardTranscript},
+    transcript::StandardTranscript,
+};
+#[cfg(feature = "prover")]
+use jf_plonk::{
+    circuit::Circuit,
+    proof_system::{
+        batch_arg::BatchArgument,
+        structs::{BatchProof, ProvingKey},
+    },
+    transcript::RescueTranscript,
 };
 use jf_utils::fr_to_fq;
 
-#[derive(Clone, Debug, PartialEq)]
+#[cfg(feature = "prover")]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct PoliciesVfyProvingKey<'a> {
     /// The actual proving key
     pub(crate) proving_key: ProvingKey<'a, OuterPairingEngine>,
@@ -65,6 +79,7 @@ pub(crate) struct PoliciesVfyPublicInput {
     pub(crate) partial_plonk_proof: InnerPartialVfyProof,
 }
 
+#[cfg(feature = "prover")]
 #[derive(Debug, Clone)]
 /// Witness for an outer policies verification circuit.
 pub(crate) struct PoliciesVfyWitness {
@@ -80,6 +95,7 @@ pub(crate) struct PoliciesVfyWitness {
     pub(crate) blind_partial_proof: InnerScalarField,
 }
 
+#[cfg(feature = "prover")]
 #[derive(Debug)]
 /// Struct for the constant parameters of a policies verification circuit
 pub(crate) struct PoliciesVfyParams {
@@ -99,28 +115,21 @@ pub(crate) struct PoliciesVfyParams {
 /// Pre-process to generate a pair of keys for policies circuit
 /// input:
 /// - outer circuit SRS
-/// - inner circuit SRS
+/// - dummy policies circuit, from `PoliciesVfyCircuit::build_for_preprocessing`
 /// - number of (birth, death) predicate pairs
-/// - inner circuit domain size
 /// output:
 /// - proving key
 /// - verification key
-/// - total number of constraints of the policies circuit
+#[cfg(feature = "prover")]
 pub fn preprocess<'a>(
     outer_srs: &'a OuterUniversalParam,
-    inner_srs: &InnerUniversalParam,
+    dummy_circuit: &PoliciesVfyCircuit,
     num_input_records: usize,
-    inner_policy_domain_size: usize,
-) -> Result<(PoliciesVfyProvingKey<'a>, PoliciesVfyVerifyingKey, usize), DPCApiError> {
-    let (dummy_circuit, n_constraints) = PoliciesVfyCircuit::build_for_preprocessing(
-        inner_srs,
-        num_input_records,
-        inner_policy_domain_size,
-    )?;
+) -> Result<(PoliciesVfyProvingKey<'a>, PoliciesVfyVerifyingKey), DPCApiError> {
     let (proving_key, verifying_key) =
         PlonkKzgSnark::<OuterPairingEngine>::preprocess(outer_srs, &dummy_circuit.0).map_err(
             |e| {
-                DPCApiError::InvalidParameters(format!(
+                DPCApiError::InvalidParameter(format!(
                     "Preprocessing policy circuit of {}-inputs failed: {}",
                     num_input_records, e
                 ))
@@ -133,7 +142,6 @@ pub fn preprocess<'a>(
             num_input_records,
         },
         verifying_key,
-        n_constraints,
     ))
 }
 
@@ -146,6 +154,7 @@ pub fn preprocess<'a>(
 /// - extra transcript message
 /// output:
 /// - proof
+#[cfg(feature = "prover")]
 pub(crate) fn prove<R>(
     rng: &mut R,
     proving_key: &PoliciesVfyProvingKey,
@@ -160,7 +169,7 @@ where
     if proving_key.num_input_records != witness.input_death_vks.len()
         || proving_key.num_input_records != witness.output_birth_vks.len()
     {
-        return Err(DPCApiError::InvalidParameters(format!(
+        return Err(DPCApiError::InvalidParameter(format!(
             "Expected input numbers: {}, actual number of birth and death predicates {} {}",
             proving_key.num_input_records,
             witness.output_birth_vks.len(),
@@ -207,14 +216,30 @@ pub(crate) fn verify(
         proof,
         extra_transcript_init_msg,
     )
-    .map_err(|e| {
-        DPCApiError::GeneralError(format!(
-            "Policies circuit proof verification failure: {}",
-            e
-        ))
-    })
+    .map_err(|_| TxnVerificationError::InvalidOuterProof.into())
+}
+
+pub(crate) fn batch_verify(
+    verifying_key: &PoliciesVfyVerifyingKey,
+    public_inputs: &[&PoliciesVfyPublicInput],
+    proofs: &[&PoliciesVfyValidityProof],
+) -> Result<(), DPCApiError> {
+    let pub_inputs: Vec<Vec<InnerBaseField>> = public_inputs
+        .iter()
+        .map(|public_input| public_input.to_scalars())
+        .collect();
+    let pub_inputs_ref: Vec<&[InnerBaseField]> =
+        pub_inputs.iter().map(|input| input.as_slice()).collect();
+    PlonkKzgSnark::<OuterPairingEngine>::batch_verify::<StandardTranscript>(
+        &vec![verifying_key; proofs.len()],
+        &pub_inputs_ref,
+        proofs,
+        &vec![None; proofs.len()],
+    )
+    .map_err(|_| TxnVerificationError::InvalidOuterProof.into())
 }
 
+#[cfg(feature = "prover")]
 impl PoliciesVfyWitness {
     /// Create a dummy witness for a transaction with `num_input` input records
     /// where each inner record policy circuit has domain size
@@ -250,7 +275,7 @@ impl PoliciesVfyWitness {
         blind_partial_proof: InnerScalarField,
     ) -> Result<Self, DPCApiError> {
         if input_death_vks.len() != output_birth_vks.len() {
-            return Err(DPCApiError::InvalidParameters(
+            return Err(DPCApiError::InvalidParameter(
                 "input death predicates and output birth predicates length mismatch".to_string(),
             ));
         }
@@ -264,6 +289,7 @@ impl PoliciesVfyWitness {
     }
 }
 
+#[cfg(feature = "prover")]
 impl PoliciesVfyPublicInput {
     /// Compute the public input from witness
     pub(crate) fn from_witness(
@@ -310,7 +336,9 @@ impl PoliciesVfyPublicInput {
             partial_plonk_proof,
         })
     }
+}
 
+impl PoliciesVfyPublicInput {
     /// Flatten out all pubic input fields into a vector of InnerBaseFields.
     /// Note that the order matters.
     pub(crate) fn to_scalars(&self) -> Vec<InnerBaseField> {
@@ -328,7 +356,7 @@ impl PoliciesVfyPublicInput {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod tests {
     use super::*;
     use crate::{
@@ -349,12 +377,12 @@ mod tests {
         let outer_srs = universal_setup_outer(max_outer_degree, rng)?;
 
         let num_inputs = 2;
-        let (pk, vk, _n_constraints) = preprocess(
-            &outer_srs,
+        let (dummy_circuit, _n_constraints) = PoliciesVfyCircuit::build_for_preprocessing(
             &inner_srs,
             num_inputs,
             INNER_DOMAIN_SIZE_FOR_TEST,
         )?;
+        let (pk, vk) = preprocess(&outer_srs, &dummy_circuit, num_inputs)?;
         let (witness, pub_input, params) =
             build_policies_vfy_circuit_params(rng, &inner_srs, num_inputs)?;
 
@@ -384,12 +412,13 @@ mod tests {
 
         // bad verification key
         {
-            let (_pk, bad_verification_key, _n_constraints) = preprocess(
-                &outer_srs,
+            let (bad_dummy_circuit, _n_constraints) = PoliciesVfyCircuit::build_for_preprocessing(
                 &inner_srs,
                 num_inputs - 1,
                 INNER_DOMAIN_SIZE_FOR_TEST,
             )?;
+            let (_pk, bad_verification_key) =
+                preprocess(&outer_srs, &bad_dummy_circuit, num_inputs - 1)?;
             assert!(verify(&bad_verification_key, &pub_input, None, &proof).is_err());
         }
 
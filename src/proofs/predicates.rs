@@ -10,13 +10,19 @@
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    errors::DPCApiError,
+    constants::dom_sep::BATCH_DECIDE_DOM_SEP,
+    errors::{DPCApiError, TxnVerificationError},
+    types::{InnerG1Projective, InnerPairingEngine, InnerScalarField},
+};
+#[cfg(feature = "prover")]
+use crate::{
     predicates::PredicateTrait,
-    types::{
-        InnerBaseField, InnerPairingEngine, InnerScalarField, InnerTranscript, InnerUniversalParam,
-    },
+    types::{InnerBaseField, InnerTranscript, InnerUniversalParam},
 };
 use ark_ec::AffineCurve;
+use ark_ff::{One, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+#[cfg(feature = "prover")]
 use ark_std::{
     format,
     rand::{CryptoRng, RngCore},
@@ -24,11 +30,13 @@ use ark_std::{
     vec,
     vec::Vec,
 };
+use jf_plonk::proof_system::{batch_arg::BatchArgument, structs::OpenKey};
+#[cfg(feature = "prover")]
 use jf_plonk::{
     circuit::{Circuit, PlonkCircuit},
     proof_system::{
-        batch_arg::{BatchArgument, Instance as PlonkPredicate},
-        structs::{BatchProof, OpenKey, ProvingKey, VerifyingKey},
+        batch_arg::Instance as PlonkPredicate,
+        structs::{BatchProof, ProvingKey, VerifyingKey},
         PlonkKzgSnark,
     },
     transcript::RescueTranscript,
@@ -37,18 +45,26 @@ use jf_plonk::{
 
 use super::policies_vfy::InnerPartialVfyProof;
 
+#[cfg(feature = "prover")]
 #[derive(Clone, Debug)]
 /// A wrapper of the plonk circuit
-pub struct PredicateCircuit(pub(crate) PlonkCircuit<InnerScalarField>);
+pub struct PredicateCircuit(pub PlonkCircuit<InnerScalarField>);
 
 /// This type can be an instantiation of either a birth predicate or a death
 /// predicate
+///
+/// NOTE: a predicate borrows from the inner SRS and is neither `Send` nor
+/// `Sync`, as the gates of its jf-plonk circuit are boxed trait objects. It is
+/// used on the thread that proves the transaction, see
+/// `transaction::generate_proof`.
+#[cfg(feature = "prover")]
 #[derive(Clone)]
 pub struct Predicate<'a> {
     pub(crate) is_finalized: bool,
     pub(crate) predicate: PlonkPredicate<'a, InnerPairingEngine>,
 }
 
+#[cfg(feature = "prover")]
 impl<'a> PredicateTrait<'a> for Predicate<'a> {
     /// the actual, unwrapped predicate.
     type PlonkPredicate = PlonkPredicate<'a, InnerPairingEngine>;
@@ -130,6 +146,7 @@ impl<'a> PredicateTrait<'a> for Predicate<'a> {
 /// - death predicate
 /// output:
 /// - proof
+#[cfg(feature = "prover")]
 pub fn prove<'a, P, R>(
     rng: &mut R,
     birth_predicates: &[P],
@@ -153,7 +170,7 @@ where
         RescueTranscript<InnerBaseField>,
     >(rng, &birth_predicates, &death_predicates)
     .map_err(|e| {
-        DPCApiError::InvalidParameters(format!("Preprocessing predicate circuit of failed: {}", e))
+        DPCApiError::InvalidParameter(format!("Preprocessing predicate circuit of failed: {}", e))
     })?;
 
     Ok(proof)
@@ -167,6 +184,7 @@ where
 /// - batched proof
 /// output:
 /// - Ok if the verification passes; or an error if fails
+#[cfg(feature = "prover")]
 pub fn verify<'a, P>(
     birth_vks: &[&P::VerificationKey],
     death_vks: &[&P::VerificationKey],
@@ -194,7 +212,7 @@ where
         &pub_inputs,
         batch_proof,
     )
-    .map_err(|e| DPCApiError::InvalidParameters(format!("Inner batch proof is invalid: {}", e)))
+    .map_err(|e| DPCApiError::InvalidParameter(format!("Inner batch proof is invalid: {}", e)))
 }
 
 /// Decide if an inner circuit partial verification proof is valid.
@@ -209,21 +227,46 @@ pub(crate) fn decide(
 ) -> Result<(), DPCApiError> {
     let inner1 = inner_partial_proof.0.into_projective();
     let inner2 = inner_partial_proof.1.into_projective();
-    let b = BatchArgument::decide(open_key, inner1, inner2).map_err(|e| {
-        DPCApiError::InvalidParameters(format!(
-            "Inner partial verification proof is invalid: {}",
-            e
-        ))
-    })?;
+    let b = BatchArgument::decide(open_key, inner1, inner2)
+        .map_err(|_| TxnVerificationError::FailedPairingCheck)?;
+    if !b {
+        return Err(TxnVerificationError::FailedPairingCheck.into());
+    }
+    Ok(())
+}
+
+/// Check a batch of inner partial verification proofs with a single final
+/// pairing check, by deciding a random linear combination of them. The
+/// coefficients are derived by hashing all the proofs in the batch.
+pub(crate) fn batch_decide(
+    open_key: &OpenKey<InnerPairingEngine>,
+    inner_partial_proofs: &[&InnerPartialVfyProof],
+) -> Result<(), DPCApiError> {
+    let mut transcript = BATCH_DECIDE_DOM_SEP.as_bytes().to_vec();
+    for proof in inner_partial_proofs.iter() {
+        proof.0.serialize(&mut transcript)?;
+        proof.1.serialize(&mut transcript)?;
+    }
+    let mut inner1 = InnerG1Projective::zero();
+    let mut inner2 = InnerG1Projective::zero();
+    for (i, proof) in inner_partial_proofs.iter().enumerate() {
+        let coeff: InnerScalarField = if i == 0 {
+            InnerScalarField::one()
+        } else {
+            jf_utils::hash_to_field([&transcript[..], &i.to_le_bytes()].concat())
+        };
+        inner1 += &proof.0.mul(coeff.into_repr());
+        inner2 += &proof.1.mul(coeff.into_repr());
+    }
+    let b = BatchArgument::decide(open_key, inner1, inner2)
+        .map_err(|_| TxnVerificationError::FailedPairingCheck)?;
     if !b {
-        return Err(DPCApiError::GeneralError(
-            "Inner partial verification proof doesn't pass the final pairing check".to_string(),
-        ));
+        return Err(TxnVerificationError::FailedPairingCheck.into());
     }
     Ok(())
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod tests {
     use super::*;
     use ark_ff::UniformRand;
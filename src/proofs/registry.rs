@@ -0,0 +1,277 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Registry of verifying keys for notes of different shapes.
+//!
+//! Keys are bound to a `KeyShape`, i.e. to a number of inputs and to an inner
+//! predicate domain size, and a note implies the shape of the keys it was
+//! generated with (see `DPCTxnBody::shape`). A `KeyRegistry` caches one
+//! verifying key per shape, and can be persisted so that validators do not
+//! preprocess the same keys on every start.
+//!
+//! Notes are only verified with the keys inserted, loaded or explicitly
+//! preprocessed by the owner of the registry, never with keys of a shape
+//! chosen by the sender of a note: preprocessing is expensive and the number
+//! of shapes is unbounded.
+
+use crate::{
+    errors::DPCApiError,
+    params::{DPCParams, KeyShape},
+    proofs::transaction::DPCVerifyingKey,
+    transaction::DPCTxnNote,
+    types::{InnerUniversalParam, NodeValue, OuterUniversalParam},
+};
+use ark_serialize::*;
+use ark_std::{collections::BTreeMap, format, vec::Vec};
+
+type UniversalParams<'a> = (&'a InnerUniversalParam, &'a OuterUniversalParam);
+
+/// Cache of DPC verifying keys indexed by shape
+#[derive(Clone)]
+pub struct KeyRegistry<'a> {
+    srs: Option<UniversalParams<'a>>,
+    params: DPCParams,
+    verifying_keys: BTreeMap<KeyShape, DPCVerifyingKey>,
+}
+
+impl<'a> KeyRegistry<'a> {
+    /// Create an empty registry that only serves the keys inserted or loaded
+    /// into it.
+    pub fn new(params: DPCParams) -> Self {
+        Self {
+            srs: None,
+            params,
+            verifying_keys: BTreeMap::new(),
+        }
+    }
+
+    /// Create an empty registry that can preprocess keys from the universal
+    /// parameters with `preprocess`, which requires the `prover` feature.
+    pub fn with_srs(
+        inner_srs: &'a InnerUniversalParam,
+        outer_srs: &'a OuterUniversalParam,
+        params: DPCParams,
+    ) -> Self {
+        Self {
+            srs: Some((inner_srs, outer_srs)),
+            params,
+            verifying_keys: BTreeMap::new(),
+        }
+    }
+
+    /// Parameters of all the keys of the registry
+    pub fn params(&self) -> &DPCParams {
+        &self.params
+    }
+
+    /// Shapes of the cached keys, in increasing order
+    pub fn shapes(&self) -> impl Iterator<Item = &KeyShape> {
+        self.verifying_keys.keys()
+    }
+
+    /// Cached verifying key for `shape`, if any
+    pub fn get(&self, shape: &KeyShape) -> Option<&DPCVerifyingKey> {
+        self.verifying_keys.get(shape)
+    }
+
+    /// Add a verifying key to the registry, replacing the cached key of the
+    /// same shape. The key must have been generated for the parameters of
+    /// the registry.
+    pub fn insert(&mut self, verifying_key: DPCVerifyingKey) -> Result<(), DPCApiError> {
+        if verifying_key.params() != &self.params {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Verifying key parameters {:?} do not match registry parameters {:?}",
+                verifying_key.params(),
+                self.params
+            )));
+        }
+        self.verifying_keys
+            .insert(verifying_key.shape(), verifying_key);
+        Ok(())
+    }
+
+    /// Cached verifying key for `shape`. Return an error if the registry has
+    /// no key for `shape`, keys are never preprocessed on demand.
+    pub fn verifying_key(&self, shape: KeyShape) -> Result<&DPCVerifyingKey, DPCApiError> {
+        self.get(&shape).ok_or_else(|| {
+            DPCApiError::InvalidParameter(format!("No verifying key for shape {:?}", shape))
+        })
+    }
+
+    /// Preprocess the verifying key for `shape` from the universal parameters
+    /// and add it to the registry, unless it is already cached. Return an
+    /// error if the registry has no universal parameters.
+    pub fn preprocess(&mut self, shape: KeyShape) -> Result<&DPCVerifyingKey, DPCApiError> {
+        if !self.verifying_keys.contains_key(&shape) {
+            let srs = self.srs.ok_or_else(|| {
+                DPCApiError::InvalidParameter(format!(
+                    "No universal parameters to preprocess the verifying key for shape {:?}",
+                    shape
+                ))
+            })?;
+            let verifying_key = preprocess_verifying_key(srs, shape, &self.params)?;
+            self.verifying_keys.insert(shape, verifying_key);
+        }
+        self.verifying_key(shape)
+    }
+
+    /// Verify a transaction note with the verifying key of its shape, which
+    /// must be in the registry. As with
+    /// `DPCTxnNote::verify`, the fee asset of the note is not checked.
+    pub fn verify_note(
+        &self,
+        note: &DPCTxnNote,
+        merkle_root: NodeValue,
+    ) -> Result<(), DPCApiError> {
+        let verifying_key = self.verifying_key(note.body.shape())?;
+        note.verify(verifying_key, merkle_root)
+    }
+
+    /// Write all the cached verifying keys to `writer`
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), DPCApiError> {
+        // same layout as a serialized `Vec<DPCVerifyingKey>`
+        (self.verifying_keys.len() as u64).serialize(&mut writer)?;
+        for verifying_key in self.verifying_keys.values() {
+            verifying_key.serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Add the verifying keys written by `save` to the registry and return
+    /// their number. The keys must have been generated for the parameters of
+    /// the registry.
+    pub fn load<R: Read>(&mut self, reader: R) -> Result<usize, DPCApiError> {
+        let verifying_keys = Vec::<DPCVerifyingKey>::deserialize(reader)?;
+        let num_keys = verifying_keys.len();
+        for verifying_key in verifying_keys {
+            self.insert(verifying_key)?;
+        }
+        Ok(num_keys)
+    }
+}
+
+#[cfg(feature = "prover")]
+fn preprocess_verifying_key(
+    (inner_srs, outer_srs): UniversalParams<'_>,
+    shape: KeyShape,
+    params: &DPCParams,
+) -> Result<DPCVerifyingKey, DPCApiError> {
+    let (_, verifying_key, _) = crate::proofs::transaction::preprocess(
+        outer_srs,
+        inner_srs,
+        shape.non_fee_input_size,
+        shape.unmerged_inner_policy_domain_size,
+        params,
+    )?;
+    Ok(verifying_key)
+}
+
+#[cfg(not(feature = "prover"))]
+fn preprocess_verifying_key(
+    _: UniversalParams<'_>,
+    shape: KeyShape,
+    _: &DPCParams,
+) -> Result<DPCVerifyingKey, DPCApiError> {
+    Err(DPCApiError::InvalidParameter(format!(
+        "Cannot preprocess the verifying key for shape {:?} without the `prover` feature",
+        shape
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "prover")]
+    use crate::proofs::{universal_setup_inner, universal_setup_outer};
+    #[cfg(feature = "prover")]
+    use ark_std::{test_rng, vec};
+
+    #[cfg(feature = "prover")]
+    const INNER_DOMAIN_SIZE_FOR_TEST: usize = 1 << 12;
+
+    #[test]
+    fn test_registry_without_srs() -> Result<(), DPCApiError> {
+        let mut registry = KeyRegistry::new(DPCParams::default());
+        assert!(registry.get(&KeyShape::new(1, 1)).is_none());
+        assert!(registry.verifying_key(KeyShape::new(1, 1)).is_err());
+        assert!(registry.preprocess(KeyShape::new(1, 1)).is_err());
+
+        let mut bytes = Vec::new();
+        registry.save(&mut bytes)?;
+        assert_eq!(registry.load(&bytes[..])?, 0);
+        assert_eq!(registry.shapes().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_registry_with_srs_serves_only_preprocessed_shapes() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let inner_srs = universal_setup_inner(64, rng)?;
+        let outer_srs = universal_setup_outer(64, rng)?;
+        let mut registry = KeyRegistry::with_srs(&inner_srs, &outer_srs, DPCParams::default());
+
+        // shapes of incoming notes are not preprocessed
+        for shape in [KeyShape::new(1, 1), KeyShape::new(100, 1 << 20)] {
+            assert!(registry.verifying_key(shape).is_err());
+        }
+        assert_eq!(registry.shapes().count(), 0);
+
+        // failed preprocessing, here with too small universal parameters,
+        // leaves the registry unchanged
+        assert!(registry
+            .preprocess(KeyShape::new(1, INNER_DOMAIN_SIZE_FOR_TEST))
+            .is_err());
+        assert_eq!(registry.shapes().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "prover")]
+    fn test_registry() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let inner_srs = universal_setup_inner((1 << 16) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 17) + 4, rng)?;
+        let params = DPCParams::default();
+
+        // keys are only served once preprocessed
+        let mut registry = KeyRegistry::with_srs(&inner_srs, &outer_srs, params);
+        let shape_1 = KeyShape::new(1, INNER_DOMAIN_SIZE_FOR_TEST);
+        let shape_2 = KeyShape::new(2, INNER_DOMAIN_SIZE_FOR_TEST);
+        assert!(registry.verifying_key(shape_1).is_err());
+        let vk_1 = registry.preprocess(shape_1)?.clone();
+        assert_eq!(vk_1.shape(), shape_1);
+        assert_eq!(vk_1.num_inputs(), 2);
+        assert_eq!(registry.verifying_key(shape_1)?, &vk_1);
+        assert_eq!(registry.preprocess(shape_1)?, &vk_1);
+        let vk_2 = registry.preprocess(shape_2)?.clone();
+        assert_eq!(vk_2.num_inputs(), 3);
+        assert_eq!(
+            registry.shapes().cloned().collect::<Vec<_>>(),
+            vec![shape_1, shape_2]
+        );
+
+        // persisted keys are served without universal parameters
+        let mut bytes = Vec::new();
+        registry.save(&mut bytes)?;
+        let mut loaded = KeyRegistry::new(params);
+        assert_eq!(loaded.load(&bytes[..])?, 2);
+        assert_eq!(loaded.verifying_key(shape_1)?, &vk_1);
+        assert_eq!(loaded.verifying_key(shape_2)?, &vk_2);
+
+        // keys for other parameters are rejected
+        let mut other = KeyRegistry::new(DPCParams::new(10, 2, 0)?);
+        assert!(other.insert(vk_1).is_err());
+        assert!(other.load(&bytes[..]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Import of universal parameters from powers-of-tau ceremony transcripts.
+//!
+//! There is no established powers-of-tau transcript format for BLS12-377 and
+//! BW6-761, the transcripts read here use a format specific to this project:
+//! the canonical (compressed) arkworks 0.3 serialization of a `PowersOfTau`.
+//! It consists of
+//! - the number `n` of G1 powers as a little-endian `u64`, followed by the
+//!   G1 powers `[tau^i]_1` for `i = 0..n`;
+//! - the number `m` of G2 powers as a little-endian `u64`, followed by the
+//!   G2 powers `[tau^i]_2` for `i = 0..m`,
+//!
+//! where each point is its x-coordinate in little-endian, with the flags of
+//! the point at infinity and of the sign of the y-coordinate in the two most
+//! significant bits of the last byte. Only the first two G2 powers are used
+//! by the KZG commitment scheme, further G2 powers are accepted and ignored.
+//!
+//! Powers output by another ceremony are converted to this format by
+//! re-encoding their points, and `veri-zexe-ceremony import` converts a pair
+//! of transcripts into the universal parameters files read by
+//! `veri-zexe-wallet`.
+
+use crate::{
+    errors::DPCApiError,
+    types::{InnerPairingEngine, InnerUniversalParam, OuterPairingEngine, OuterUniversalParam},
+};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_poly_commit::kzg10::UniversalParams;
+use ark_serialize::*;
+use ark_std::{
+    collections::BTreeMap,
+    format,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+};
+use jf_plonk::proof_system::structs::UniversalSrs;
+
+/// Powers of a secret `tau` output by a powers-of-tau ceremony
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PowersOfTau<E: PairingEngine> {
+    /// `[tau^i]_1` for `i = 0, 1, ...`
+    pub tau_powers_g1: Vec<E::G1Affine>,
+    /// `[tau^i]_2` for `i = 0, 1, ...`
+    pub tau_powers_g2: Vec<E::G2Affine>,
+}
+
+impl<E: PairingEngine> CanonicalSerialize for PowersOfTau<E> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.tau_powers_g1.serialize(&mut writer)?;
+        self.tau_powers_g2.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.tau_powers_g1.serialized_size() + self.tau_powers_g2.serialized_size()
+    }
+
+    fn serialize_uncompressed<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.tau_powers_g1.serialize_uncompressed(&mut writer)?;
+        self.tau_powers_g2.serialize_uncompressed(&mut writer)
+    }
+
+    fn uncompressed_size(&self) -> usize {
+        self.tau_powers_g1.uncompressed_size() + self.tau_powers_g2.uncompressed_size()
+    }
+}
+
+impl<E: PairingEngine> CanonicalDeserialize for PowersOfTau<E> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(Self {
+            tau_powers_g1: Vec::deserialize(&mut reader)?,
+            tau_powers_g2: Vec::deserialize(&mut reader)?,
+        })
+    }
+
+    fn deserialize_uncompressed<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(Self {
+            tau_powers_g1: Vec::deserialize_uncompressed(&mut reader)?,
+            tau_powers_g2: Vec::deserialize_uncompressed(&mut reader)?,
+        })
+    }
+}
+
+impl<E: PairingEngine> PowersOfTau<E> {
+    /// Maximum polynomial degree supported by the powers
+    pub fn max_degree(&self) -> usize {
+        self.tau_powers_g1.len().saturating_sub(1)
+    }
+
+    /// Check that the powers are consistent, i.e. that all of them are powers
+    /// of the same non-trivial `tau`: for all `i`,
+    /// `e([tau^{i+1}]_1, [1]_2) = e([tau^i]_1, [tau]_2)`. The pairing
+    /// equations are batched with random coefficients sampled from `rng`.
+    pub fn verify<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), DPCApiError> {
+        if self.tau_powers_g1.len() < 2 || self.tau_powers_g2.len() < 2 {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Powers of tau need at least 2 powers in G1 and in G2".to_string(),
+            ));
+        }
+        if self.tau_powers_g1[0].is_zero()
+            || self.tau_powers_g2[0].is_zero()
+            || self.tau_powers_g2[1].is_zero()
+        {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Powers of tau are degenerate".to_string(),
+            ));
+        }
+        let coeffs: Vec<_> = (1..self.tau_powers_g1.len())
+            .map(|_| E::Fr::rand(rng).into_repr())
+            .collect();
+        let lhs = VariableBaseMSM::multi_scalar_mul(&self.tau_powers_g1[1..], &coeffs);
+        let rhs = VariableBaseMSM::multi_scalar_mul(
+            &self.tau_powers_g1[..self.tau_powers_g1.len() - 1],
+            &coeffs,
+        );
+        if E::pairing(lhs, self.tau_powers_g2[0]) != E::pairing(rhs, self.tau_powers_g2[1]) {
+            return Err(DPCApiError::ParametersGenerationError(
+                "Powers of tau are not consistent".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verify the powers and convert them into universal parameters
+    /// supporting polynomials of degree up to `max_degree`, as required by
+    /// the key preprocessing.
+    pub fn into_universal_srs<R: RngCore + CryptoRng>(
+        mut self,
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<UniversalSrs<E>, DPCApiError> {
+        if self.max_degree() < max_degree {
+            return Err(DPCApiError::ParametersGenerationError(format!(
+                "Powers of tau support degree {}, but degree {} is required",
+                self.max_degree(),
+                max_degree
+            )));
+        }
+        self.verify(rng)?;
+        self.tau_powers_g1.truncate(max_degree + 1);
+        let h = self.tau_powers_g2[0];
+        let beta_h = self.tau_powers_g2[1];
+        // the hiding powers of KZG10 are not used by the Plonk proofs
+        Ok(UniversalSrs::from(UniversalParams::<E> {
+            powers_of_g: self.tau_powers_g1,
+            powers_of_gamma_g: BTreeMap::new(),
+            h,
+            beta_h,
+            neg_powers_of_h: BTreeMap::new(),
+            prepared_h: h.into(),
+            prepared_beta_h: beta_h.into(),
+        }))
+    }
+}
+
+/// Load the universal parameters for the UTXO and inner predicate circuits
+/// from a BLS12-377 powers-of-tau transcript, checking the consistency of
+/// the powers and that polynomials of degree `max_degree` are supported.
+pub fn load_inner_srs<T: Read, R: RngCore + CryptoRng>(
+    transcript: T,
+    max_degree: usize,
+    rng: &mut R,
+) -> Result<InnerUniversalParam, DPCApiError> {
+    PowersOfTau::<InnerPairingEngine>::deserialize(transcript)?.into_universal_srs(max_degree, rng)
+}
+
+/// Load the universal parameters for the outer circuit from a BW6-761
+/// powers-of-tau transcript, checking the consistency of the powers and that
+/// polynomials of degree `max_degree` are supported.
+pub fn load_outer_srs<T: Read, R: RngCore + CryptoRng>(
+    transcript: T,
+    max_degree: usize,
+    rng: &mut R,
+) -> Result<OuterUniversalParam, DPCApiError> {
+    PowersOfTau::<OuterPairingEngine>::deserialize(transcript)?.into_universal_srs(max_degree, rng)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::One;
+
+    fn powers_of_tau<E: PairingEngine, R: RngCore + CryptoRng>(
+        num_powers: usize,
+        rng: &mut R,
+    ) -> PowersOfTau<E> {
+        let tau = E::Fr::rand(rng);
+        let mut tau_powers = Vec::new();
+        let mut power = E::Fr::one();
+        for _ in 0..num_powers {
+            tau_powers.push(power);
+            power *= tau;
+        }
+        let g1 = E::G1Affine::prime_subgroup_generator();
+        let g2 = E::G2Affine::prime_subgroup_generator();
+        PowersOfTau {
+            tau_powers_g1: tau_powers
+                .iter()
+                .map(|t| g1.mul(*t).into_affine())
+                .collect(),
+            tau_powers_g2: tau_powers[..3]
+                .iter()
+                .map(|t| g2.mul(*t).into_affine())
+                .collect(),
+        }
+    }
+
+    fn test_load_srs_helper<E: PairingEngine>() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        let powers = powers_of_tau::<E, _>(17, rng);
+        assert_eq!(powers.max_degree(), 16);
+        powers.verify(rng)?;
+
+        // transcript round trip
+        let mut transcript = Vec::new();
+        powers.serialize(&mut transcript)?;
+        assert_eq!(PowersOfTau::<E>::deserialize(&transcript[..])?, powers);
+
+        let srs = powers.clone().into_universal_srs(8, rng)?;
+        assert_eq!(srs.powers_of_g_ref(), &powers.tau_powers_g1[..9]);
+        assert!(powers.clone().into_universal_srs(17, rng).is_err());
+
+        // a single inconsistent power is detected
+        let mut bad_powers = powers.clone();
+        bad_powers.tau_powers_g1[5] = bad_powers.tau_powers_g1[4];
+        assert!(bad_powers.verify(rng).is_err());
+        assert!(bad_powers.into_universal_srs(4, rng).is_err());
+
+        let mut bad_powers = powers.clone();
+        bad_powers.tau_powers_g2[1] = bad_powers.tau_powers_g2[2];
+        assert!(bad_powers.verify(rng).is_err());
+
+        let mut bad_powers = powers;
+        bad_powers.tau_powers_g2[1] = E::G2Affine::zero();
+        assert!(bad_powers.verify(rng).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_srs() -> Result<(), DPCApiError> {
+        test_load_srs_helper::<InnerPairingEngine>()?;
+        test_load_srs_helper::<OuterPairingEngine>()?;
+
+        let rng = &mut ark_std::test_rng();
+        let mut transcript = Vec::new();
+        powers_of_tau::<InnerPairingEngine, _>(9, rng).serialize(&mut transcript)?;
+        assert_eq!(
+            load_inner_srs(&transcript[..], 8, rng)?
+                .powers_of_g_ref()
+                .len(),
+            9
+        );
+        assert!(load_outer_srs(&transcript[..], 8, rng).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Size of the circuits behind a set of DPC keys.
+//!
+//! Building the circuits is cheap compared to the universal setup and to
+//! preprocessing, so that `CircuitStats` can be used to choose the maximal
+//! degrees of the inner and outer SRS before generating them.
+
+use crate::{
+    circuit::{policies_vfy::PoliciesVfyCircuit, utxo::DPCUtxoCircuit},
+    errors::DPCApiError,
+    params::{DPCParams, KeyShape},
+    proofs::predicates::PredicateCircuit,
+    types::{InnerUniversalParam, OuterUniversalParam},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+use jf_plonk::{
+    circuit::{Arithmetization, Circuit, PlonkCircuit},
+    MergeableCircuitType,
+};
+
+/// Size of a single circuit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitSize {
+    /// Number of gates before padding
+    pub num_gates: usize,
+    /// Size of the evaluation domain, i.e. number of gates after padding
+    pub domain_size: usize,
+    /// Minimal degree of the SRS the circuit can be preprocessed with
+    pub srs_degree: usize,
+}
+
+impl CircuitSize {
+    // `circuit` must be finalized, `num_gates` is its size before padding
+    fn new<F: PrimeField>(
+        circuit: &PlonkCircuit<F>,
+        num_gates: usize,
+    ) -> Result<Self, DPCApiError> {
+        Ok(Self {
+            num_gates,
+            domain_size: circuit.eval_domain_size()?,
+            srs_degree: circuit.srs_size()?,
+        })
+    }
+
+    fn of_predicate(
+        circuit: &PredicateCircuit,
+        circuit_type: MergeableCircuitType,
+    ) -> Result<Self, DPCApiError> {
+        let mut circuit = circuit.0.clone();
+        let num_gates = circuit.num_gates();
+        if circuit.eval_domain_size().is_err() {
+            circuit.finalize_for_mergeable_circuit(circuit_type)?;
+        }
+        Self::new(&circuit, num_gates)
+    }
+}
+
+/// Size of all the circuits of a key shape, see `CircuitStats::new`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// Shape of the keys preprocessed from the circuits
+    pub shape: KeyShape,
+    /// UTXO circuit, over the inner curve
+    pub utxo: CircuitSize,
+    /// Birth predicate circuit, over the inner curve
+    pub birth_predicate: CircuitSize,
+    /// Death predicate circuit, over the inner curve
+    pub death_predicate: CircuitSize,
+    /// Policies verification (outer) circuit, over the outer curve
+    pub policies_vfy: CircuitSize,
+}
+
+impl CircuitStats {
+    /// Measure the circuits of a transaction with `non_fee_input_size`
+    /// non-fee inputs/outputs, the record layout of `params`, and the given
+    /// dummy birth and death predicate circuits, finalized or not.
+    ///
+    /// Return an error if the domain sizes of the predicate circuits differ,
+    /// as they cannot be merged.
+    pub fn new(
+        non_fee_input_size: usize,
+        birth_predicate_circuit: &PredicateCircuit,
+        death_predicate_circuit: &PredicateCircuit,
+        params: &DPCParams,
+    ) -> Result<Self, DPCApiError> {
+        let birth_predicate =
+            CircuitSize::of_predicate(birth_predicate_circuit, MergeableCircuitType::TypeA)?;
+        let death_predicate =
+            CircuitSize::of_predicate(death_predicate_circuit, MergeableCircuitType::TypeB)?;
+        if birth_predicate.domain_size != death_predicate.domain_size {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Birth predicate domain size {} does not match death predicate domain size {}",
+                birth_predicate.domain_size, death_predicate.domain_size
+            )));
+        }
+        let shape = KeyShape::new(non_fee_input_size, birth_predicate.domain_size);
+        let (utxo, policies_vfy) = dpc_circuit_sizes(shape, params)?;
+
+        Ok(Self {
+            shape,
+            utxo,
+            birth_predicate,
+            death_predicate,
+            policies_vfy,
+        })
+    }
+
+    /// Minimal degree of the inner SRS, used by the UTXO and predicate
+    /// circuits. Same as the first degree of `required_srs_degrees`.
+    pub fn inner_srs_degree(&self) -> usize {
+        self.utxo
+            .srs_degree
+            .max(self.birth_predicate.srs_degree)
+            .max(self.death_predicate.srs_degree)
+    }
+
+    /// Minimal degree of the outer SRS, used by the policies verification
+    /// circuit
+    pub fn outer_srs_degree(&self) -> usize {
+        self.policies_vfy.srs_degree
+    }
+}
+
+/// Minimal degrees of the inner and outer SRS, in this order, to preprocess
+/// the DPC keys for `non_fee_input_size` non-fee inputs/outputs and the
+/// record layout of `params`, and the predicates of domain size
+/// `unmerged_inner_policy_domain_size`.
+///
+/// The degrees are derived from the circuits themselves, which are built but
+/// not preprocessed, so that they can be passed as `max_degree` of
+/// `universal_setup_inner` and `universal_setup_outer`.
+pub fn required_srs_degrees(
+    non_fee_input_size: usize,
+    unmerged_inner_policy_domain_size: usize,
+    params: &DPCParams,
+) -> Result<(usize, usize), DPCApiError> {
+    let shape = KeyShape::new(non_fee_input_size, unmerged_inner_policy_domain_size);
+    let (utxo, policies_vfy) = dpc_circuit_sizes(shape, params)?;
+    Ok((
+        utxo.srs_degree
+            .max(predicate_srs_degree(unmerged_inner_policy_domain_size)),
+        policies_vfy.srs_degree,
+    ))
+}
+
+/// Return an error if the universal parameters are too small to preprocess
+/// the keys of `shape` and its predicates, given the finalized UTXO and
+/// policies verification circuits of `shape` that are about to be
+/// preprocessed.
+pub(crate) fn check_srs_degrees(
+    inner_srs: &InnerUniversalParam,
+    outer_srs: &OuterUniversalParam,
+    shape: KeyShape,
+    utxo_circuit: &DPCUtxoCircuit,
+    policies_vfy_circuit: &PoliciesVfyCircuit,
+) -> Result<(), DPCApiError> {
+    let predicate_degree = predicate_srs_degree(shape.unmerged_inner_policy_domain_size);
+    let inner_degree = utxo_circuit.0.srs_size()?.max(predicate_degree);
+    let outer_degree = policies_vfy_circuit.0.srs_size()?;
+    check_srs_degree(
+        "Inner",
+        inner_srs.powers_of_g_ref().len(),
+        inner_degree,
+        shape,
+    )?;
+    check_srs_degree(
+        "Outer",
+        outer_srs.powers_of_g_ref().len(),
+        outer_degree,
+        shape,
+    )
+}
+
+fn check_srs_degree(
+    name: &str,
+    num_powers: usize,
+    required_degree: usize,
+    shape: KeyShape,
+) -> Result<(), DPCApiError> {
+    let degree = num_powers.saturating_sub(1);
+    if degree < required_degree {
+        return Err(DPCApiError::InvalidParameter(format!(
+            "{} SRS supports degree {}, but degree {} is required for shape {:?}",
+            name, degree, required_degree, shape
+        )));
+    }
+    Ok(())
+}
+
+// Size of the UTXO and policies verification circuits of `shape`
+fn dpc_circuit_sizes(
+    shape: KeyShape,
+    params: &DPCParams,
+) -> Result<(CircuitSize, CircuitSize), DPCApiError> {
+    let (utxo_circuit, utxo_num_gates) =
+        DPCUtxoCircuit::build_for_preprocessing(shape.non_fee_input_size, params)?;
+    let (policies_vfy_circuit, policies_vfy_num_gates) = PoliciesVfyCircuit::build_for_stats(
+        shape.non_fee_input_size,
+        shape.unmerged_inner_policy_domain_size,
+    )?;
+    Ok((
+        CircuitSize::new(&utxo_circuit.0, utxo_num_gates)?,
+        CircuitSize::new(&policies_vfy_circuit.0, policies_vfy_num_gates)?,
+    ))
+}
+
+// Same as `Arithmetization::srs_size` of a finalized predicate circuit: the
+// quotient polynomial of a TurboPlonk circuit has 2 more coefficients than the
+// domain size
+fn predicate_srs_degree(domain_size: usize) -> usize {
+    domain_size + 2
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        app::{BirthPredicateCircuit, DeathPredicateCircuit, PredicateOps},
+        examples::zcash_example::{ZcashPredicate, ZcashPredicateCircuit},
+        proofs::{transaction::preprocess, universal_setup_inner, universal_setup_outer},
+    };
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_circuit_stats() -> Result<(), DPCApiError> {
+        let params = DPCParams::default();
+        let birth_circuit = ZcashPredicateCircuit::preprocessed_birth_circuit(3, &params)?;
+        let death_circuit = ZcashPredicateCircuit::preprocessed_death_circuit(3, &params)?;
+        let stats = CircuitStats::new(2, &birth_circuit.0, &death_circuit.0, &params)?;
+        assert_eq!(
+            stats.shape,
+            KeyShape::new(2, stats.birth_predicate.domain_size)
+        );
+        for size in [
+            stats.utxo,
+            stats.birth_predicate,
+            stats.death_predicate,
+            stats.policies_vfy,
+        ] {
+            assert!(size.domain_size.is_power_of_two());
+            assert!(size.num_gates <= size.domain_size);
+            assert!(size.domain_size < size.srs_degree);
+        }
+        assert!(stats.inner_srs_degree() >= stats.utxo.srs_degree);
+        assert_eq!(stats.outer_srs_degree(), stats.policies_vfy.srs_degree);
+        assert_eq!(
+            stats.birth_predicate.srs_degree,
+            predicate_srs_degree(stats.birth_predicate.domain_size)
+        );
+        assert_eq!(
+            required_srs_degrees(2, stats.shape.unmerged_inner_policy_domain_size, &params)?,
+            (stats.inner_srs_degree(), stats.outer_srs_degree())
+        );
+
+        // finalized predicate circuits have the same domain
+        let mut finalized_birth_circuit = birth_circuit.0.clone();
+        finalized_birth_circuit
+            .0
+            .finalize_for_mergeable_circuit(MergeableCircuitType::TypeA)?;
+        let finalized_stats =
+            CircuitStats::new(2, &finalized_birth_circuit, &death_circuit.0, &params)?;
+        assert_eq!(finalized_stats.shape, stats.shape);
+        assert_eq!(
+            finalized_stats.birth_predicate.domain_size,
+            stats.birth_predicate.domain_size
+        );
+
+        // predicate circuits of different domain sizes cannot be merged
+        let mut large_death_circuit = death_circuit.0.clone();
+        let zero = large_death_circuit.0.zero();
+        for _ in 0..stats.death_predicate.domain_size {
+            large_death_circuit.0.add(zero, zero)?;
+        }
+        assert!(CircuitStats::new(2, &birth_circuit.0, &large_death_circuit, &params).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_circuit_stats_srs_degrees() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let params = DPCParams::default();
+        let birth_circuit = ZcashPredicateCircuit::preprocessed_birth_circuit(3, &params)?;
+        let death_circuit = ZcashPredicateCircuit::preprocessed_death_circuit(3, &params)?;
+        let stats = CircuitStats::new(2, &birth_circuit.0, &death_circuit.0, &params)?;
+
+        // the reported degrees are enough to preprocess all the circuits
+        let inner_srs = universal_setup_inner(stats.inner_srs_degree(), rng)?;
+        let outer_srs = universal_setup_outer(stats.outer_srs_degree(), rng)?;
+        let (_, dpc_vk, (utxo_n_constraints, outer_n_constraints)) = preprocess(
+            &outer_srs,
+            &inner_srs,
+            stats.shape.non_fee_input_size,
+            stats.shape.unmerged_inner_policy_domain_size,
+            &params,
+        )?;
+        assert_eq!(dpc_vk.shape(), stats.shape);
+        assert_eq!(utxo_n_constraints, stats.utxo.num_gates);
+        assert_eq!(outer_n_constraints, stats.policies_vfy.num_gates);
+        ZcashPredicate::preprocess(&inner_srs, &outer_srs, 3, &params)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_preprocess_with_small_srs() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let params = DPCParams::default();
+        let (inner_degree, outer_degree) = required_srs_degrees(1, 1 << 12, &params)?;
+        let small_inner_srs = universal_setup_inner(16, rng)?;
+        let small_outer_srs = universal_setup_outer(16, rng)?;
+        assert!(inner_degree > 16 && outer_degree > 16);
+
+        // fails before preprocessing any circuit
+        match preprocess(&small_outer_srs, &small_inner_srs, 1, 1 << 12, &params) {
+            Err(DPCApiError::InvalidParameter(msg)) => assert!(msg.starts_with("Inner SRS")),
+            _ => panic!("preprocessing with a small inner SRS should fail"),
+        }
+        let shape = KeyShape::new(1, 1 << 12);
+        let (utxo_circuit, _) = DPCUtxoCircuit::build_for_preprocessing(1, &params)?;
+        let (policies_vfy_circuit, _) =
+            PoliciesVfyCircuit::build_for_preprocessing(&small_inner_srs, 1, 1 << 12)?;
+        assert!(check_srs_degrees(
+            &small_inner_srs,
+            &small_outer_srs,
+            shape,
+            &utxo_circuit,
+            &policies_vfy_circuit
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -11,22 +11,17 @@
 
 //! Transaction-related Proof preprocessing, generation, verification API
 
+#[cfg(feature = "prover")]
 use crate::{
+    circuit::{policies_vfy::PoliciesVfyCircuit, utxo::DPCUtxoCircuit},
     constants::{NONNATIVE_FIELD_M, RANGE_BIT_LEN},
-    errors::DPCApiError,
     predicates::PredicateTrait,
     proofs::{
         policies_vfy,
-        policies_vfy::{
-            InnerPartialVfyProof, PoliciesVfyParams, PoliciesVfyProvingKey, PoliciesVfyPublicInput,
-            PoliciesVfyValidityProof, PoliciesVfyVerifyingKey, PoliciesVfyWitness,
-        },
-        predicates,
+        policies_vfy::{PoliciesVfyParams, PoliciesVfyProvingKey, PoliciesVfyWitness},
         predicates::Predicate,
-        utxo::{
-            preprocess_utxo_keys, DPCUtxoPublicInput, DPCUtxoWitness, ProofUtxo, UtxoProvingKey,
-            UtxoVerifyingKey,
-        },
+        stats,
+        utxo::{preprocess_utxo_keys, DPCUtxoWitness, UtxoProvingKey},
     },
     structs::{NoteInput, RecordOpening},
     types::{
@@ -34,14 +29,31 @@ use crate::{
         OuterUniversalParam,
     },
 };
+use crate::{
+    errors::DPCApiError,
+    params::{DPCParams, KeyShape},
+    proofs::{
+        policies_vfy::{
+            InnerPartialVfyProof, PoliciesVfyPublicInput, PoliciesVfyValidityProof,
+            PoliciesVfyVerifyingKey,
+        },
+        predicates,
+        utxo::{DPCUtxoPublicInput, ProofUtxo, UtxoVerifyingKey},
+    },
+};
+#[cfg(feature = "prover")]
+use ark_ec::PairingEngine;
+#[cfg(feature = "prover")]
 use ark_ff::{Field, UniformRand};
 use ark_serialize::{CanonicalSerialize, *};
+#[cfg(feature = "prover")]
 use ark_std::{
-    rand::{CryptoRng, RngCore},
-    string::ToString,
-    vec::Vec,
+    rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng},
+    time::Duration,
 };
-use jf_plonk::proof_system::structs::VerifyingKey;
+use ark_std::{string::ToString, vec::Vec};
+#[cfg(feature = "prover")]
+use jf_plonk::proof_system::structs::{ProvingKey, VerifyingKey};
 use jf_utils::tagged_blob;
 
 #[tagged_blob("DPC_PROOF")]
@@ -56,87 +68,127 @@ pub struct DPCValidityProof {
     pub(crate) inner_partial_vfy_proof: InnerPartialVfyProof,
 }
 
-#[derive(Clone, Debug, PartialEq)] // TODO: derive hash and serialize/deserialize
+#[cfg(feature = "prover")]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)] // TODO: derive hash
 /// DPC Transaction proving key
-pub struct DPCProvingKey<'a> {
-    utxo_proving_key: UtxoProvingKey<'a>,
-    policies_vfy_proving_key: PoliciesVfyProvingKey<'a>,
+///
+/// The key owns its commitment keys, i.e. it does not borrow from the SRS it
+/// was generated from, and it can be kept or shared across threads once the
+/// SRS is dropped.
+pub struct DPCProvingKey {
+    utxo_proving_key: UtxoProvingKey<'static>,
+    policies_vfy_proving_key: PoliciesVfyProvingKey<'static>,
     // A group element used in inner predicate proofs verification circuit.
     pub(crate) beta_g: InnerG1Affine,
+    pub(crate) params: DPCParams,
+    pub(crate) shape: KeyShape,
+}
+
+#[cfg(feature = "prover")]
+impl DPCProvingKey {
+    /// Parameters the proving key was generated for
+    pub fn params(&self) -> &DPCParams {
+        &self.params
+    }
+
+    /// Maximum number of inputs of a transaction, including the fee input,
+    /// which is also its maximum number of outputs, including the fee change.
+    /// Transactions with fewer inputs or outputs are padded with dummy records
+    /// by `transaction::pad_with_dummy_records`.
+    pub fn max_num_inputs(&self) -> usize {
+        self.shape.entire_input_size()
+    }
+
+    /// Shape of the transactions generated with the proving key
+    pub fn shape(&self) -> KeyShape {
+        self.shape
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)] // TODO: derive hash and serialize/deserialize
+#[tagged_blob("DPC_VERIFYING_KEY")]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)] // TODO: derive hash
 /// DPC Transaction verifying key
 pub struct DPCVerifyingKey {
     utxo_verifying_key: UtxoVerifyingKey,
     policies_vfy_verifying_key: PoliciesVfyVerifyingKey,
+    params: DPCParams,
+    shape: KeyShape,
 }
 
-pub(crate) struct DPCWitness<'a> {
-    pub(crate) utxo_witness: DPCUtxoWitness<'a>,
-    pub(crate) policies_vfy_witness: PoliciesVfyWitness,
+impl DPCVerifyingKey {
+    /// Parameters the verifying key was generated for
+    pub fn params(&self) -> &DPCParams {
+        &self.params
+    }
+
+    /// Number of input nullifiers of the notes verified by the key, including
+    /// that of the fee input and of dummy inputs. Notes have as many output
+    /// commitments as input nullifiers.
+    pub fn num_inputs(&self) -> usize {
+        self.shape.entire_input_size()
+    }
+
+    /// Shape of the notes verified by the key
+    pub fn shape(&self) -> KeyShape {
+        self.shape
+    }
 }
 
-impl<'a> DPCWitness<'a> {
-    pub(crate) fn new_unchecked<R: CryptoRng + RngCore>(
-        rng: &mut R,
-        entire_inputs: Vec<NoteInput<'a>>,
-        entire_outputs: Vec<RecordOpening>,
-        input_death_predicates: &[Predicate],
-        output_birth_predicates: &[Predicate],
-        blinding_local_data: InnerScalarField,
-    ) -> Result<Self, DPCApiError> {
-        // When a predicate circuit was initialized, it does not have the
-        // correct commitment to the local data (and thus cannot generate)
-        // a correct proof. So to generate a correct witness, it is required
-        // that the predicates are finalized
-        for predicate in input_death_predicates {
-            if !predicate.is_finalized {
-                return Err(DPCApiError::InternalError(
-                    "The input predicate hasn't been finalized".to_string(),
-                ));
-            }
+// When a predicate circuit was initialized, it does not have the correct
+// commitment to the local data (and thus cannot generate) a correct proof. So
+// to generate a correct witness, it is required that the predicates are
+// finalized
+#[cfg(feature = "prover")]
+fn check_predicates_finalized(
+    input_death_predicates: &[Predicate],
+    output_birth_predicates: &[Predicate],
+) -> Result<(), DPCApiError> {
+    for predicate in input_death_predicates {
+        if !predicate.is_finalized {
+            return Err(DPCApiError::InternalError(
+                "The input predicate hasn't been finalized".to_string(),
+            ));
         }
+    }
 
-        for predicate in output_birth_predicates {
-            if !predicate.is_finalized {
-                return Err(DPCApiError::InternalError(
-                    "The input predicate hasn't been finalized".to_string(),
-                ));
-            }
+    for predicate in output_birth_predicates {
+        if !predicate.is_finalized {
+            return Err(DPCApiError::InternalError(
+                "The input predicate hasn't been finalized".to_string(),
+            ));
         }
-
-        // derive utxo circuit witness
-        let utxo_witness =
-            DPCUtxoWitness::new_unchecked(rng, entire_inputs, entire_outputs, blinding_local_data);
-        // derive outer circuit witness
-        let batch_proof =
-            predicates::prove(rng, output_birth_predicates, input_death_predicates).unwrap();
-
-        // TODO: remove clone
-        let input_death_vks: Vec<VerifyingKey<InnerPairingEngine>> = input_death_predicates
-            .iter()
-            .map(|pred| pred.verifying_key().clone())
-            .collect();
-        let output_birth_vks: Vec<VerifyingKey<InnerPairingEngine>> = output_birth_predicates
-            .iter()
-            .map(|pred| pred.verifying_key().clone())
-            .collect();
-        let blind_partial_proof = InnerScalarField::rand(rng);
-
-        let policies_vfy_witness = PoliciesVfyWitness::new(
-            input_death_vks,
-            output_birth_vks,
-            batch_proof,
-            utxo_witness.blinding_predicates,
-            blind_partial_proof,
-        )
-        .unwrap();
-        Ok(Self {
-            utxo_witness,
-            policies_vfy_witness,
-        })
     }
+    Ok(())
+}
+
+// Batch prove the inner predicates and derive the outer circuit witness.
+#[cfg(feature = "prover")]
+fn new_policies_vfy_witness<R: CryptoRng + RngCore>(
+    rng: &mut R,
+    input_death_predicates: &[Predicate],
+    output_birth_predicates: &[Predicate],
+    blinding_predicates: InnerScalarField,
+) -> Result<PoliciesVfyWitness, DPCApiError> {
+    let batch_proof = predicates::prove(rng, output_birth_predicates, input_death_predicates)?;
+
+    // TODO: remove clone
+    let input_death_vks: Vec<VerifyingKey<InnerPairingEngine>> = input_death_predicates
+        .iter()
+        .map(|pred| pred.verifying_key().clone())
+        .collect();
+    let output_birth_vks: Vec<VerifyingKey<InnerPairingEngine>> = output_birth_predicates
+        .iter()
+        .map(|pred| pred.verifying_key().clone())
+        .collect();
+    let blind_partial_proof = InnerScalarField::rand(rng);
+
+    PoliciesVfyWitness::new(
+        input_death_vks,
+        output_birth_vks,
+        batch_proof,
+        blinding_predicates,
+        blind_partial_proof,
+    )
 }
 
 #[derive(Clone, Debug)]
@@ -147,58 +199,49 @@ pub(crate) struct DPCPublicInput {
     pub(crate) inner_partial_vfy_proof: InnerPartialVfyProof,
 }
 
-impl DPCPublicInput {
-    pub(crate) fn from_witness(
-        witness: &DPCWitness,
-        fee: u64,
-        memo: Vec<InnerScalarField>,
-        beta_g: InnerG1Affine,
-    ) -> Result<Self, DPCApiError> {
-        // derive utxo circuit public input
-        let utxo_public_input = DPCUtxoPublicInput::from_witness(&witness.utxo_witness, fee, memo)?;
-
-        // derive outer circuit public input
-        let params = PoliciesVfyParams {
-            beta_g,
-            generator_g: witness.policies_vfy_witness.input_death_vks[0].open_key.g,
-            m: NONNATIVE_FIELD_M,
-            two_power_m: Some(InnerBaseField::from(2u8).pow(&[NONNATIVE_FIELD_M as u64])),
-            range_bit_len: RANGE_BIT_LEN,
-        };
-        let policies_vfy_public_input = PoliciesVfyPublicInput::from_witness(
-            &witness.policies_vfy_witness,
-            &params,
-            utxo_public_input.commitment_local_data,
-        )?;
+#[cfg(feature = "prover")]
+fn policies_vfy_params(beta_g: InnerG1Affine, witness: &PoliciesVfyWitness) -> PoliciesVfyParams {
+    PoliciesVfyParams {
+        beta_g,
+        generator_g: witness.input_death_vks[0].open_key.g,
+        m: NONNATIVE_FIELD_M,
+        two_power_m: Some(InnerBaseField::from(2u8).pow(&[NONNATIVE_FIELD_M as u64])),
+        range_bit_len: RANGE_BIT_LEN,
+    }
+}
 
-        if policies_vfy_public_input.comm_predicates != utxo_public_input.commitment_predicates {
-            return Err(DPCApiError::InvalidParameters(
-                "The predicates commitment computed in the UTXO circuit is different from that in the outer circuit".to_string(),
-            ));
-        }
-        // double-check that the inner predicates batch proof is valid
-        let output_birth_vks_ref: Vec<&VerifyingKey<InnerPairingEngine>> = witness
-            .policies_vfy_witness
-            .output_birth_vks
-            .iter()
-            .collect();
-        let input_death_vks_ref: Vec<&VerifyingKey<InnerPairingEngine>> = witness
-            .policies_vfy_witness
-            .input_death_vks
-            .iter()
-            .collect();
-        predicates::verify::<Predicate>(
-            &output_birth_vks_ref,
-            &input_death_vks_ref,
-            &[utxo_public_input.commitment_local_data],
-            &witness.policies_vfy_witness.batch_proof,
-        )?;
+// Derive the outer circuit public input, which shares the local data and
+// predicates commitments with the UTXO public input.
+#[cfg(feature = "prover")]
+fn new_policies_vfy_public_input(
+    witness: &PoliciesVfyWitness,
+    utxo_public_input: &DPCUtxoPublicInput,
+    beta_g: InnerG1Affine,
+) -> Result<PoliciesVfyPublicInput, DPCApiError> {
+    let policies_vfy_public_input = PoliciesVfyPublicInput::from_witness(
+        witness,
+        &policies_vfy_params(beta_g, witness),
+        utxo_public_input.commitment_local_data,
+    )?;
 
-        Ok(Self {
-            utxo_public_input,
-            inner_partial_vfy_proof: policies_vfy_public_input.partial_plonk_proof,
-        })
+    if policies_vfy_public_input.comm_predicates != utxo_public_input.commitment_predicates {
+        return Err(DPCApiError::InvalidParameter(
+            "The predicates commitment computed in the UTXO circuit is different from that in the outer circuit".to_string(),
+        ));
     }
+    // double-check that the inner predicates batch proof is valid
+    let output_birth_vks_ref: Vec<&VerifyingKey<InnerPairingEngine>> =
+        witness.output_birth_vks.iter().collect();
+    let input_death_vks_ref: Vec<&VerifyingKey<InnerPairingEngine>> =
+        witness.input_death_vks.iter().collect();
+    predicates::verify::<Predicate>(
+        &output_birth_vks_ref,
+        &input_death_vks_ref,
+        &[utxo_public_input.commitment_local_data],
+        &witness.batch_proof,
+    )?;
+
+    Ok(policies_vfy_public_input)
 }
 
 /// Pre-process to generate keys for DPC transaction proofs.
@@ -207,46 +250,63 @@ impl DPCPublicInput {
 /// - inner circuit SRS
 /// - number of input/output records (excluding fee input/output)
 /// - unmerged inner circuit domain size
+/// - record layout and accumulator parameters
 ///
 /// output:
 /// - DPC proving key
 /// - DPC verification key
 /// - total number of constraints of the utxo/outer circuit
-pub fn preprocess<'a>(
-    outer_srs: &'a OuterUniversalParam,
-    inner_srs: &'a InnerUniversalParam,
+///
+/// Fail before any preprocessing if the SRS do not support the degrees
+/// returned by `stats::required_srs_degrees`.
+///
+/// The proving key owns its commitment keys, so it does not borrow from the
+/// SRS and can be kept or moved to another thread once the SRS is dropped.
+#[cfg(feature = "prover")]
+pub fn preprocess(
+    outer_srs: &OuterUniversalParam,
+    inner_srs: &InnerUniversalParam,
     non_fee_input_size: usize,
     unmerged_inner_policy_domain_size: usize,
-) -> Result<(DPCProvingKey<'a>, DPCVerifyingKey, (usize, usize)), DPCApiError> {
-    let (utxo_proving_key, utxo_verifying_key, utxo_n_constraints) =
-        preprocess_utxo_keys(inner_srs, non_fee_input_size)?;
-
-    let (policies_vfy_proving_key, policies_vfy_verifying_key, outer_n_constraints) =
-        policies_vfy::preprocess(
-            outer_srs,
-            inner_srs,
-            non_fee_input_size,
-            unmerged_inner_policy_domain_size,
-        )?;
+    params: &DPCParams,
+) -> Result<(DPCProvingKey, DPCVerifyingKey, (usize, usize)), DPCApiError> {
+    let shape = KeyShape::new(non_fee_input_size, unmerged_inner_policy_domain_size);
+    // the circuits are built once, and measured before being preprocessed
+    let (utxo_circuit, utxo_n_constraints) =
+        DPCUtxoCircuit::build_for_preprocessing(non_fee_input_size, params)?;
+    let (policies_vfy_circuit, outer_n_constraints) = PoliciesVfyCircuit::build_for_preprocessing(
+        inner_srs,
+        non_fee_input_size,
+        unmerged_inner_policy_domain_size,
+    )?;
+    stats::check_srs_degrees(
+        inner_srs,
+        outer_srs,
+        shape,
+        &utxo_circuit,
+        &policies_vfy_circuit,
+    )?;
+    let (utxo_proving_key, utxo_verifying_key) = preprocess_utxo_keys(inner_srs, &utxo_circuit)?;
 
-    #[cfg(test)]
-    {
-        ark_std::println!(
-            "ℹ️ num_constraint of UTXO circuit: {}, of outer circuit: {}",
-            utxo_n_constraints,
-            outer_n_constraints,
-        );
-    }
+    let (policies_vfy_proving_key, policies_vfy_verifying_key) =
+        policies_vfy::preprocess(outer_srs, &policies_vfy_circuit, non_fee_input_size)?;
 
     let dpc_proving_key = DPCProvingKey {
-        utxo_proving_key,
-        policies_vfy_proving_key,
+        utxo_proving_key: detach_from_srs(&utxo_proving_key)?,
+        policies_vfy_proving_key: PoliciesVfyProvingKey {
+            proving_key: detach_from_srs(&policies_vfy_proving_key.proving_key)?,
+            num_input_records: policies_vfy_proving_key.num_input_records,
+        },
         beta_g: inner_srs.powers_of_g_ref()[1],
+        params: *params,
+        shape,
     };
 
     let dpc_verifying_key = DPCVerifyingKey {
         utxo_verifying_key,
         policies_vfy_verifying_key,
+        params: *params,
+        shape,
     };
 
     Ok((
@@ -256,52 +316,171 @@ pub fn preprocess<'a>(
     ))
 }
 
-/// Generate a transaction validity proof (a zk-SNARK proof) given the witness,
-/// public inputs, and the proving key.
-pub(crate) fn prove<R: RngCore + CryptoRng>(
+// Copy a jf-plonk proving key into one that does not borrow from the SRS.
+// jf-plonk does not expose the commitment key of its proving keys, whose
+// canonical encoding is the only way to rebuild them with another lifetime.
+#[cfg(feature = "prover")]
+fn detach_from_srs<E: PairingEngine>(
+    proving_key: &ProvingKey<E>,
+) -> Result<ProvingKey<'static, E>, DPCApiError> {
+    let mut bytes = Vec::with_capacity(proving_key.serialized_size());
+    proving_key.serialize_unchecked(&mut bytes)?;
+    Ok(CanonicalDeserialize::deserialize_unchecked(&bytes[..])?)
+}
+
+#[cfg(feature = "prover")]
+fn prove_policies_vfy<R: RngCore + CryptoRng>(
     rng: &mut R,
     proving_key: &DPCProvingKey,
-    witness: &DPCWitness,
-    public_inputs: &DPCPublicInput,
-) -> Result<DPCValidityProof, DPCApiError> {
-    // compute inner UTXO proof
-
-    let utxo_proof = super::utxo::prove_utxo(
+    witness: &PoliciesVfyWitness,
+    pub_input: &PoliciesVfyPublicInput,
+) -> Result<PoliciesVfyValidityProof, DPCApiError> {
+    super::policies_vfy::prove(
         rng,
-        &proving_key.utxo_proving_key,
-        &witness.utxo_witness,
-        &public_inputs.utxo_public_input,
-    )?;
+        &proving_key.policies_vfy_proving_key,
+        witness,
+        &policies_vfy_params(proving_key.beta_g, witness),
+        pub_input,
+        None,
+    )
+}
 
-    // compute outer proof
-    let params = PoliciesVfyParams {
-        beta_g: proving_key.beta_g,
-        generator_g: witness.policies_vfy_witness.input_death_vks[0].open_key.g,
-        m: NONNATIVE_FIELD_M,
-        two_power_m: Some(InnerBaseField::from(2u8).pow(&[NONNATIVE_FIELD_M as u64])),
-        range_bit_len: RANGE_BIT_LEN,
-    };
+/// Time spent in each stage of the generation of a DPC validity proof.
+///
+/// With the `parallel` feature, the UTXO proof is computed concurrently with
+/// the inner predicates batch proof and the outer proof, so `total` is less
+/// than the sum of the stages. Without the `std` feature, no time is measured
+/// and all durations are zero.
+#[cfg(feature = "prover")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProvingTimings {
+    /// Proving the UTXO circuit
+    pub utxo: Duration,
+    /// Batch proving the inner predicate circuits
+    pub inner_batch: Duration,
+    /// Proving the outer circuit that partially verifies the inner batch proof
+    pub outer: Duration,
+    /// Deriving the witness and public input and proving all the circuits
+    pub total: Duration,
+}
 
-    let pub_input = PoliciesVfyPublicInput {
-        comm_local_data: public_inputs.utxo_public_input.commitment_local_data,
-        comm_predicates: public_inputs.utxo_public_input.commitment_predicates,
-        partial_plonk_proof: public_inputs.inner_partial_vfy_proof,
-    };
+/// Derive the witness and public input of a DPC transaction and generate its
+/// validity proof.
+///
+/// The UTXO proof does not depend on the inner predicates batch proof, so
+/// with the `parallel` feature it is computed on the thread pool concurrently
+/// with the inner batch and outer proofs.
+#[cfg(feature = "prover")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn generate_proof<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    proving_key: &DPCProvingKey,
+    entire_inputs: Vec<NoteInput>,
+    entire_outputs: Vec<RecordOpening>,
+    input_death_predicates: &[Predicate],
+    output_birth_predicates: &[Predicate],
+    blinding_local_data: InnerScalarField,
+    fee: u64,
+    memo: Vec<InnerScalarField>,
+) -> Result<(DPCPublicInput, DPCValidityProof, ProvingTimings), DPCApiError> {
+    let (result, total) = timed(|| -> Result<_, DPCApiError> {
+        check_predicates_finalized(input_death_predicates, output_birth_predicates)?;
+        let utxo_witness =
+            DPCUtxoWitness::new_unchecked(rng, entire_inputs, entire_outputs, blinding_local_data);
+        let utxo_public_input = DPCUtxoPublicInput::from_witness(&utxo_witness, fee, memo)?;
+
+        // each stage has its own rng so that the stages can run on different threads
+        let utxo_rng = &mut fork_rng(rng);
+        let policies_vfy_rng = &mut fork_rng(rng);
+
+        let mut utxo_stage = || {
+            timed(|| {
+                super::utxo::prove_utxo(
+                    utxo_rng,
+                    &proving_key.utxo_proving_key,
+                    &utxo_witness,
+                    &utxo_public_input,
+                )
+            })
+        };
+        let mut policies_vfy_stage = || -> Result<_, DPCApiError> {
+            let (witness, inner_batch) = timed(|| {
+                new_policies_vfy_witness(
+                    policies_vfy_rng,
+                    input_death_predicates,
+                    output_birth_predicates,
+                    utxo_witness.blinding_predicates,
+                )
+            });
+            let witness = witness?;
+            let pub_input =
+                new_policies_vfy_public_input(&witness, &utxo_public_input, proving_key.beta_g)?;
+            let (proof, outer) =
+                timed(|| prove_policies_vfy(policies_vfy_rng, proving_key, &witness, &pub_input));
+            Ok((pub_input.partial_plonk_proof, proof?, inner_batch, outer))
+        };
 
-    let policies_vfy_proof = super::policies_vfy::prove(
-        rng,
-        &proving_key.policies_vfy_proving_key,
-        &witness.policies_vfy_witness,
-        &params,
-        &pub_input,
-        None,
-    )?;
+        // The predicates are not `Send`, so the inner batch and outer proofs are
+        // computed on the current thread while the UTXO proof is computed on the
+        // thread pool.
+        #[cfg(feature = "parallel")]
+        let ((utxo_proof, utxo), policies_vfy) = {
+            let mut utxo_result = None;
+            let utxo_result_ref = &mut utxo_result;
+            let policies_vfy = rayon::in_place_scope(|scope| {
+                scope.spawn(move |_| *utxo_result_ref = Some(utxo_stage()));
+                policies_vfy_stage()
+            });
+            (
+                utxo_result.expect("the UTXO stage completes within the scope"),
+                policies_vfy,
+            )
+        };
+        #[cfg(not(feature = "parallel"))]
+        let ((utxo_proof, utxo), policies_vfy) = (utxo_stage(), policies_vfy_stage());
+
+        let (inner_partial_vfy_proof, policies_vfy_proof, inner_batch, outer) = policies_vfy?;
+        let proof = DPCValidityProof {
+            utxo_proof: utxo_proof?,
+            policies_vfy_proof,
+            inner_partial_vfy_proof,
+        };
+        let pub_input = DPCPublicInput {
+            utxo_public_input,
+            inner_partial_vfy_proof,
+        };
+        let timings = ProvingTimings {
+            utxo,
+            inner_batch,
+            outer,
+            total: Duration::default(),
+        };
+        Ok((pub_input, proof, timings))
+    });
+    let (pub_input, proof, timings) = result?;
+    Ok((pub_input, proof, ProvingTimings { total, ..timings }))
+}
 
-    Ok(DPCValidityProof {
-        utxo_proof,
-        policies_vfy_proof,
-        inner_partial_vfy_proof: pub_input.partial_plonk_proof,
-    })
+// Run `f` and measure its duration, which is always zero without `std`.
+#[cfg(feature = "prover")]
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    #[cfg(feature = "std")]
+    {
+        let start = std::time::Instant::now();
+        let result = f();
+        (result, start.elapsed())
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        (f(), Duration::default())
+    }
+}
+
+#[cfg(feature = "prover")]
+fn fork_rng<R: RngCore>(rng: &mut R) -> StdRng {
+    let mut seed = <StdRng as SeedableRng>::Seed::default();
+    rng.fill_bytes(&mut seed);
+    StdRng::from_seed(seed)
 }
 
 pub(crate) fn verify(
@@ -336,18 +515,77 @@ pub(crate) fn verify(
     )
 }
 
-#[cfg(test)]
+/// Verify a batch of DPC validity proofs, aggregating the UTXO and outer
+/// proofs as well as the final pairing checks of the inner partial
+/// verification proofs. It does not tell which proofs are invalid.
+pub(crate) fn batch_verify(
+    proofs: &[&DPCValidityProof],
+    verifying_key: &DPCVerifyingKey,
+    public_inputs: &[&DPCPublicInput],
+) -> Result<(), DPCApiError> {
+    if proofs.len() != public_inputs.len() {
+        return Err(DPCApiError::InvalidParameter(
+            "Number of proofs and public inputs mismatch".to_string(),
+        ));
+    }
+    if proofs.is_empty() {
+        return Ok(());
+    }
+
+    // check UTXO proofs
+    let utxo_proofs: Vec<&ProofUtxo> = proofs.iter().map(|proof| &proof.utxo_proof).collect();
+    let utxo_public_inputs: Vec<&DPCUtxoPublicInput> = public_inputs
+        .iter()
+        .map(|public_input| &public_input.utxo_public_input)
+        .collect();
+    super::utxo::batch_verify_utxo(
+        &utxo_proofs,
+        &verifying_key.utxo_verifying_key,
+        &utxo_public_inputs,
+    )?;
+
+    // check outer proofs
+    let policies_vfy_proofs: Vec<&PoliciesVfyValidityProof> = proofs
+        .iter()
+        .map(|proof| &proof.policies_vfy_proof)
+        .collect();
+    let policies_vfy_public_inputs: Vec<PoliciesVfyPublicInput> = public_inputs
+        .iter()
+        .map(|public_input| PoliciesVfyPublicInput {
+            comm_local_data: public_input.utxo_public_input.commitment_local_data,
+            comm_predicates: public_input.utxo_public_input.commitment_predicates,
+            partial_plonk_proof: public_input.inner_partial_vfy_proof,
+        })
+        .collect();
+    super::policies_vfy::batch_verify(
+        &verifying_key.policies_vfy_verifying_key,
+        &policies_vfy_public_inputs.iter().collect::<Vec<_>>(),
+        &policies_vfy_proofs,
+    )?;
+
+    // check inner partial verification proofs
+    let inner_partial_vfy_proofs: Vec<&InnerPartialVfyProof> = proofs
+        .iter()
+        .map(|proof| &proof.inner_partial_vfy_proof)
+        .collect();
+    predicates::batch_decide(
+        &verifying_key.utxo_verifying_key.open_key,
+        &inner_partial_vfy_proofs,
+    )
+}
+
+#[cfg(all(test, feature = "prover"))]
 mod tests {
     use super::*;
     use crate::{
         constants::{MEMO_LEN, TREE_DEPTH},
         keys::{DiversifiedAddress, DiversifierRandomizer, KeyChainMasterKey, ProofGenerationKey},
         proofs::{predicates::PredicateCircuit, universal_setup_inner, universal_setup_outer},
-        structs::{compress_local_data, Nullifier, PolicyIdentifier},
+        structs::{compress_local_data, Nullifier, OwnedNoteInput, PolicyIdentifier},
         types::{CommitmentValue, NodeValue},
     };
     use ark_ff::{One, Zero};
-    use ark_std::{rand::Rng, test_rng, vec};
+    use ark_std::{borrow::Cow, rand::Rng, test_rng, vec};
     use jf_plonk::circuit::{Circuit, PlonkCircuit};
     use jf_primitives::{
         circuit::commitment::CommitmentGadget,
@@ -356,6 +594,7 @@ mod tests {
 
     const INNER_DOMAIN_SIZE_FOR_TEST: usize = 1 << 12;
 
+    // CI runs this test both with and without the `parallel` feature.
     #[test]
     #[ignore]
     fn test_dpc_proof() -> Result<(), DPCApiError> {
@@ -373,8 +612,10 @@ mod tests {
             &inner_srs,
             num_source_inputs,
             INNER_DOMAIN_SIZE_FOR_TEST,
+            &DPCParams::default(),
         )?;
-        let (bad_dpc_pk, bad_dpc_vk, (..)) = preprocess(&outer_srs, &inner_srs, 1, 1)?;
+        let (bad_dpc_pk, bad_dpc_vk, (..)) =
+            preprocess(&outer_srs, &inner_srs, 1, 1, &DPCParams::default())?;
 
         // generate proof generation key and addresses
         let mut wsk = [0u8; 32];
@@ -393,24 +634,40 @@ mod tests {
             memo,
             blinding_local_data,
         ) = build_dpc_info_for_test(rng, &inner_srs, &pgk, addr, rd, num_source_inputs)?;
-        let witness = DPCWitness::new_unchecked(
+        let (pub_input, dpc_proof, timings) = generate_proof(
             rng,
-            inputs,
-            outputs,
+            &dpc_pk,
+            inputs.clone(),
+            outputs.clone(),
             &input_death_predicates[..],
             &output_birth_predicates[..],
             blinding_local_data,
+            fee,
+            memo.clone(),
         )?;
-        let pub_input =
-            DPCPublicInput::from_witness(&witness, fee, memo, inner_srs.powers_of_g_ref()[1])?;
-        let dpc_proof = prove(rng, &dpc_pk, &witness, &pub_input)?;
 
         // good path
         assert!(verify(&dpc_proof, &dpc_vk, &pub_input).is_ok());
+        assert!(timings.total >= timings.utxo);
+        assert!(timings.total >= timings.inner_batch + timings.outer);
+        // the stages run one after the other without `parallel`
+        #[cfg(not(feature = "parallel"))]
+        assert!(timings.total >= timings.utxo + timings.inner_batch + timings.outer);
 
         // bad path: wrong proving key
         {
-            assert!(prove(rng, &bad_dpc_pk, &witness, &pub_input).is_err());
+            assert!(generate_proof(
+                rng,
+                &bad_dpc_pk,
+                inputs,
+                outputs,
+                &input_death_predicates[..],
+                &output_birth_predicates[..],
+                blinding_local_data,
+                fee,
+                memo,
+            )
+            .is_err());
         }
 
         // bad path: wrong verification key
@@ -471,6 +728,58 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[ignore]
+    fn test_dpc_keys_serde() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let max_inner_degree = (1 << 16) + 4;
+        let inner_srs = universal_setup_inner(max_inner_degree, rng)?;
+        let max_outer_degree = (1 << 17) + 4;
+        let outer_srs = universal_setup_outer(max_outer_degree, rng)?;
+        let (dpc_pk, dpc_vk, (..)) = preprocess(
+            &outer_srs,
+            &inner_srs,
+            1,
+            INNER_DOMAIN_SIZE_FOR_TEST,
+            &DPCParams::default(),
+        )?;
+
+        let mut bytes = Vec::new();
+        dpc_pk.serialize(&mut bytes)?;
+        let loaded_pk = DPCProvingKey::deserialize(&bytes[..])?;
+        assert_eq!(loaded_pk, dpc_pk);
+
+        let mut bytes = Vec::new();
+        dpc_vk.serialize(&mut bytes)?;
+        assert_eq!(DPCVerifyingKey::deserialize(&bytes[..])?, dpc_vk);
+
+        // the proving key does not borrow from the SRS and can be shared
+        // across threads
+        drop(inner_srs);
+        drop(outer_srs);
+        let shared_pk = std::sync::Arc::new(dpc_pk);
+        let thread_pk = shared_pk.clone();
+        let shape = std::thread::spawn(move || thread_pk.shape())
+            .join()
+            .unwrap();
+        assert_eq!(shape, shared_pk.shape());
+        assert_eq!(loaded_pk, *shared_pk);
+        Ok(())
+    }
+
+    #[test]
+    fn test_owned_types_are_send_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<DPCProvingKey>();
+        assert_send_sync::<DPCVerifyingKey>();
+        assert_send_sync::<OwnedNoteInput>();
+
+        let pgk = ProofGenerationKey::default();
+        let input = NoteInput::dummy(&pgk);
+        let owned_input: OwnedNoteInput = input.clone().into_owned();
+        assert_eq!(*owned_input.proof_gen_key, *input.proof_gen_key);
+    }
+
     // The inner predicate circuit proves knowledge of the local data commitment.
     fn build_inner_predicate_circuit_for_test(
         compressed_local_data: &[InnerScalarField],
@@ -581,7 +890,7 @@ mod tests {
             note_inputs.push(NoteInput {
                 ro,
                 acc_member_witness: mt_witness,
-                proof_gen_key: &pgk,
+                proof_gen_key: Cow::Borrowed(&pgk),
                 authorization_randomizer: Default::default(),
                 diversifier_randomizer: rd.clone(),
             })
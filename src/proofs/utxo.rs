@@ -9,33 +9,39 @@
 // FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+#[cfg(feature = "prover")]
 use crate::{
     circuit::utxo::DPCUtxoCircuit,
-    constants::TREE_DEPTH,
-    errors::DPCApiError,
+    errors::TxnParameterError,
     keys::ProofGenerationKey,
+    params::DPCParams,
     structs::{
-        compress_local_data, derive_predicates_commitment, NoteInput, Nullifier, RecordOpening,
-    },
-    types::{
-        CommitmentValue, InnerPairingEngine, InnerScalarField, InnerUniversalParam, NodeValue,
-        SigVerKey,
+        compress_local_data, derive_predicates_commitment, NoteInput, Payload, RecordOpening,
     },
+    types::InnerUniversalParam,
+};
+use crate::{
+    errors::{DPCApiError, TxnVerificationError},
+    structs::Nullifier,
+    types::{CommitmentValue, InnerPairingEngine, InnerScalarField, NodeValue, SigVerKey},
 };
+#[cfg(feature = "prover")]
 use ark_ff::UniformRand;
+#[cfg(feature = "prover")]
 use ark_std::{
+    borrow::Cow,
     rand::{CryptoRng, RngCore},
-    vec,
-    vec::Vec,
 };
+use ark_std::{vec, vec::Vec};
 use jf_plonk::{
-    circuit::Circuit,
     proof_system::{PlonkKzgSnark, Snark},
     transcript::StandardTranscript,
 };
+#[cfg(feature = "prover")]
 use jf_primitives::merkle_tree::AccMemberWitness;
 
 /// The UTXO part of the proving key
+#[cfg(feature = "prover")]
 pub type UtxoProvingKey<'a> =
     jf_plonk::proof_system::structs::ProvingKey<'a, crate::types::InnerPairingEngine>;
 
@@ -46,6 +52,7 @@ pub type UtxoVerifyingKey =
 pub(super) type ProofUtxo =
     jf_plonk::proof_system::structs::Proof<crate::types::InnerPairingEngine>;
 
+#[cfg(feature = "prover")]
 #[derive(Clone, Debug)]
 pub struct DPCUtxoWitness<'a> {
     // input notes, including the fee note
@@ -58,20 +65,29 @@ pub struct DPCUtxoWitness<'a> {
     pub(crate) blinding_predicates: InnerScalarField,
 }
 
+#[cfg(feature = "prover")]
 impl<'a> DPCUtxoWitness<'a> {
     /// Build a dummy witness for `non_fee_input_size` number of non-fee
     /// notes/records. The actual number of notes/records will be
     /// `non_fee_input_size + 1` where `1` comes from the fees.
-    pub(crate) fn dummy(non_fee_input_size: usize, pgk: &'a ProofGenerationKey) -> Self {
+    pub(crate) fn dummy(
+        non_fee_input_size: usize,
+        pgk: &'a ProofGenerationKey,
+        params: &DPCParams,
+    ) -> Self {
         let n_inputs = non_fee_input_size + 1;
-        let outputs = vec![RecordOpening::default(); n_inputs];
+        let ro = RecordOpening {
+            payload: Payload::zero(params),
+            ..Default::default()
+        };
+        let outputs = vec![ro.clone(); n_inputs];
         let rng = &mut ark_std::test_rng();
         let mut inputs = vec![];
         for _ in 0..n_inputs {
             inputs.push(NoteInput {
-                ro: Default::default(),
-                acc_member_witness: AccMemberWitness::dummy(TREE_DEPTH),
-                proof_gen_key: pgk,
+                ro: ro.clone(),
+                acc_member_witness: AccMemberWitness::dummy(params.tree_depth),
+                proof_gen_key: Cow::Borrowed(pgk),
                 authorization_randomizer: Default::default(),
                 diversifier_randomizer: Default::default(),
             });
@@ -103,6 +119,7 @@ impl<'a> DPCUtxoWitness<'a> {
 pub(crate) struct DPCUtxoPublicInput {
     pub(crate) root: NodeValue,
     pub(crate) fee: u64,
+    pub(crate) fee_asset: InnerScalarField,
     pub(crate) input_nullifiers: Vec<Nullifier>,
     pub(crate) output_commitments: Vec<CommitmentValue>,
     pub(crate) commitment_local_data: CommitmentValue,
@@ -111,6 +128,7 @@ pub(crate) struct DPCUtxoPublicInput {
     pub(crate) authorization_verification_key: SigVerKey,
 }
 
+#[cfg(feature = "prover")]
 impl DPCUtxoPublicInput {
     pub(crate) fn from_witness(
         witness: &DPCUtxoWitness,
@@ -135,8 +153,18 @@ impl DPCUtxoPublicInput {
 
         // TODO compute detection keys
 
-        // retrieve merkle root
-        let root = witness.entire_inputs[0].acc_member_witness.root;
+        // retrieve merkle root and fee asset from the fee input
+        let fee_input = witness
+            .entire_inputs
+            .first()
+            .ok_or(TxnParameterError::NoInput)?;
+        let root = fee_input.acc_member_witness.root;
+        let fee_asset = *fee_input
+            .ro
+            .payload
+            .data
+            .first()
+            .ok_or(TxnParameterError::WrongFeeInputPayload)?;
 
         // Compute commitment to predicates, ignore fee input/output
         let input_death_pids: Vec<InnerScalarField> = witness
@@ -173,6 +201,7 @@ impl DPCUtxoPublicInput {
         Ok(DPCUtxoPublicInput {
             root,
             fee,
+            fee_asset,
             input_nullifiers: nullifiers,
             output_commitments: compressed_local_data.output_record_commitments,
             commitment_local_data,
@@ -181,7 +210,9 @@ impl DPCUtxoPublicInput {
             authorization_verification_key: auth_ver_key,
         })
     }
+}
 
+impl DPCUtxoPublicInput {
     pub(crate) fn to_scalars(&self) -> Vec<InnerScalarField> {
         let mut scalars = vec![];
         self.input_nullifiers
@@ -194,6 +225,7 @@ impl DPCUtxoPublicInput {
         scalars.push(self.commitment_local_data);
         scalars.push(self.root.to_scalar());
         scalars.push(InnerScalarField::from(self.fee));
+        scalars.push(self.fee_asset);
         self.memo
             .iter()
             .for_each(|memo_elem| scalars.push(*memo_elem));
@@ -204,13 +236,14 @@ impl DPCUtxoPublicInput {
     }
 }
 
+#[cfg(feature = "prover")]
 pub(super) fn prove_utxo<R: RngCore + CryptoRng>(
     rng: &mut R,
     proving_key: &UtxoProvingKey,
     witness: &DPCUtxoWitness,
     public_inputs: &DPCUtxoPublicInput,
 ) -> Result<ProofUtxo, DPCApiError> {
-    let utxo_circuit =
+    let (utxo_circuit, _) =
         DPCUtxoCircuit::build(witness, public_inputs).map_err(DPCApiError::FailedSnark)?;
 
     PlonkKzgSnark::prove::<_, _, StandardTranscript>(rng, &utxo_circuit.0, proving_key, None)
@@ -228,27 +261,45 @@ pub(super) fn verify_utxo(
         proof,
         None,
     )
-    .map_err(DPCApiError::FailedSnark)
+    .map_err(|_| TxnVerificationError::InvalidUtxoProof.into())
 }
 
-// `num_non_fee_inputs` is the number of inputs that exclude the fee input.
-pub(crate) fn preprocess_utxo_keys(
-    srs: &InnerUniversalParam,
-    num_non_fee_inputs: usize,
-) -> Result<(UtxoProvingKey, UtxoVerifyingKey, usize), DPCApiError> {
-    let dummy_circuit = DPCUtxoCircuit::build_for_preprocessing(num_non_fee_inputs)?;
-    let (proving_key, verifying_key) =
-        PlonkKzgSnark::<InnerPairingEngine>::preprocess(srs, &dummy_circuit.0)
-            .map_err(DPCApiError::FailedSnark)?;
-    Ok((proving_key, verifying_key, dummy_circuit.0.num_gates()))
+pub(super) fn batch_verify_utxo(
+    proofs: &[&ProofUtxo],
+    verifying_key: &UtxoVerifyingKey,
+    public_inputs: &[&DPCUtxoPublicInput],
+) -> Result<(), DPCApiError> {
+    let pub_inputs: Vec<Vec<InnerScalarField>> = public_inputs
+        .iter()
+        .map(|public_input| public_input.to_scalars())
+        .collect();
+    let pub_inputs_ref: Vec<&[InnerScalarField]> =
+        pub_inputs.iter().map(|input| input.as_slice()).collect();
+    PlonkKzgSnark::<InnerPairingEngine>::batch_verify::<StandardTranscript>(
+        &vec![verifying_key; proofs.len()],
+        &pub_inputs_ref,
+        proofs,
+        &vec![None; proofs.len()],
+    )
+    .map_err(|_| TxnVerificationError::InvalidUtxoProof.into())
+}
+
+// `dummy_circuit` is built by `DPCUtxoCircuit::build_for_preprocessing`.
+#[cfg(feature = "prover")]
+pub(crate) fn preprocess_utxo_keys<'a>(
+    srs: &'a InnerUniversalParam,
+    dummy_circuit: &DPCUtxoCircuit,
+) -> Result<(UtxoProvingKey<'a>, UtxoVerifyingKey), DPCApiError> {
+    PlonkKzgSnark::<InnerPairingEngine>::preprocess(srs, &dummy_circuit.0)
+        .map_err(DPCApiError::FailedSnark)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod tests {
     use crate::{
         circuit::utxo::DPCUtxoCircuit,
         constants::{MEMO_LEN, TREE_DEPTH},
-        errors::DPCApiError,
+        errors::{DPCApiError, TxnParameterError},
         keys::KeyChainMasterKey,
         proofs::{
             universal_setup_inner,
@@ -258,7 +309,7 @@ mod tests {
         types::{InnerScalarField, InnerUniversalParam},
     };
     use ark_ff::{UniformRand, Zero};
-    use ark_std::{rand::Rng, vec};
+    use ark_std::{borrow::Cow, rand::Rng, vec};
     use jf_plonk::circuit::Circuit;
     use jf_primitives::merkle_tree::{AccMemberWitness, MerkleTree};
 
@@ -294,7 +345,7 @@ mod tests {
             inputs.push(NoteInput {
                 ro: ro.clone(),
                 acc_member_witness: acc_witness,
-                proof_gen_key: &pgk,
+                proof_gen_key: Cow::Borrowed(&pgk),
                 authorization_randomizer: Default::default(),
                 diversifier_randomizer: diversifier_fee_input.clone(),
             });
@@ -317,17 +368,28 @@ mod tests {
         let public_input = DPCUtxoPublicInput::from_witness(&witness, fee, vec![]).unwrap();
         let public_input_scalars = public_input.to_scalars();
 
-        let circuit = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
+        let (circuit, _) = DPCUtxoCircuit::build(&witness, &public_input).unwrap();
         let circuit_pub_input = circuit.0.public_input().unwrap();
         assert_eq!(public_input_scalars, circuit_pub_input);
+        // a fee input without an asset code is rejected instead of panicking
+        let mut bad_witness = witness.clone();
+        bad_witness.entire_inputs[0].ro.payload.data.clear();
+        assert!(matches!(
+            DPCUtxoPublicInput::from_witness(&bad_witness, fee, vec![]),
+            Err(DPCApiError::InvalidTransactionParameter(
+                TxnParameterError::WrongFeeInputPayload
+            ))
+        ));
     }
 
     fn _test_utxo_proof(
         universal_params: &InnerUniversalParam,
         n_source_inputs: usize,
     ) -> Result<(), DPCApiError> {
-        let (proving_key, verifying_key, _) =
-            super::preprocess_utxo_keys(&universal_params, n_source_inputs)?;
+        let (dummy_circuit, _) =
+            DPCUtxoCircuit::build_for_preprocessing(n_source_inputs, &Default::default())?;
+        let (proving_key, verifying_key) =
+            super::preprocess_utxo_keys(&universal_params, &dummy_circuit)?;
 
         let mut merkle_tree = MerkleTree::new(TREE_DEPTH).unwrap();
         let mut wsk = [0u8; 32];
@@ -377,7 +439,7 @@ mod tests {
             note_inputs.push(NoteInput {
                 ro,
                 acc_member_witness: mt_witness,
-                proof_gen_key: &pgk,
+                proof_gen_key: Cow::Borrowed(&pgk),
                 authorization_randomizer: Default::default(),
                 diversifier_randomizer: rd.clone(),
             });
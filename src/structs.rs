@@ -10,18 +10,26 @@
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! Data structures used in DPC scheme
+#[cfg(feature = "prover")]
 use crate::{
-    constants::{MEMO_LEN, NATIVE_ASSET_CODE, PAYLOAD_DATA_LEN, TREE_DEPTH},
+    constants::MEMO_LEN,
+    keys::{DiversifierRandomizer, ProofGenerationKey},
+};
+use crate::{
+    constants::{NATIVE_ASSET_CODE, PAYLOAD_DATA_LEN},
     errors::DPCApiError,
     keys::{
-        DetectionKeyPair, DetectionPubKey, DiversifiedAddress, DiversifierRandomizer,
-        IncomingViewingKey, NullifierDerivingKey, ProofGenerationKey,
+        DetectionKeyPair, DetectionPubKey, DiversifiedAddress, IncomingViewingKey,
+        NullifierDerivingKey,
     },
+    params::DPCParams,
     types::*,
 };
 use ark_ec::{group::Group, models::twisted_edwards_extended::GroupProjective};
 use ark_ff::UniformRand;
 use ark_serialize::{CanonicalSerialize, *};
+#[cfg(feature = "prover")]
+use ark_std::borrow::Cow;
 use ark_std::{
     borrow::ToOwned,
     format,
@@ -41,15 +49,30 @@ use curve25519_dalek::{
     scalar::Scalar as Curve25519Scalar,
 };
 use hkdf::Hkdf;
+#[cfg(feature = "prover")]
 use jf_plonk::proof_system::structs::VerifyingKey;
-use jf_primitives::{
-    commitment::Commitment, hash_to_group::TEHashToGroup, merkle_tree::AccMemberWitness, prf::PRF,
-};
+#[cfg(feature = "prover")]
+use jf_primitives::{commitment::Commitment, merkle_tree::AccMemberWitness};
+use jf_primitives::{hash_to_group::TEHashToGroup, prf::PRF};
+#[cfg(feature = "prover")]
 use jf_rescue::Permutation;
-use jf_utils::{fq_to_fr_with_mask, tagged_blob};
+#[cfg(feature = "prover")]
+use jf_utils::fq_to_fr_with_mask;
+use jf_utils::tagged_blob;
 
 #[tagged_blob("NULLIFIER")]
-#[derive(Clone, Default, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    CanonicalSerialize,
+    CanonicalDeserialize,
+)]
 /// Record nullifier, unique across ledger to prevent double spending
 pub struct Nullifier(pub(crate) InnerScalarField);
 
@@ -61,11 +84,20 @@ pub struct Nullifier(pub(crate) InnerScalarField);
 pub struct PolicyIdentifier(pub(crate) InnerScalarField);
 
 impl PolicyIdentifier {
+    /// Scalar representation of the policy identifier, as stored in the
+    /// `pid_birth` and `pid_death` fields of a record.
+    pub fn to_scalar(&self) -> InnerScalarField {
+        self.0
+    }
+
+    #[cfg(feature = "prover")]
     pub(crate) fn from_verifying_key(vk: &VerifyingKey<InnerPairingEngine>) -> Self {
         let rescue = Permutation::default();
         let tmp = rescue.sponge_with_padding(&vk.convert_te_coordinates_to_scalars(), 1)[0];
         Self(fq_to_fr_with_mask::<InnerBaseField, InnerScalarField>(&tmp))
     }
+
+    #[cfg(feature = "prover")]
     pub(crate) fn from_verifying_keys(vks: &[VerifyingKey<InnerPairingEngine>]) -> Vec<Self> {
         vks.iter().map(Self::from_verifying_key).collect()
     }
@@ -119,8 +151,50 @@ impl RecordOpening {
         position_in_note: usize,
         note_first_nullifier: Nullifier,
     ) -> Self {
-        let mut payload = Payload::default();
-        payload.data[0] = NATIVE_ASSET_CODE;
+        Self::new_native_asset_with_params(
+            rng,
+            addr,
+            amount,
+            position_in_note,
+            note_first_nullifier,
+            &DPCParams::default(),
+        )
+    }
+
+    /// Sample a new native asset record with the payload length of `params`
+    pub fn new_native_asset_with_params<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        addr: DiversifiedAddress,
+        amount: u128,
+        position_in_note: usize,
+        note_first_nullifier: Nullifier,
+        params: &DPCParams,
+    ) -> Self {
+        Self::new_fee_asset_with_params(
+            rng,
+            addr,
+            NATIVE_ASSET_CODE,
+            amount,
+            position_in_note,
+            note_first_nullifier,
+            params,
+        )
+    }
+
+    /// Sample a new record that can pay fees in the asset `asset_code`: its
+    /// payload only has the asset code and the amount, and it has no
+    /// predicate. The payload length is that of `params`.
+    pub fn new_fee_asset_with_params<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        addr: DiversifiedAddress,
+        asset_code: InnerScalarField,
+        amount: u128,
+        position_in_note: usize,
+        note_first_nullifier: Nullifier,
+        params: &DPCParams,
+    ) -> Self {
+        let mut payload = Payload::zero(params);
+        payload.data[0] = asset_code;
         payload.data[1] = InnerScalarField::from(amount);
         Self::new(
             rng,
@@ -135,9 +209,14 @@ impl RecordOpening {
 
     /// Return a dummy record
     pub fn dummy() -> Self {
+        Self::dummy_with_params(&DPCParams::default())
+    }
+
+    /// Return a dummy record with the payload length of `params`
+    pub fn dummy_with_params(params: &DPCParams) -> Self {
         Self {
             addr: DiversifiedAddress::default(),
-            payload: Payload::dummy(),
+            payload: Payload::dummy(params),
             pid_birth: PolicyIdentifier::default().0,
             pid_death: PolicyIdentifier::default().0,
             nonce: InnerScalarField::zero(),
@@ -145,18 +224,66 @@ impl RecordOpening {
         }
     }
 
+    // Dummy record padding a transaction. The nonce and the blinding factor
+    // are random so that padding records have distinct nullifiers and
+    // commitments, even though padding inputs all share the default
+    // nullifier deriving key.
+    #[cfg(feature = "prover")]
+    pub(crate) fn padding<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        addr: DiversifiedAddress,
+        params: &DPCParams,
+        pid_birth: PolicyIdentifier,
+        pid_death: PolicyIdentifier,
+    ) -> Self {
+        Self {
+            addr,
+            pid_birth: pid_birth.0,
+            pid_death: pid_death.0,
+            nonce: InnerScalarField::rand(rng),
+            blinding: InnerScalarField::rand(rng),
+            ..Self::dummy_with_params(params)
+        }
+    }
+
     /// Return a dummy record
     pub fn dummy_with_pid(pid_birth: PolicyIdentifier, pid_death: PolicyIdentifier) -> Self {
+        Self::dummy_with_pid_and_params(pid_birth, pid_death, &DPCParams::default())
+    }
+
+    /// Return a dummy record with the payload length of `params`
+    pub fn dummy_with_pid_and_params(
+        pid_birth: PolicyIdentifier,
+        pid_death: PolicyIdentifier,
+        params: &DPCParams,
+    ) -> Self {
         Self {
-            addr: DiversifiedAddress::default(),
-            payload: Payload::dummy(),
             pid_birth: pid_birth.0,
             pid_death: pid_death.0,
-            nonce: InnerScalarField::zero(),
-            blinding: InnerScalarField::zero(),
+            ..Self::dummy_with_params(params)
         }
     }
 
+    /// Getter for the owner address
+    pub fn addr(&self) -> &DiversifiedAddress {
+        &self.addr
+    }
+
+    /// Getter for the payload
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    /// Identifier of the birth predicate
+    pub fn pid_birth(&self) -> PolicyIdentifier {
+        PolicyIdentifier(self.pid_birth)
+    }
+
+    /// Identifier of the death predicate
+    pub fn pid_death(&self) -> PolicyIdentifier {
+        PolicyIdentifier(self.pid_death)
+    }
+
     /// Compute record commitment for record
     pub fn derive_record_commitment(&self) -> Result<CommitmentValue, DPCApiError> {
         let mut msg = self.addr.to_elems();
@@ -167,7 +294,7 @@ impl RecordOpening {
         msg.push(self.nonce);
 
         let comm_scheme =
-            jf_primitives::commitment::Commitment::new(7usize + (PAYLOAD_DATA_LEN as usize));
+            jf_primitives::commitment::Commitment::new(7usize + self.payload.data.len());
         comm_scheme
             .commit(&msg, &self.blinding)
             .map_err(DPCApiError::FailedPrimitives)
@@ -195,7 +322,6 @@ pub struct ReceiverMemo {
 impl ReceiverMemo {
     // Calculate the shared symmetric encryption key.
     // Return KDF(DH(public, private))
-    #[allow(dead_code)] // TODO remove it
     fn derive_enc_key(
         public: &GroupProjective<InnerEmbeddedGroup>,
         private: &InnerEmbeddedScalarField,
@@ -216,7 +342,6 @@ impl ReceiverMemo {
     }
 
     // internal function to create `ReceiverMemo` from data in plaintext bytes.
-    #[allow(dead_code)] // TODO remove it
     pub(crate) fn from_bytes<R: CryptoRng + RngCore>(
         rng: &mut R,
         data: &[u8],
@@ -251,6 +376,35 @@ impl ReceiverMemo {
         Ok(Self { epk, ct, tag: None })
     }
 
+    /// Create a `ReceiverMemo` encrypting the record opening `ro` to its
+    /// owner address, optionally tagged for the detector holding the
+    /// secret key of `detection_pub_key`.
+    pub fn from_ro<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        ro: &RecordOpening,
+        detection_pub_key: Option<&DetectionPubKey>,
+    ) -> Result<Self, DPCApiError> {
+        let mut ro_bytes = Vec::new();
+        ro.serialize(&mut ro_bytes)?;
+        let mut memo = Self::from_bytes(rng, &ro_bytes, &ro.addr)?;
+        memo.tag = detection_pub_key.map(|dpk| DetectionTag::create(rng, dpk));
+        Ok(memo)
+    }
+
+    /// Decrypt the receiver memo with the correct `IncomingViewKey` and
+    /// deserialize the `RecordOpening` it contains
+    pub fn decrypt_record_opening(
+        &self,
+        ivk: &IncomingViewingKey,
+    ) -> Result<RecordOpening, DPCApiError> {
+        let ro_bytes = self.decrypt(ivk)?;
+        RecordOpening::deserialize(&ro_bytes[..]).map_err(|_| {
+            DPCApiError::DeserializationError(
+                "Unable to deserialize record opening from receiver memo".to_string(),
+            )
+        })
+    }
+
     /// Decrypt the receiver memo with the correct `IncomingViewKey`
     pub fn decrypt(&self, ivk: &IncomingViewingKey) -> Result<Vec<u8>, DPCApiError> {
         let dec_key = Self::derive_enc_key(&self.epk, &ivk.0)?;
@@ -349,23 +503,39 @@ impl CanonicalDeserialize for DetectionTag {
 pub struct Payload {
     /// true only if record is dummy
     pub is_dummy: bool,
-    pub(crate) data: [InnerScalarField; PAYLOAD_DATA_LEN],
+    pub(crate) data: Vec<InnerScalarField>,
 }
 
+// Payloads of the default length `PAYLOAD_DATA_LEN` keep their fixed-length
+// encoding: the dummy flag byte followed by the data. Payloads of any other
+// length set `VAR_LEN_PAYLOAD_FLAG` in the flag byte and prefix the data with
+// its length, so that records and notes built with the default parameters
+// are encoded as before `DPCParams` was introduced.
+const VAR_LEN_PAYLOAD_FLAG: u8 = 2;
+
 impl CanonicalSerialize for Payload {
     fn serialize<W>(&self, mut writer: W) -> Result<(), ark_serialize::SerializationError>
     where
         W: ark_serialize::Write,
     {
-        writer.write_all(&[self.is_dummy as u8])?;
-        for e in self.data.iter() {
-            e.serialize(&mut writer)?
+        if self.data.len() == PAYLOAD_DATA_LEN {
+            writer.write_all(&[self.is_dummy as u8])?;
+            for e in self.data.iter() {
+                e.serialize(&mut writer)?
+            }
+            Ok(())
+        } else {
+            writer.write_all(&[self.is_dummy as u8 | VAR_LEN_PAYLOAD_FLAG])?;
+            self.data.serialize(&mut writer)
         }
-        Ok(())
     }
 
     fn serialized_size(&self) -> usize {
-        PAYLOAD_DATA_LEN * self.data[0].serialized_size() + 1
+        if self.data.len() == PAYLOAD_DATA_LEN {
+            self.data.iter().map(|e| e.serialized_size()).sum::<usize>() + 1
+        } else {
+            self.data.serialized_size() + 1
+        }
     }
 }
 
@@ -374,28 +544,49 @@ impl CanonicalDeserialize for Payload {
     where
         R: ark_serialize::Read,
     {
-        let mut res = Self::default();
-        let mut is_dummy = [0u8; 1];
-        reader.read_exact(&mut is_dummy)?;
+        let mut flags = [0u8; 1];
+        reader.read_exact(&mut flags)?;
 
-        res.is_dummy = match is_dummy[0] {
+        let is_dummy = match flags[0] & !VAR_LEN_PAYLOAD_FLAG {
             1 => true,
             0 => false,
             _ => return Err(ark_serialize::SerializationError::InvalidData),
         };
-        for e in res.data.iter_mut() {
-            *e = InnerScalarField::deserialize(&mut reader)?;
-        }
-        Ok(res)
+        let data = if flags[0] & VAR_LEN_PAYLOAD_FLAG == 0 {
+            (0..PAYLOAD_DATA_LEN)
+                .map(|_| InnerScalarField::deserialize(&mut reader))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            let data = Vec::<InnerScalarField>::deserialize(&mut reader)?;
+            // the default length has a single encoding
+            if data.len() == PAYLOAD_DATA_LEN {
+                return Err(ark_serialize::SerializationError::InvalidData);
+            }
+            data
+        };
+        Ok(Self { is_dummy, data })
     }
 }
 
 impl Payload {
+    /// Getter for the payload data
+    pub fn data(&self) -> &[InnerScalarField] {
+        &self.data
+    }
+
     /// Creating a dummy payload.
-    fn dummy() -> Payload {
+    fn dummy(params: &DPCParams) -> Payload {
         Self {
             is_dummy: true,
-            data: [InnerScalarField::zero(); PAYLOAD_DATA_LEN as usize],
+            data: vec![InnerScalarField::zero(); params.payload_data_len],
+        }
+    }
+
+    /// Creating an all-zero, non dummy payload.
+    pub fn zero(params: &DPCParams) -> Payload {
+        Self {
+            is_dummy: false,
+            data: vec![InnerScalarField::zero(); params.payload_data_len],
         }
     }
 
@@ -403,33 +594,38 @@ impl Payload {
     /// length is less than PAYLOAD_DATA_LEN; return an error if the length
     /// exceeds PAYLOAD_DATA_LEN.
     pub fn from_scalars(scalars: &[InnerScalarField]) -> Result<Self, DPCApiError> {
-        if scalars.len() > PAYLOAD_DATA_LEN as usize {
-            return Err(DPCApiError::InvalidParameters(format!(
+        Self::from_scalars_with_params(scalars, &DPCParams::default())
+    }
+
+    /// Build the payload from a list of scalars. Pad with zeros if the input
+    /// length is less than the payload length of `params`; return an error
+    /// if the length exceeds it.
+    pub fn from_scalars_with_params(
+        scalars: &[InnerScalarField],
+        params: &DPCParams,
+    ) -> Result<Self, DPCApiError> {
+        if scalars.len() > params.payload_data_len {
+            return Err(DPCApiError::InvalidParameter(format!(
                 "input payload length {} is greater than max allowed {}",
                 scalars.len(),
-                PAYLOAD_DATA_LEN
+                params.payload_data_len
             )));
         }
 
-        let mut res = Self::default();
-        for (i, &scalar) in scalars.iter().enumerate() {
-            res.data[i] = scalar;
-        }
-        res.is_dummy = false;
+        let mut res = Self::zero(params);
+        res.data[..scalars.len()].copy_from_slice(scalars);
 
         Ok(res)
     }
 }
 impl Default for Payload {
     fn default() -> Self {
-        Self {
-            is_dummy: false,
-            data: [InnerScalarField::zero(); PAYLOAD_DATA_LEN as usize],
-        }
+        Self::zero(&DPCParams::default())
     }
 }
 
 /// Commitment structure
+#[cfg(feature = "prover")]
 #[derive(Clone, Default, Debug, PartialEq, Eq)]
 pub struct CompressedLocalData {
     pub(crate) input_record_commitments: Vec<CommitmentValue>,
@@ -437,11 +633,10 @@ pub struct CompressedLocalData {
     pub(crate) memo: Vec<InnerScalarField>,
 }
 
+#[cfg(feature = "prover")]
 impl CompressedLocalData {
-    pub(crate) fn commit(
-        &self,
-        blinding: InnerScalarField,
-    ) -> Result<CommitmentValue, DPCApiError> {
+    /// Commit to the local data with `blinding`
+    pub fn commit(&self, blinding: InnerScalarField) -> Result<CommitmentValue, DPCApiError> {
         let mut msg = vec![];
         for in_rc in self.input_record_commitments.iter() {
             msg.push(*in_rc)
@@ -477,6 +672,7 @@ impl CompressedLocalData {
     }
 }
 
+#[cfg(feature = "prover")]
 #[derive(Clone, Debug)]
 /// A DPC Transaction Note input record and spending data
 pub struct NoteInput<'a> {
@@ -484,21 +680,44 @@ pub struct NoteInput<'a> {
     pub ro: RecordOpening,
     /// Witness of record membership in accumulator
     pub acc_member_witness: AccMemberWitness<InnerScalarField>,
-    /// Proof generation key
-    pub proof_gen_key: &'a ProofGenerationKey,
+    /// Proof generation key, borrowed or owned
+    pub proof_gen_key: Cow<'a, ProofGenerationKey>,
     /// Authorization randomizer
     pub authorization_randomizer: GroupProjective<InnerEmbeddedGroup>,
     /// Diversifier randomizer
     pub diversifier_randomizer: DiversifierRandomizer,
 }
 
+/// A DPC Transaction Note input that owns its proof generation key
+#[cfg(feature = "prover")]
+pub type OwnedNoteInput = NoteInput<'static>;
+
+#[cfg(feature = "prover")]
 impl<'a> NoteInput<'a> {
+    /// Convert the note input into an `OwnedNoteInput`, cloning the proof
+    /// generation key if it is borrowed.
+    pub fn into_owned(self) -> OwnedNoteInput {
+        NoteInput {
+            ro: self.ro,
+            acc_member_witness: self.acc_member_witness,
+            proof_gen_key: Cow::Owned(self.proof_gen_key.into_owned()),
+            authorization_randomizer: self.authorization_randomizer,
+            diversifier_randomizer: self.diversifier_randomizer,
+        }
+    }
+
     /// Create a dummy note input.
     pub fn dummy(proof_gen_key: &'a ProofGenerationKey) -> Self {
+        Self::dummy_with_params(proof_gen_key, &DPCParams::default())
+    }
+
+    /// Create a dummy note input for the record layout and tree depth of
+    /// `params`.
+    pub fn dummy_with_params(proof_gen_key: &'a ProofGenerationKey, params: &DPCParams) -> Self {
         Self {
-            ro: RecordOpening::dummy(),
-            acc_member_witness: AccMemberWitness::dummy(TREE_DEPTH),
-            proof_gen_key,
+            ro: RecordOpening::dummy_with_params(params),
+            acc_member_witness: AccMemberWitness::dummy(params.tree_depth),
+            proof_gen_key: Cow::Borrowed(proof_gen_key),
             authorization_randomizer: Default::default(),
             diversifier_randomizer: DiversifierRandomizer(InnerScalarField::zero()),
         }
@@ -509,26 +728,35 @@ impl<'a> NoteInput<'a> {
         proof_gen_key: &'a ProofGenerationKey,
         pid_birth: PolicyIdentifier,
         pid_death: PolicyIdentifier,
+    ) -> Self {
+        Self::dummy_with_pid_and_params(proof_gen_key, pid_birth, pid_death, &DPCParams::default())
+    }
+
+    /// Create a dummy note input for the record layout and tree depth of
+    /// `params`.
+    pub fn dummy_with_pid_and_params(
+        proof_gen_key: &'a ProofGenerationKey,
+        pid_birth: PolicyIdentifier,
+        pid_death: PolicyIdentifier,
+        params: &DPCParams,
     ) -> Self {
         Self {
-            ro: RecordOpening::dummy_with_pid(pid_birth, pid_death),
-            acc_member_witness: AccMemberWitness::dummy(TREE_DEPTH),
-            proof_gen_key,
-            authorization_randomizer: Default::default(),
-            diversifier_randomizer: DiversifierRandomizer(InnerScalarField::zero()),
+            ro: RecordOpening::dummy_with_pid_and_params(pid_birth, pid_death, params),
+            ..Self::dummy_with_params(proof_gen_key, params)
         }
     }
 }
 
 // Derive predicates/policies commitment from policy identifiers and blinding
 // factor.
+#[cfg(feature = "prover")]
 pub(crate) fn derive_predicates_commitment(
     input_death_pids: &[InnerScalarField],
     output_birth_pids: &[InnerScalarField],
     blind_comm_predicates: InnerScalarField,
 ) -> Result<CommitmentValue, DPCApiError> {
     if input_death_pids.len() != output_birth_pids.len() {
-        return Err(DPCApiError::InvalidParameters(
+        return Err(DPCApiError::InvalidParameter(
             "input death predicates and output birth predicates length mismatch".to_string(),
         ));
     }
@@ -539,8 +767,10 @@ pub(crate) fn derive_predicates_commitment(
         .map_err(DPCApiError::FailedPrimitives)
 }
 
-// this function compresses the entire local data (including fees).
-pub(crate) fn compress_local_data(
+/// Compress the entire local data of a transaction (including fee input and
+/// fee change) into the record commitments and the memo.
+#[cfg(feature = "prover")]
+pub fn compress_local_data(
     entire_note_inputs: &[NoteInput],
     entire_output_ros: &[RecordOpening],
     memo: Vec<InnerScalarField>,
@@ -564,13 +794,18 @@ pub(crate) fn compress_local_data(
 #[cfg(test)]
 mod test {
     use crate::{
-        constants::{NATIVE_ASSET_CODE, PAYLOAD_DATA_LEN},
+        constants::{MEMO_LEN, NATIVE_ASSET_CODE, PAYLOAD_DATA_LEN, TREE_DEPTH},
         errors::DPCApiError,
         keys::{DetectionKeyPair, KeyChainMasterKey},
-        structs::{DetectionTag, Nullifier, Payload, ReceiverMemo, RecordOpening},
+        params::DPCParams,
+        structs::{
+            DetectionTag, Nullifier, Payload, PolicyIdentifier, ReceiverMemo, RecordOpening,
+        },
         types::InnerScalarField,
     };
     use ark_ff::Zero;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{vec, vec::Vec};
     use jf_utils::test_serde_default;
 
     #[test]
@@ -593,6 +828,29 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn receiver_memo_record_opening() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+
+        let wsk = KeyChainMasterKey::test_rand(rng);
+        let wsk2 = KeyChainMasterKey::test_rand(rng);
+        let (_ask, pgk, ivk) = wsk.derive_key_chain_single_consumer();
+        let (_ask2, _pgk2, ivk2) = wsk2.derive_key_chain_single_consumer();
+        let (addr, _d) = wsk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let ro =
+            RecordOpening::new_native_asset(rng, addr, 10, 0, Nullifier(InnerScalarField::zero()));
+
+        let memo = ReceiverMemo::from_ro(rng, &ro, None)?;
+        assert!(memo.tag.is_none());
+        assert_eq!(memo.decrypt_record_opening(&ivk)?, ro);
+        assert!(memo.decrypt_record_opening(&ivk2).is_err());
+
+        let detection_key = DetectionKeyPair::generate(rng);
+        let memo = ReceiverMemo::from_ro(rng, &ro, Some(&detection_key.public_key()))?;
+        assert!(memo.tag.unwrap().detect(&detection_key));
+        Ok(())
+    }
+
     #[test]
     fn detection_tag() {
         let rng = &mut ark_std::test_rng();
@@ -625,7 +883,8 @@ mod test {
 
         assert!(RecordOpening::dummy().payload.is_dummy);
 
-        let ro_native1 = RecordOpening::new_native_asset(rng, addr, 10, 1, Nullifier::default());
+        let ro_native1 =
+            RecordOpening::new_native_asset(rng, addr.clone(), 10, 1, Nullifier::default());
         let commitment0 = ro_native0.derive_record_commitment()?;
         let commitment1 = ro_native1.derive_record_commitment()?;
         assert_ne!(commitment0, commitment1);
@@ -634,6 +893,82 @@ mod test {
         let nullifier1 = ro_native1.nullify(&pgk.nk)?;
         assert_ne!(nullifier0, nullifier1);
 
+        // larger payloads
+        let params = DPCParams::new(TREE_DEPTH, 2 * PAYLOAD_DATA_LEN, MEMO_LEN)?;
+        let ro_native2 = RecordOpening::new_native_asset_with_params(
+            rng,
+            addr,
+            10,
+            0,
+            Nullifier::default(),
+            &params,
+        );
+        assert_eq!(ro_native2.payload.data.len(), 2 * PAYLOAD_DATA_LEN);
+        assert_eq!(&ro_native2.payload.data[..2], &expected_payload_data[..2]);
+        assert!(ro_native2.derive_record_commitment().is_ok());
+        assert_eq!(
+            RecordOpening::dummy_with_params(&params).payload.data.len(),
+            2 * PAYLOAD_DATA_LEN
+        );
+        let pid = PolicyIdentifier(InnerScalarField::from(3u64));
+        let dummy_ro = RecordOpening::dummy_with_pid_and_params(pid, pid, &params);
+        assert_eq!(dummy_ro.payload.data.len(), 2 * PAYLOAD_DATA_LEN);
+        assert!(dummy_ro.payload.is_dummy);
+        assert_eq!((dummy_ro.pid_birth, dummy_ro.pid_death), (pid.0, pid.0));
+        #[cfg(feature = "prover")]
+        {
+            use crate::{keys::ProofGenerationKey, structs::NoteInput};
+            use jf_primitives::merkle_tree::AccMemberWitness;
+
+            let params = DPCParams::new(16, 4, MEMO_LEN)?;
+            let dummy_pgk = ProofGenerationKey::default();
+            let dummy_input = NoteInput::dummy_with_pid_and_params(&dummy_pgk, pid, pid, &params);
+            assert_eq!(dummy_input.ro.payload.data.len(), 4);
+            assert_eq!(dummy_input.ro.pid_death, pid.0);
+            assert!(dummy_input.acc_member_witness == AccMemberWitness::dummy(16));
+        }
+        assert!(
+            Payload::from_scalars_with_params(&[InnerScalarField::zero(); 17], &params).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_serde() -> Result<(), DPCApiError> {
+        let scalar_size = InnerScalarField::zero().serialized_size();
+
+        // default payloads keep the fixed-length encoding
+        let payload = Payload::from_scalars(&[NATIVE_ASSET_CODE, InnerScalarField::from(10u64)])?;
+        let mut bytes = Vec::new();
+        payload.serialize(&mut bytes)?;
+        assert_eq!(bytes.len(), 1 + PAYLOAD_DATA_LEN * scalar_size);
+        assert_eq!(bytes.len(), payload.serialized_size());
+        assert_eq!(bytes[0], 0);
+        assert_eq!(Payload::deserialize(&bytes[..])?, payload);
+        let mut dummy_bytes = Vec::new();
+        RecordOpening::dummy().payload.serialize(&mut dummy_bytes)?;
+        assert_eq!(dummy_bytes[0], 1);
+
+        // other lengths are prefixed with their length
+        for len in [2, 2 * PAYLOAD_DATA_LEN] {
+            let params = DPCParams::new(TREE_DEPTH, len, MEMO_LEN)?;
+            let payload = Payload::from_scalars_with_params(&[NATIVE_ASSET_CODE], &params)?;
+            let mut bytes = Vec::new();
+            payload.serialize(&mut bytes)?;
+            assert_eq!(bytes.len(), 1 + 8 + len * scalar_size);
+            assert_eq!(bytes.len(), payload.serialized_size());
+            assert_eq!(bytes[0], 2);
+            assert_eq!(Payload::deserialize(&bytes[..])?, payload);
+        }
+
+        // the default length only has the fixed-length encoding
+        let mut bytes = vec![2u8];
+        payload.data.serialize(&mut bytes)?;
+        assert!(Payload::deserialize(&bytes[..]).is_err());
+        let mut bytes = vec![4u8];
+        bytes.resize(1 + PAYLOAD_DATA_LEN * scalar_size, 0);
+        assert!(Payload::deserialize(&bytes[..]).is_err());
         Ok(())
     }
 
@@ -10,26 +10,46 @@
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! DPC transaction API to build DPCTxnNote
+#[cfg(feature = "prover")]
 use crate::{
-    errors::DPCApiError,
+    app::PredicateOps,
+    errors::TxnParameterError,
+    keys::{
+        aggregate_authorization_signing_keypairs, AuthorizationKeyPair, DetectionPubKey,
+        DiversifiedAddress, ProofGenerationKey,
+    },
+    params::DPCParams,
     proofs::{
         predicates::Predicate,
-        transaction::{
-            DPCProvingKey, DPCPublicInput, DPCValidityProof, DPCVerifyingKey, DPCWitness,
-        },
+        transaction::{DPCProvingKey, ProvingTimings},
+    },
+    structs::{compress_local_data, NoteInput, Payload, PolicyIdentifier, RecordOpening},
+};
+use crate::{
+    errors::{DPCApiError, TxnVerificationError},
+    params::KeyShape,
+    proofs::{
+        transaction::{DPCPublicInput, DPCValidityProof, DPCVerifyingKey},
         utxo::DPCUtxoPublicInput,
     },
-    structs::{NoteInput, Nullifier, RecordOpening},
+    structs::{Nullifier, ReceiverMemo},
     types::{
         CommitmentValue, InnerEmbeddedGroup, InnerScalarField, NodeValue, SigKeyPair, SigVerKey,
     },
 };
+#[cfg(feature = "prover")]
+use ark_ff::{PrimeField, UniformRand, Zero};
 use ark_serialize::{CanonicalSerialize, *};
+#[cfg(feature = "prover")]
 use ark_std::{
+    borrow::Cow,
+    format,
     rand::{CryptoRng, RngCore},
-    string::ToString,
-    vec::Vec,
+    vec,
 };
+use ark_std::{string::ToString, vec::Vec};
+#[cfg(feature = "prover")]
+use jf_primitives::merkle_tree::AccMemberWitness;
 use jf_primitives::signatures::{SchnorrSignatureScheme, SignatureScheme};
 use jf_utils::{hash_to_field, tagged_blob};
 
@@ -45,6 +65,11 @@ pub struct DPCTxnNote {
 
 impl DPCTxnNote {
     /// Verify transaction note
+    ///
+    /// NOTE: the proofs only bind the fee to the public `aux_info.fee_asset`,
+    /// which may be any asset. Whether fees can be paid in it is decided by
+    /// the ledger, callers must check it, e.g. with
+    /// `LedgerState::validate_note`.
     pub fn verify(
         &self,
         verifying_key: &DPCVerifyingKey,
@@ -63,6 +88,67 @@ impl DPCTxnNote {
         self.body.verify_validity_proof(verifying_key, merkle_root)
     }
 
+    /// Verify a batch of transaction notes against the same `merkle_root`.
+    /// The validity proofs of all notes are checked together, at a cost of a
+    /// constant number of pairings. On failure, return
+    /// `DPCApiError::FailedBatchVerification` with the indices of the invalid
+    /// notes.
+    ///
+    /// As with `verify`, callers must check that the fee asset of each note
+    /// is approved, e.g. with `LedgerState::native_fee`.
+    pub fn verify_batch(
+        notes: &[DPCTxnNote],
+        verifying_key: &DPCVerifyingKey,
+        merkle_root: NodeValue,
+    ) -> Result<(), DPCApiError> {
+        let mut invalid = Vec::new();
+        let mut candidates = Vec::new();
+        let mut public_inputs = Vec::new();
+        for (i, note) in notes.iter().enumerate() {
+            match note
+                .body
+                .check_shape(verifying_key)
+                .and_then(|_| note.verify_authorization())
+                .and_then(|_| note.body.check_instance_and_get_public_input(merkle_root))
+            {
+                Ok(public_input) => {
+                    candidates.push(i);
+                    public_inputs.push(public_input);
+                },
+                Err(_) => invalid.push(i),
+            }
+        }
+
+        let proofs: Vec<&DPCValidityProof> =
+            candidates.iter().map(|&i| &notes[i].body.proof).collect();
+        let batch_result = crate::proofs::transaction::batch_verify(
+            &proofs,
+            verifying_key,
+            &public_inputs.iter().collect::<Vec<_>>(),
+        );
+        if batch_result.is_err() {
+            // fall back to individual verification to find the culprits
+            for (&i, public_input) in candidates.iter().zip(public_inputs.iter()) {
+                if crate::proofs::transaction::verify(
+                    &notes[i].body.proof,
+                    verifying_key,
+                    public_input,
+                )
+                .is_err()
+                {
+                    invalid.push(i);
+                }
+            }
+            invalid.sort_unstable();
+        }
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(DPCApiError::FailedBatchVerification(invalid))
+        }
+    }
+
     /// Verify authorization signature
     pub fn verify_authorization(&self) -> Result<(), DPCApiError> {
         let hashed_body = self.body.hash_to_inner_scalar()?;
@@ -72,7 +158,7 @@ impl DPCTxnNote {
             &[hashed_body],
             &self.signature,
         )
-        .map_err(DPCApiError::FailedAuthorizationSignature)
+        .map_err(|_| TxnVerificationError::InvalidAuthorizationSignature.into())
     }
 }
 /// DPC transaction note body
@@ -85,6 +171,9 @@ pub struct DPCTxnBody {
     pub output_commitments: Vec<CommitmentValue>,
     /// Arbitrary note memo data
     pub memo: Vec<InnerScalarField>,
+    /// Encrypted openings of the output records, either empty or one per
+    /// output record
+    pub receiver_memos: Vec<ReceiverMemo>,
     /// Auxiliary information such as valid Merkle root, fee information (value,
     /// input, change outpout)
     pub aux_info: DPCNoteAuxInfo,
@@ -103,10 +192,16 @@ pub struct DPCTxnBody {
 pub struct DPCNoteAuxInfo {
     /// Accumulator state
     pub merkle_root: NodeValue,
-    /// Proposed fee in native asset type for the transfer
+    /// Proposed fee for the transfer, in units of `fee_asset`
     pub fee: u64,
+    /// Asset code of the fee input and fee change, either the native asset or
+    /// another asset the ledger accepts fees in
+    pub fee_asset: InnerScalarField,
     /// Authorization verification key
     pub auth_verification_key: SigVerKey,
+    /// Domain size of the unmerged inner predicate circuits the note was
+    /// proven for
+    pub inner_policy_domain_size: usize,
 }
 
 impl DPCTxnBody {
@@ -115,6 +210,11 @@ impl DPCTxnBody {
     /// NOTE: `input_death_predicates` and `output_birth_predicates` exclude
     /// that of the first input (fee) and output (fee change) since they don't
     /// have any predicate.
+    ///
+    /// The number of inputs and outputs must be the maximum supported by
+    /// `proving_key`, transactions with fewer inputs or outputs are padded
+    /// with `pad_with_dummy_records` beforehand.
+    #[cfg(feature = "prover")]
     #[allow(clippy::too_many_arguments)]
     pub fn generate<'a, R: CryptoRng + RngCore>(
         rng: &mut R,
@@ -127,42 +227,134 @@ impl DPCTxnBody {
         memo: Vec<InnerScalarField>,
         local_data_commitment_randomness: InnerScalarField,
     ) -> Result<DPCTxnBody, DPCApiError> {
+        let (body, _) = Self::generate_with_timings(
+            rng,
+            proving_key,
+            inputs,
+            outputs,
+            input_death_predicates,
+            output_birth_predicates,
+            fee,
+            memo,
+            local_data_commitment_randomness,
+        )?;
+        Ok(body)
+    }
+
+    /// Generate a DPC transaction Body as `generate` does, and report the
+    /// time spent in each proving stage.
+    #[cfg(feature = "prover")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_with_timings<'a, R: CryptoRng + RngCore>(
+        rng: &mut R,
+        proving_key: &DPCProvingKey,
+        inputs: Vec<NoteInput<'a>>,
+        outputs: Vec<RecordOpening>,
+        input_death_predicates: &[Predicate],
+        output_birth_predicates: &[Predicate],
+        fee: u64,
+        memo: Vec<InnerScalarField>,
+        local_data_commitment_randomness: InnerScalarField,
+    ) -> Result<(DPCTxnBody, ProvingTimings), DPCApiError> {
         // check parameters are correct
+        if inputs.len() != proving_key.max_num_inputs() {
+            return Err(TxnParameterError::WrongNumInputs {
+                expected: proving_key.max_num_inputs(),
+                actual: inputs.len(),
+            }
+            .into());
+        }
+        if outputs.len() != proving_key.max_num_inputs() {
+            return Err(TxnParameterError::WrongNumOutputs {
+                expected: proving_key.max_num_inputs(),
+                actual: outputs.len(),
+            }
+            .into());
+        }
         crate::utils::txn_parameter_sanity_check(
             &inputs,
             &outputs,
             input_death_predicates,
             output_birth_predicates,
             fee,
+            &memo,
+            &proving_key.params,
         )?;
 
-        // assemble witness
-        let witness = DPCWitness::new_unchecked(
+        let (pub_input, proof, timings) = crate::proofs::transaction::generate_proof(
             rng,
+            proving_key,
             inputs,
             outputs,
             input_death_predicates,
             output_birth_predicates,
             local_data_commitment_randomness,
+            fee,
+            memo,
         )?;
-        // derive transaction public inputs
-        let pub_input = DPCPublicInput::from_witness(&witness, fee, memo, proving_key.beta_g)?;
 
-        let proof = crate::proofs::transaction::prove(rng, proving_key, &witness, &pub_input)?;
-
-        Ok(DPCTxnBody {
+        let body = DPCTxnBody {
             input_nullifiers: pub_input.utxo_public_input.input_nullifiers,
             output_commitments: pub_input.utxo_public_input.output_commitments,
             memo: pub_input.utxo_public_input.memo,
+            receiver_memos: Vec::new(),
             aux_info: DPCNoteAuxInfo {
                 merkle_root: pub_input.utxo_public_input.root,
                 fee: pub_input.utxo_public_input.fee,
+                fee_asset: pub_input.utxo_public_input.fee_asset,
                 auth_verification_key: pub_input.utxo_public_input.authorization_verification_key,
+                inner_policy_domain_size: proving_key.shape().unmerged_inner_policy_domain_size,
             },
             predicates_commitment: pub_input.utxo_public_input.commitment_predicates,
             local_data_commitment: pub_input.utxo_public_input.commitment_local_data,
             proof,
-        })
+        };
+        Ok((body, timings))
+    }
+
+    /// Generate a DPC transaction Body together with one `ReceiverMemo` per
+    /// output record (including the fee change), encrypting its opening to
+    /// the output address. The memo of the i-th output is tagged for
+    /// detection if `output_detection_keys[i]` is not `None`.
+    #[cfg(feature = "prover")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_with_receiver_memos<'a, R: CryptoRng + RngCore>(
+        rng: &mut R,
+        proving_key: &DPCProvingKey,
+        inputs: Vec<NoteInput<'a>>,
+        outputs: Vec<RecordOpening>,
+        output_detection_keys: &[Option<DetectionPubKey>],
+        input_death_predicates: &[Predicate],
+        output_birth_predicates: &[Predicate],
+        fee: u64,
+        memo: Vec<InnerScalarField>,
+        local_data_commitment_randomness: InnerScalarField,
+    ) -> Result<DPCTxnBody, DPCApiError> {
+        if output_detection_keys.len() != outputs.len() {
+            return Err(DPCApiError::InvalidParameter(format!(
+                "Number of output detection keys {} does not match number of outputs {}",
+                output_detection_keys.len(),
+                outputs.len()
+            )));
+        }
+        let receiver_memos = outputs
+            .iter()
+            .zip(output_detection_keys.iter())
+            .map(|(ro, dpk)| ReceiverMemo::from_ro(rng, ro, dpk.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut body = Self::generate(
+            rng,
+            proving_key,
+            inputs,
+            outputs,
+            input_death_predicates,
+            output_birth_predicates,
+            fee,
+            memo,
+            local_data_commitment_randomness,
+        )?;
+        body.receiver_memos = receiver_memos;
+        Ok(body)
     }
 
     /// Authorize transaction body by signing it and returning a DPCTxnNote
@@ -181,29 +373,69 @@ impl DPCTxnBody {
         })
     }
 
+    /// Shape of the keys the transaction body was generated with, implied by
+    /// its number of inputs and its inner predicate domain size
+    pub fn shape(&self) -> KeyShape {
+        KeyShape::new(
+            self.input_nullifiers.len().saturating_sub(1),
+            self.aux_info.inner_policy_domain_size,
+        )
+    }
+
     /// Verify transaction body validity proof
     pub(crate) fn verify_validity_proof(
         &self,
         verifying_key: &DPCVerifyingKey,
         merkle_root: NodeValue,
     ) -> Result<(), DPCApiError> {
+        self.check_shape(verifying_key)?;
         let pub_input = self.check_instance_and_get_public_input(merkle_root)?;
         crate::proofs::transaction::verify(&self.proof, verifying_key, &pub_input)
     }
 
+    fn check_shape(&self, verifying_key: &DPCVerifyingKey) -> Result<(), DPCApiError> {
+        if self.shape() != verifying_key.shape() {
+            return Err(TxnVerificationError::WrongShape {
+                expected: verifying_key.shape(),
+                actual: self.shape(),
+            }
+            .into());
+        }
+        if self.output_commitments.len() != verifying_key.num_inputs() {
+            return Err(TxnVerificationError::WrongNumOutputs {
+                expected: verifying_key.num_inputs(),
+                actual: self.output_commitments.len(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
     fn check_instance_and_get_public_input(
         &self,
         merkle_root: NodeValue,
     ) -> Result<DPCPublicInput, DPCApiError> {
         // check root consistency
         if merkle_root != self.aux_info.merkle_root {
-            return Err(DPCApiError::FailedTransactionVerification(
-                "Merkle root do not match".to_string(),
-            ));
+            return Err(TxnVerificationError::WrongMerkleRoot {
+                expected: merkle_root,
+                actual: self.aux_info.merkle_root,
+            }
+            .into());
+        }
+        if !self.receiver_memos.is_empty()
+            && self.receiver_memos.len() != self.output_commitments.len()
+        {
+            return Err(TxnVerificationError::WrongNumReceiverMemos {
+                expected: self.output_commitments.len(),
+                actual: self.receiver_memos.len(),
+            }
+            .into());
         }
         let utxo_public_input = DPCUtxoPublicInput {
             root: self.aux_info.merkle_root,
             fee: self.aux_info.fee,
+            fee_asset: self.aux_info.fee_asset,
             input_nullifiers: self.input_nullifiers.clone(),
             output_commitments: self.output_commitments.clone(),
             commitment_local_data: self.local_data_commitment,
@@ -226,3 +458,544 @@ impl DPCTxnBody {
         Ok(hash_to_field(&serialized_body))
     }
 }
+
+/// Pad the inputs and outputs of a transaction with dummy records up to the
+/// maximum numbers of inputs and outputs of `proving_key`, so that a single
+/// key set supports transactions with any number of inputs and any number of
+/// outputs up to these maxima.
+///
+/// Dummy records carry the policy identifiers `pid_birth` and `pid_death`,
+/// usually those of the application, whose predicates must be listed for the
+/// dummy records too when finalizing the predicates and generating the
+/// transaction. Dummy inputs do not contribute to the authorization key, and
+/// dummy outputs are addressed to the owner of the fee change so that their
+/// receiver memos can be produced as usual.
+#[cfg(feature = "prover")]
+pub fn pad_with_dummy_records<'a, R: CryptoRng + RngCore>(
+    rng: &mut R,
+    proving_key: &DPCProvingKey,
+    inputs: &mut Vec<NoteInput<'a>>,
+    outputs: &mut Vec<RecordOpening>,
+    pid_birth: PolicyIdentifier,
+    pid_death: PolicyIdentifier,
+) -> Result<(), DPCApiError> {
+    pad_records(
+        rng,
+        proving_key.max_num_inputs(),
+        proving_key.params(),
+        inputs,
+        outputs,
+        pid_birth,
+        pid_death,
+    )
+}
+
+// Pad `inputs` and `outputs` to `entire_input_size` records each.
+#[cfg(feature = "prover")]
+pub(crate) fn pad_records<'a, R: CryptoRng + RngCore>(
+    rng: &mut R,
+    entire_input_size: usize,
+    params: &DPCParams,
+    inputs: &mut Vec<NoteInput<'a>>,
+    outputs: &mut Vec<RecordOpening>,
+    pid_birth: PolicyIdentifier,
+    pid_death: PolicyIdentifier,
+) -> Result<(), DPCApiError> {
+    if inputs.is_empty() || outputs.is_empty() {
+        return Err(DPCApiError::InvalidParameter(
+            "Cannot pad a transaction without fee input and fee change output".to_string(),
+        ));
+    }
+    if inputs.len() > entire_input_size || outputs.len() > entire_input_size {
+        return Err(DPCApiError::InvalidParameter(format!(
+            "Too many inputs ({}) or outputs ({}), at most {} are supported",
+            inputs.len(),
+            outputs.len(),
+            entire_input_size
+        )));
+    }
+    let fee_change_addr = outputs[0].addr.clone();
+    while inputs.len() < entire_input_size {
+        inputs.push(NoteInput {
+            ro: RecordOpening::padding(rng, Default::default(), params, pid_birth, pid_death),
+            acc_member_witness: AccMemberWitness::dummy(params.tree_depth),
+            // the default authorization key is the identity, padding inputs
+            // share its nullifier deriving key and only differ by their nonce
+            proof_gen_key: Cow::Owned(ProofGenerationKey::default()),
+            authorization_randomizer: Default::default(),
+            diversifier_randomizer: Default::default(),
+        });
+    }
+    while outputs.len() < entire_input_size {
+        outputs.push(RecordOpening::padding(
+            rng,
+            fee_change_addr.clone(),
+            params,
+            pid_birth,
+            pid_death,
+        ));
+    }
+    Ok(())
+}
+
+/// Builder of authorized transaction notes for the records of one
+/// application.
+///
+/// The builder takes the spent notes, the recipients, the fee and the memo,
+/// and takes care of the rest: it creates the fee change and the output
+/// records, pads the transaction with dummy records, commits to the local
+/// data, finalizes the application predicates, generates the transaction
+/// body with receiver memos for all outputs and authorizes it.
+///
+/// Spent notes other than the fee input must be spendable by the death
+/// predicate of the application, and outputs are bound to both predicates of
+/// the application. Authorization randomizers of spent notes are not
+/// supported and must be left to their default value.
+#[cfg(feature = "prover")]
+pub struct TransactionBuilder<'b, P> {
+    proving_key: &'b DPCProvingKey,
+    birth_predicate: P,
+    birth_pid: PolicyIdentifier,
+    death_predicate: P,
+    death_pid: PolicyIdentifier,
+    fee_input: Option<(NoteInput<'b>, SigKeyPair)>,
+    inputs: Vec<(NoteInput<'b>, SigKeyPair)>,
+    recipients: Vec<(DiversifiedAddress, Payload, Option<DetectionPubKey>)>,
+    change_address: Option<DiversifiedAddress>,
+    fee: u64,
+    memo: Option<Vec<InnerScalarField>>,
+}
+
+#[cfg(feature = "prover")]
+impl<'a, 'b, P: PredicateOps<'a>> TransactionBuilder<'b, P> {
+    /// Start a transaction of the application whose keys and predicates are
+    /// output by `PredicateOps::preprocess`.
+    pub fn new(
+        proving_key: &'b DPCProvingKey,
+        birth_predicate: P,
+        birth_pid: PolicyIdentifier,
+        death_predicate: P,
+        death_pid: PolicyIdentifier,
+    ) -> Self {
+        Self {
+            proving_key,
+            birth_predicate,
+            birth_pid,
+            death_predicate,
+            death_pid,
+            fee_input: None,
+            inputs: Vec::new(),
+            recipients: Vec::new(),
+            change_address: None,
+            fee: 0,
+            memo: None,
+        }
+    }
+
+    /// Pay the fee with a record owned by `authorization_key`, of the native
+    /// asset or of another asset the ledger accepts fees in (see
+    /// `RecordOpening::new_fee_asset_with_params`). The fee change is of the
+    /// same asset and returns to the address of the record, unless
+    /// `change_address` is set.
+    ///
+    /// The owner of the fee input may differ from that of the other inputs,
+    /// so that a sponsor pays the fee of the transaction.
+    pub fn fee_input(
+        mut self,
+        note: NoteInput<'b>,
+        authorization_key: &AuthorizationKeyPair,
+    ) -> Self {
+        self.fee_input = Some((note, authorization_key.signing_key_pair().clone()));
+        self
+    }
+
+    /// Spend a record of the application owned by `authorization_key`
+    pub fn add_input(
+        mut self,
+        note: NoteInput<'b>,
+        authorization_key: &AuthorizationKeyPair,
+    ) -> Self {
+        self.inputs
+            .push((note, authorization_key.signing_key_pair().clone()));
+        self
+    }
+
+    /// Create a record of the application with `payload` for `addr`. Its
+    /// receiver memo is tagged for detection if `detection_key` is not
+    /// `None`.
+    pub fn add_recipient(
+        mut self,
+        addr: DiversifiedAddress,
+        payload: Payload,
+        detection_key: Option<DetectionPubKey>,
+    ) -> Self {
+        self.recipients.push((addr, payload, detection_key));
+        self
+    }
+
+    /// Address receiving the fee change
+    pub fn change_address(mut self, addr: DiversifiedAddress) -> Self {
+        self.change_address = Some(addr);
+        self
+    }
+
+    /// Fee paid by the transaction, 0 by default
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Memo of the transaction, all zeros by default
+    pub fn memo(mut self, memo: Vec<InnerScalarField>) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    /// Generate and authorize the transaction note
+    pub fn build<R: CryptoRng + RngCore>(mut self, rng: &mut R) -> Result<DPCTxnNote, DPCApiError> {
+        let params = *self.proving_key.params();
+        let (fee_input, fee_key) = self.fee_input.ok_or_else(|| {
+            DPCApiError::InvalidParameter("Transaction has no fee input".to_string())
+        })?;
+        let (fee_asset, fee_input_value) = check_fee_input(&fee_input.ro, self.fee, &params)?;
+
+        // fee change first, then one output per recipient
+        let first_nullifier = fee_input.ro.nullify(&fee_input.proof_gen_key.nk)?;
+        let change_address = self
+            .change_address
+            .unwrap_or_else(|| fee_input.ro.addr.clone());
+        let mut outputs = vec![RecordOpening::new_fee_asset_with_params(
+            rng,
+            change_address,
+            fee_asset,
+            (fee_input_value - self.fee) as u128,
+            0,
+            first_nullifier.clone(),
+            &params,
+        )];
+        let mut detection_keys = vec![None];
+        for (i, (addr, payload, detection_key)) in self.recipients.into_iter().enumerate() {
+            outputs.push(RecordOpening::new(
+                rng,
+                addr,
+                payload,
+                self.birth_pid.0,
+                self.death_pid.0,
+                i + 1,
+                first_nullifier.clone(),
+            ));
+            detection_keys.push(detection_key);
+        }
+
+        let mut auth_keys = vec![fee_key];
+        let mut inputs = vec![fee_input];
+        for (note, auth_key) in self.inputs.into_iter() {
+            inputs.push(note);
+            auth_keys.push(auth_key);
+        }
+        pad_with_dummy_records(
+            rng,
+            self.proving_key,
+            &mut inputs,
+            &mut outputs,
+            self.birth_pid,
+            self.death_pid,
+        )?;
+        detection_keys.resize(outputs.len(), None);
+
+        // finalize the predicates on the local data commitment
+        let memo = self
+            .memo
+            .unwrap_or_else(|| vec![InnerScalarField::zero(); params.memo_len]);
+        let blinding_local_data = InnerScalarField::rand(rng);
+        let comm_local_data =
+            compress_local_data(&inputs, &outputs, memo.clone())?.commit(blinding_local_data)?;
+        self.birth_predicate.finalize_for_proving(
+            &inputs,
+            &outputs,
+            &memo,
+            blinding_local_data,
+            comm_local_data,
+            true,
+        )?;
+        self.death_predicate.finalize_for_proving(
+            &inputs,
+            &outputs,
+            &memo,
+            blinding_local_data,
+            comm_local_data,
+            false,
+        )?;
+        let input_death_predicates =
+            vec![self.death_predicate.as_predicate().clone(); inputs.len() - 1];
+        let output_birth_predicates =
+            vec![self.birth_predicate.as_predicate().clone(); outputs.len() - 1];
+
+        let body = DPCTxnBody::generate_with_receiver_memos(
+            rng,
+            self.proving_key,
+            inputs,
+            outputs,
+            &detection_keys,
+            &input_death_predicates,
+            &output_birth_predicates,
+            self.fee,
+            memo,
+            blinding_local_data,
+        )?;
+
+        // dummy inputs have the identity authorization key
+        let randomizers = vec![Default::default(); auth_keys.len()];
+        let auth_key = aggregate_authorization_signing_keypairs(&auth_keys, &randomizers)?;
+        body.authorize(&auth_key)
+    }
+}
+
+// Asset code and value of the fee input of a transaction with the record
+// layout of `params`, which must hold at least `fee`.
+#[cfg(feature = "prover")]
+fn check_fee_input(
+    ro: &RecordOpening,
+    fee: u64,
+    params: &DPCParams,
+) -> Result<(InnerScalarField, u64), DPCApiError> {
+    if params.payload_data_len < 2 {
+        return Err(DPCApiError::InvalidParameter(format!(
+            "Payload data length {} is too short for an asset code and a value",
+            params.payload_data_len
+        )));
+    }
+    let (fee_asset, value) = fee_asset_value(ro).ok_or_else(|| {
+        DPCApiError::InvalidParameter("Fee input is not a fee asset record".to_string())
+    })?;
+    if value < fee {
+        return Err(DPCApiError::IncorrectFee(format!(
+            "Fee input value {} is smaller than the fee {}",
+            value, fee
+        )));
+    }
+    Ok((fee_asset, value))
+}
+
+// Asset code and value of a record that can pay fees, `None` for dummy
+// records, records with predicates, payloads without a value or values that
+// do not fit in a `u64`.
+#[cfg(feature = "prover")]
+fn fee_asset_value(ro: &RecordOpening) -> Option<(InnerScalarField, u64)> {
+    if ro.payload.is_dummy || !ro.pid_death.is_zero() {
+        return None;
+    }
+    let (asset, value) = match ro.payload.data.as_slice() {
+        [asset, value, ..] => (*asset, value.into_repr()),
+        _ => return None,
+    };
+    let limbs = value.as_ref();
+    if limbs[1..].iter().any(|limb| *limb != 0) {
+        return None;
+    }
+    Some((asset, limbs[0]))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod test {
+    use super::*;
+    use crate::{
+        examples::{
+            tests::{build_notes, build_notes_and_records},
+            zcash_example::ZcashPredicate,
+        },
+        keys::KeyChainMasterKey,
+        proofs::{universal_setup_inner, universal_setup_outer},
+        structs::Nullifier,
+    };
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_builder_fee_input_and_padding() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let params = DPCParams::default();
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (change_addr, _) = msk.derive_diversified_address(&pgk, &ivk, 1)?;
+
+        // fee input checks
+        let fee_record =
+            RecordOpening::new_native_asset(rng, addr.clone(), 300, 0, Nullifier::default());
+        assert_eq!(
+            check_fee_input(&fee_record, 5, &params)?,
+            (fee_record.payload.data[0], 300)
+        );
+        assert!(matches!(
+            check_fee_input(&fee_record, 301, &params),
+            Err(DPCApiError::IncorrectFee(_))
+        ));
+        let short_params = DPCParams {
+            payload_data_len: 1,
+            ..params
+        };
+        assert!(check_fee_input(&fee_record, 5, &short_params).is_err());
+        let mut short_record = fee_record.clone();
+        short_record.payload.data.truncate(1);
+        assert_eq!(fee_asset_value(&short_record), None);
+        assert!(check_fee_input(&short_record, 5, &params).is_err());
+        let dummy_record = RecordOpening::dummy_with_params(&params);
+        assert!(check_fee_input(&dummy_record, 0, &params).is_err());
+
+        // one fee input and one application input, padded with 2 dummy
+        // inputs and 3 dummy outputs
+        let (input_records, _) = build_notes_and_records(
+            rng,
+            &addr,
+            &pgk,
+            300,
+            295,
+            2,
+            &[40],
+            &[],
+            PolicyIdentifier::default(),
+            PolicyIdentifier::default(),
+        )?;
+        let mut inputs = build_notes(&input_records, &pgk, &rd)?;
+        let mut outputs = vec![RecordOpening::new_native_asset(
+            rng,
+            change_addr.clone(),
+            295,
+            0,
+            inputs[0].ro.nullify(&pgk.nk)?,
+        )];
+        pad_records(
+            rng,
+            4,
+            &params,
+            &mut inputs,
+            &mut outputs,
+            PolicyIdentifier::default(),
+            PolicyIdentifier::default(),
+        )?;
+        assert_eq!(inputs.len(), 4);
+        assert_eq!(outputs.len(), 4);
+        assert!(inputs[2..].iter().all(|input| input.ro.payload.is_dummy));
+        assert!(outputs[1..]
+            .iter()
+            .all(|output| output.payload.is_dummy && output.addr == change_addr));
+        let nullifiers = inputs
+            .iter()
+            .map(|input| input.ro.nullify(&input.proof_gen_key.nk))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, nullifier) in nullifiers.iter().enumerate() {
+            assert!(!nullifiers[i + 1..].contains(nullifier));
+        }
+
+        // no more inputs than supported
+        assert!(pad_records(
+            rng,
+            3,
+            &params,
+            &mut inputs,
+            &mut outputs,
+            PolicyIdentifier::default(),
+            PolicyIdentifier::default(),
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_verify_batch() -> Result<(), DPCApiError> {
+        let rng = &mut test_rng();
+        let inner_srs = universal_setup_inner((1 << 17) + 4, rng)?;
+        let outer_srs = universal_setup_outer((1 << 18) + 4, rng)?;
+        let (dpc_pk, dpc_vk, birth_predicate, birth_pid, death_predicate, death_pid) =
+            ZcashPredicate::preprocess(&inner_srs, &outer_srs, 2, &DPCParams::default())?;
+
+        let msk = KeyChainMasterKey::generate([1u8; 32], &[]);
+        let (ak, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+
+        // two pairs of fee and application records in the same tree
+        let mut input_records = vec![];
+        for _ in 0..2 {
+            let (records, _) = build_notes_and_records(
+                rng,
+                &addr,
+                &pgk,
+                300,
+                295,
+                2,
+                &[40],
+                &[],
+                birth_pid,
+                death_pid,
+            )?;
+            input_records.extend(records);
+        }
+        let input_notes = build_notes(&input_records, &pgk, &rd)?;
+        let merkle_root = input_notes[0].acc_member_witness.root;
+        let mut input_notes = input_notes.into_iter();
+        let payload =
+            Payload::from_scalars(&[InnerScalarField::from(2u64), InnerScalarField::from(40u64)])?;
+        let mut notes = vec![];
+        for _ in 0..2 {
+            notes.push(
+                TransactionBuilder::new(
+                    &dpc_pk,
+                    ZcashPredicate(birth_predicate.0.clone()),
+                    birth_pid,
+                    ZcashPredicate(death_predicate.0.clone()),
+                    death_pid,
+                )
+                .fee_input(input_notes.next().unwrap(), &ak)
+                .add_input(input_notes.next().unwrap(), &ak)
+                .add_recipient(addr.clone(), payload.clone(), None)
+                .fee(5)
+                .build(rng)?,
+            );
+        }
+        let auth_key = aggregate_authorization_signing_keypairs(
+            &[ak.signing_key_pair().clone(), ak.signing_key_pair().clone()],
+            &[Default::default(), Default::default()],
+        )?;
+
+        // good path
+        DPCTxnNote::verify_batch(&[], &dpc_vk, merkle_root)?;
+        DPCTxnNote::verify_batch(&notes, &dpc_vk, merkle_root)?;
+
+        // bad path: a correctly signed note with an invalid proof
+        let mut bad_body = notes[0].body.clone();
+        bad_body.memo[0] += InnerScalarField::from(1u64);
+        let bad_proof_note = bad_body.authorize(&auth_key)?;
+        bad_proof_note.verify_authorization()?;
+
+        // bad path: a correctly signed note of another shape
+        let mut bad_body = notes[0].body.clone();
+        bad_body.aux_info.inner_policy_domain_size *= 2;
+        let bad_shape_note = bad_body.authorize(&auth_key)?;
+        assert!(matches!(
+            bad_shape_note.verify(&dpc_vk, merkle_root),
+            Err(DPCApiError::FailedTransactionVerification(
+                TxnVerificationError::WrongShape { .. }
+            ))
+        ));
+
+        // bad path: a valid proof with the signature of another note
+        let mut bad_signature_note = notes[0].clone();
+        bad_signature_note.signature = notes[1].signature.clone();
+
+        let batch = vec![
+            notes[0].clone(),
+            bad_proof_note,
+            notes[1].clone(),
+            bad_shape_note,
+            bad_signature_note,
+        ];
+        match DPCTxnNote::verify_batch(&batch, &dpc_vk, merkle_root) {
+            Err(DPCApiError::FailedBatchVerification(indices)) => {
+                assert_eq!(indices, vec![1, 3, 4])
+            },
+            _ => panic!("batch verification should fail on notes 1, 3 and 4"),
+        }
+        Ok(())
+    }
+}
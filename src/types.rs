@@ -98,12 +98,13 @@ pub type CommitmentValue = InnerScalarField;
 
 /// The universal parameters (Structured Reference String) for proving/verifying
 /// UTXO relations and inner predicate policies. Generate only once during a
-/// multi-party setup. For testing purpose, use `universal_setup()` for a
-/// one-time generation.
+/// multi-party setup and import it with `srs::load_inner_srs`. For testing
+/// purpose, use `universal_setup_inner()` for a one-time generation.
 pub type InnerUniversalParam = UniversalSrs<InnerPairingEngine>;
 
 /// The universal parameters (Structured Reference String) for proving/verifying
 /// the outer relation that recursively verifies the inner policy proofs.
-/// Generate only once during a multi-party setup. For testing purpose, use
-/// `universal_setup()` for a one-time generation.
+/// Generate only once during a multi-party setup and import it with
+/// `srs::load_outer_srs`. For testing purpose, use `universal_setup_outer()`
+/// for a one-time generation.
 pub type OuterUniversalParam = UniversalSrs<OuterPairingEngine>;
@@ -10,14 +10,14 @@
 // details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    constants::{PAYLOAD_DATA_LEN, TREE_DEPTH},
-    errors::DPCApiError,
+    errors::{DPCApiError, TxnParameterError},
+    params::DPCParams,
     predicates::PredicateTrait,
     proofs::predicates::Predicate,
     structs::{NoteInput, PolicyIdentifier, RecordOpening},
+    types::InnerScalarField,
 };
 use ark_ff::Zero;
-use ark_std::{format, string::ToString};
 use jf_primitives::merkle_tree::AccMemberWitness;
 
 pub(crate) fn txn_parameter_sanity_check(
@@ -26,20 +26,25 @@ pub(crate) fn txn_parameter_sanity_check(
     input_death_predicates: &[Predicate],
     output_birth_predicates: &[Predicate],
     fee: u64,
+    memo: &[InnerScalarField],
+    params: &DPCParams,
 ) -> Result<(), DPCApiError> {
     if inputs.is_empty() {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: input is empty".to_string(),
-        ));
+        return Err(TxnParameterError::NoInput.into());
     }
     if outputs.is_empty() {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: output is empty".to_string(),
-        ));
+        return Err(TxnParameterError::NoOutput.into());
     }
-    check_payload_length(inputs, outputs)?;
+    if memo.len() != params.memo_len {
+        return Err(TxnParameterError::WrongMemoLength {
+            expected: params.memo_len,
+            actual: memo.len(),
+        }
+        .into());
+    }
+    check_payload_length(inputs, outputs, params.payload_data_len)?;
     check_fee(&inputs[0].ro, &outputs[0], fee)?;
-    check_non_dummy_inputs_witnesses(inputs)?;
+    check_non_dummy_inputs_witnesses(inputs, params.tree_depth)?;
     check_predicates_consistency(
         inputs,
         outputs,
@@ -60,13 +65,16 @@ fn check_predicates_consistency(
         .zip(input_death_predicates.iter())
         .enumerate()
     {
-        let pid = note.ro.pid_death;
-        let expected_pid = PolicyIdentifier::from_verifying_key(predicate.verifying_key()).0;
+        let pid = note.ro.pid_death();
+        let expected_pid = PolicyIdentifier::from_verifying_key(predicate.verifying_key());
         if pid != expected_pid {
-            return Err(DPCApiError::InvalidParameter(format!(
-                "Wrong input death predicate {}, expected {}, actual {}",
-                index, expected_pid, pid,
-            )));
+            return Err(TxnParameterError::WrongInputDeathPredicate {
+                // predicates skip the fee input
+                input: index + 1,
+                expected: expected_pid,
+                actual: pid,
+            }
+            .into());
         }
     }
 
@@ -76,13 +84,15 @@ fn check_predicates_consistency(
         .zip(output_birth_predicates.iter())
         .enumerate()
     {
-        let pid = ro.pid_birth;
-        let expected_pid = PolicyIdentifier::from_verifying_key(predicate.verifying_key()).0;
+        let pid = ro.pid_birth();
+        let expected_pid = PolicyIdentifier::from_verifying_key(predicate.verifying_key());
         if pid != expected_pid {
-            return Err(DPCApiError::InvalidParameter(format!(
-                "Wrong output birth predicate {}, expected {}, actual {}",
-                index, expected_pid, pid,
-            )));
+            return Err(TxnParameterError::WrongOutputBirthPredicate {
+                output: index + 1,
+                expected: expected_pid,
+                actual: pid,
+            }
+            .into());
         }
     }
 
@@ -92,25 +102,26 @@ fn check_predicates_consistency(
 fn check_payload_length(
     inputs: &[NoteInput],
     outputs: &[RecordOpening],
+    payload_data_len: usize,
 ) -> Result<(), DPCApiError> {
     for (index, input) in inputs.iter().enumerate() {
-        if input.ro.payload.data.iter().len() != PAYLOAD_DATA_LEN as usize {
-            return Err(DPCApiError::InvalidParameter(format!(
-                "Wrong payload length for input {}, expected {}, actual {}",
-                index,
-                PAYLOAD_DATA_LEN,
-                input.ro.payload.data.len()
-            )));
+        if input.ro.payload.data.len() != payload_data_len {
+            return Err(TxnParameterError::WrongInputPayloadLength {
+                input: index,
+                expected: payload_data_len,
+                actual: input.ro.payload.data.len(),
+            }
+            .into());
         }
     }
     for (index, output) in outputs.iter().enumerate() {
-        if output.payload.data.iter().len() != PAYLOAD_DATA_LEN as usize {
-            return Err(DPCApiError::InvalidParameter(format!(
-                "Wrong payload length for output {}, expected {}, actual {}",
-                index,
-                PAYLOAD_DATA_LEN,
-                output.payload.data.len()
-            )));
+        if output.payload.data.len() != payload_data_len {
+            return Err(TxnParameterError::WrongOutputPayloadLength {
+                output: index,
+                expected: payload_data_len,
+                actual: output.payload.data.len(),
+            }
+            .into());
         }
     }
     Ok(())
@@ -123,83 +134,156 @@ fn check_fee(
 ) -> Result<(), DPCApiError> {
     // not dummy input
     if fee_input.payload.is_dummy {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: fee input is dummy".to_string(),
-        ));
+        return Err(TxnParameterError::DummyFeeInput.into());
     }
     // payload has dummy data except amount and asset type
-    if fee_input
-        .payload
-        .data
-        .iter()
-        .skip(2)
-        .any(|elem| !elem.is_zero())
+    if fee_input.payload.data.len() < 2
+        || fee_input
+            .payload
+            .data
+            .iter()
+            .skip(2)
+            .any(|elem| !elem.is_zero())
     {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: fee input wrong payload".to_string(),
-        ));
+        return Err(TxnParameterError::WrongFeeInputPayload.into());
     }
-    // input is of native asset type
-    if fee_input.payload.data[0] != crate::constants::NATIVE_ASSET_CODE {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: fee input is not native asset type".to_string(),
-        ));
+    // predicates skip the fee input
+    if !fee_input.pid_death.is_zero() {
+        return Err(TxnParameterError::WrongFeeInputPolicy.into());
     }
 
     // Check OUTPUT
     // not dummy input
     if fee_chg_output.payload.is_dummy {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: fee change is dummy".to_string(),
-        ));
+        return Err(TxnParameterError::DummyFeeChange.into());
     }
     // payload has dummy data except amount and asset type
-    if fee_chg_output
-        .payload
-        .data
-        .iter()
-        .skip(2)
-        .any(|elem| !elem.is_zero())
+    if fee_chg_output.payload.data.len() < 2
+        || fee_chg_output
+            .payload
+            .data
+            .iter()
+            .skip(2)
+            .any(|elem| !elem.is_zero())
     {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: fee change output wrong payload".to_string(),
-        ));
+        return Err(TxnParameterError::WrongFeeChangePayload.into());
     }
-    // output is of native asset type
-    if fee_chg_output.payload.data[0] != crate::constants::NATIVE_ASSET_CODE {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: fee change is not native asset type".to_string(),
-        ));
+    // predicates skip the fee change
+    if !fee_chg_output.pid_birth.is_zero() || !fee_chg_output.pid_death.is_zero() {
+        return Err(TxnParameterError::WrongFeeChangePolicy.into());
+    }
+    // output is of the fee asset type, whether native or not: approved fee
+    // assets are checked by the ledger
+    if fee_chg_output.payload.data[0] != fee_input.payload.data[0] {
+        return Err(TxnParameterError::WrongFeeChangeAsset.into());
     }
     // check fee amount is correct
     if fee_input.payload.data[1] - fee_chg_output.payload.data[1]
         != crate::types::InnerScalarField::from(fee as u128)
     {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: wrong fee".to_string(),
-        ));
+        return Err(TxnParameterError::WrongFee(fee).into());
     }
     Ok(())
 }
 
-fn check_non_dummy_inputs_witnesses(inputs: &[NoteInput]) -> Result<(), DPCApiError> {
-    let dummy_witness = AccMemberWitness::dummy(TREE_DEPTH);
-    if inputs
+fn check_non_dummy_inputs_witnesses(
+    inputs: &[NoteInput],
+    tree_depth: u8,
+) -> Result<(), DPCApiError> {
+    let dummy_witness = AccMemberWitness::dummy(tree_depth);
+    // non dummy records cannot have dummy acc member witness, but dummy
+    // records must
+    if let Some(index) = inputs
         .iter()
-        .any(|input| input.ro.payload.is_dummy ^ (input.acc_member_witness == dummy_witness))
+        .position(|input| input.ro.payload.is_dummy ^ (input.acc_member_witness == dummy_witness))
     {
-        return Err(DPCApiError::InvalidParameter("Cannot generate DPC transaction: Non dummy records cannot have dummy acc member witness, but dummy records must".to_string()));
+        return Err(TxnParameterError::WrongInputWitness(index).into());
     }
     // assume inputs[0] already checked is non dummy (it is the fee input)
     let root = inputs[0].acc_member_witness.root;
-    if inputs
+    if let Some(index) = inputs
         .iter()
-        .any(|input| !input.ro.payload.is_dummy && input.acc_member_witness.root != root)
+        .position(|input| !input.ro.payload.is_dummy && input.acc_member_witness.root != root)
     {
-        return Err(DPCApiError::InvalidParameter(
-            "Cannot generate DPC transaction: input witnesses do not share same merkle root"
-                .to_string(),
-        ));
+        return Err(TxnParameterError::WrongInputRoot(index).into());
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        examples::tests::{build_notes, build_notes_and_records},
+        keys::KeyChainMasterKey,
+    };
+    use ark_std::vec;
+
+    #[test]
+    fn test_txn_parameter_errors() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        let params = DPCParams::default();
+        let msk = KeyChainMasterKey::generate([7u8; 32], &[]);
+        let (_, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (addr, rd) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let pid = PolicyIdentifier::default();
+        let (input_records, output_records) =
+            build_notes_and_records(rng, &addr, &pgk, 30, 25, 2, &[10], &[10], pid, pid)?;
+        let inputs = build_notes(&input_records, &pgk, &rd)?;
+        let memo = vec![InnerScalarField::zero(); params.memo_len];
+        let check = |inputs: &[NoteInput], outputs: &[RecordOpening], fee, memo: &[_]| {
+            txn_parameter_sanity_check(inputs, outputs, &[], &[], fee, memo, &params)
+        };
+        check(&inputs, &output_records, 5, &memo)?;
+
+        let expect_err = |result: Result<(), DPCApiError>, expected: TxnParameterError| {
+            assert!(matches!(
+                result,
+                Err(DPCApiError::InvalidTransactionParameter(e)) if e == expected
+            ));
+        };
+        expect_err(
+            check(&[], &output_records, 5, &memo),
+            TxnParameterError::NoInput,
+        );
+        expect_err(
+            check(&inputs, &output_records, 5, &[]),
+            TxnParameterError::WrongMemoLength {
+                expected: params.memo_len,
+                actual: 0,
+            },
+        );
+        expect_err(
+            check(&inputs, &output_records, 4, &memo),
+            TxnParameterError::WrongFee(4),
+        );
+        expect_err(
+            check(&inputs[1..], &output_records, 5, &memo),
+            TxnParameterError::WrongFeeChangeAsset,
+        );
+
+        let mut bad_inputs = inputs.clone();
+        bad_inputs[1].acc_member_witness = AccMemberWitness::dummy(params.tree_depth);
+        expect_err(
+            check(&bad_inputs, &output_records, 5, &memo),
+            TxnParameterError::WrongInputWitness(1),
+        );
+        let mut bad_outputs = output_records.clone();
+        bad_outputs[0].pid_birth = InnerScalarField::from(1u64);
+        expect_err(
+            check(&inputs, &bad_outputs, 5, &memo),
+            TxnParameterError::WrongFeeChangePolicy,
+        );
+        let mut bad_outputs = output_records;
+        bad_outputs[1].payload.data.pop();
+        expect_err(
+            check(&inputs, &bad_outputs, 5, &memo),
+            TxnParameterError::WrongOutputPayloadLength {
+                output: 1,
+                expected: params.payload_data_len,
+                actual: params.payload_data_len - 1,
+            },
+        );
+        Ok(())
+    }
+}
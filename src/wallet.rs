@@ -0,0 +1,233 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the VeriZexe library.
+
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version. This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details. You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Wallet scanning: discover records owned by a key-chain from the
+//! `ReceiverMemo`s attached to transaction notes and keep track of the
+//! unspent ones.
+
+use crate::{
+    errors::DPCApiError,
+    keys::{DetectionKeyPair, IncomingViewingKey, NullifierDerivingKey, ProofGenerationKey},
+    structs::{Nullifier, ReceiverMemo, RecordOpening},
+    transaction::DPCTxnNote,
+    types::CommitmentValue,
+};
+use ark_std::{collections::BTreeMap, vec::Vec};
+
+/// A record owned by the wallet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedRecord {
+    /// Opening of the record
+    pub ro: RecordOpening,
+    /// Position of the record commitment in the records accumulator
+    pub uid: u64,
+    /// Nullifier to be published when spending the record
+    pub nullifier: Nullifier,
+}
+
+/// Scanner of transaction notes maintaining the set of owned unspent records
+/// of a key-chain.
+#[derive(Clone, Debug)]
+pub struct WalletScanner {
+    ivk: IncomingViewingKey,
+    nk: NullifierDerivingKey,
+    detection_key: Option<DetectionKeyPair>,
+    unspent: BTreeMap<Nullifier, OwnedRecord>,
+}
+
+impl WalletScanner {
+    /// Create a scanner for the key-chain with proof generation key `pgk`
+    /// and incoming viewing key `ivk`. If `detection_key` is provided, memos
+    /// whose detection tag does not match it are skipped without attempting
+    /// decryption.
+    pub fn new(
+        pgk: &ProofGenerationKey,
+        ivk: IncomingViewingKey,
+        detection_key: Option<DetectionKeyPair>,
+    ) -> Self {
+        Self {
+            ivk,
+            nk: pgk.nk.clone(),
+            detection_key,
+            unspent: BTreeMap::new(),
+        }
+    }
+
+    /// Scan a transaction note whose first output commitment was inserted at
+    /// position `first_uid` of the records accumulator. Owned records spent
+    /// by the note are removed from the unspent set, and newly received
+    /// records are added to it. Return the newly received records.
+    ///
+    /// NOTE: notes must be scanned in the order they are applied to the
+    /// ledger.
+    pub fn scan_note(
+        &mut self,
+        note: &DPCTxnNote,
+        first_uid: u64,
+    ) -> Result<Vec<OwnedRecord>, DPCApiError> {
+        self.scan(
+            &note.body.input_nullifiers,
+            &note.body.output_commitments,
+            &note.body.receiver_memos,
+            first_uid,
+        )
+    }
+
+    /// Scan a sequence of transaction notes together with the position of
+    /// their first output commitment, return all newly received records.
+    pub fn scan_notes<'a, I>(&mut self, notes: I) -> Result<Vec<OwnedRecord>, DPCApiError>
+    where
+        I: IntoIterator<Item = (&'a DPCTxnNote, u64)>,
+    {
+        let mut received = Vec::new();
+        for (note, first_uid) in notes {
+            received.extend(self.scan_note(note, first_uid)?);
+        }
+        Ok(received)
+    }
+
+    /// Track a record received outside of any scanned note (e.g. a genesis
+    /// record) at position `uid` of the records accumulator.
+    pub fn insert_record(
+        &mut self,
+        ro: RecordOpening,
+        uid: u64,
+    ) -> Result<OwnedRecord, DPCApiError> {
+        let nullifier = ro.nullify(&self.nk)?;
+        let record = OwnedRecord { ro, uid, nullifier };
+        self.unspent
+            .insert(record.nullifier.clone(), record.clone());
+        Ok(record)
+    }
+
+    /// Iterator over the owned unspent records
+    pub fn unspent_records(&self) -> impl Iterator<Item = &OwnedRecord> {
+        self.unspent.values()
+    }
+
+    /// Return true if the record with `nullifier` is owned and unspent
+    pub fn is_unspent(&self, nullifier: &Nullifier) -> bool {
+        self.unspent.contains_key(nullifier)
+    }
+
+    fn scan(
+        &mut self,
+        input_nullifiers: &[Nullifier],
+        output_commitments: &[CommitmentValue],
+        receiver_memos: &[ReceiverMemo],
+        first_uid: u64,
+    ) -> Result<Vec<OwnedRecord>, DPCApiError> {
+        for nullifier in input_nullifiers.iter() {
+            self.unspent.remove(nullifier);
+        }
+
+        let mut received = Vec::new();
+        for ((memo, commitment), uid) in receiver_memos
+            .iter()
+            .zip(output_commitments.iter())
+            .zip(first_uid..)
+        {
+            if let Some(ro) = self.try_open(memo, commitment)? {
+                received.push(self.insert_record(ro, uid)?);
+            }
+        }
+        Ok(received)
+    }
+
+    // Return the record opening in `memo` if it is addressed to this wallet
+    // and it opens `commitment`.
+    fn try_open(
+        &self,
+        memo: &ReceiverMemo,
+        commitment: &CommitmentValue,
+    ) -> Result<Option<RecordOpening>, DPCApiError> {
+        if let (Some(dk), Some(tag)) = (&self.detection_key, &memo.tag) {
+            if !tag.detect(dk) {
+                return Ok(None);
+            }
+        }
+        let ro = match memo.decrypt_record_opening(&self.ivk) {
+            Ok(ro) => ro,
+            Err(_) => return Ok(None),
+        };
+        if ro.payload.is_dummy || ro.derive_record_commitment()? != *commitment {
+            return Ok(None);
+        }
+        Ok(Some(ro))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::keys::KeyChainMasterKey;
+    use ark_ff::{UniformRand, Zero};
+    use ark_std::vec;
+
+    #[test]
+    fn test_wallet_scanner() -> Result<(), DPCApiError> {
+        let rng = &mut ark_std::test_rng();
+        let msk = KeyChainMasterKey::test_rand(rng);
+        let other_msk = KeyChainMasterKey::test_rand(rng);
+        let (_ask, pgk, ivk) = msk.derive_key_chain_single_consumer();
+        let (_ask, other_pgk, other_ivk) = other_msk.derive_key_chain_single_consumer();
+        let (addr, _) = msk.derive_diversified_address(&pgk, &ivk, 0)?;
+        let (other_addr, _) = other_msk.derive_diversified_address(&other_pgk, &other_ivk, 0)?;
+        let detection_key = DetectionKeyPair::generate(rng);
+        let other_detection_key = DetectionKeyPair::generate(rng);
+
+        let first_nullifier = Nullifier(CommitmentValue::rand(rng));
+        let ros = vec![
+            RecordOpening::new_native_asset(rng, addr.clone(), 10, 0, first_nullifier.clone()),
+            RecordOpening::new_native_asset(rng, other_addr, 20, 1, first_nullifier.clone()),
+            RecordOpening::new_native_asset(rng, addr.clone(), 30, 2, first_nullifier.clone()),
+            RecordOpening::new_native_asset(rng, addr, 40, 3, first_nullifier.clone()),
+            RecordOpening::dummy(),
+        ];
+        let commitments = ros
+            .iter()
+            .map(|ro| ro.derive_record_commitment())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut memos = vec![
+            ReceiverMemo::from_ro(rng, &ros[0], Some(&detection_key.public_key()))?,
+            ReceiverMemo::from_ro(rng, &ros[1], Some(&other_detection_key.public_key()))?,
+            ReceiverMemo::from_ro(rng, &ros[2], None)?,
+            ReceiverMemo::from_ro(rng, &ros[3], None)?,
+            ReceiverMemo::from_ro(rng, &ros[4], None)?,
+        ];
+        // the 4th memo does not open the published commitment
+        memos[3] = ReceiverMemo::from_ro(rng, &ros[0], None)?;
+
+        let mut scanner = WalletScanner::new(&pgk, ivk, Some(detection_key));
+        let received = scanner.scan(&[first_nullifier], &commitments, &memos, 5)?;
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].ro, ros[0]);
+        assert_eq!(received[0].uid, 5);
+        assert_eq!(received[1].ro, ros[2]);
+        assert_eq!(received[1].uid, 7);
+        assert_eq!(scanner.unspent_records().count(), 2);
+
+        // spending a record removes it from the unspent set
+        let spent = received[0].nullifier.clone();
+        assert!(scanner.is_unspent(&spent));
+        assert!(scanner
+            .scan(&[spent.clone(), Nullifier(Zero::zero())], &[], &[], 10)?
+            .is_empty());
+        assert!(!scanner.is_unspent(&spent));
+        assert_eq!(scanner.unspent_records().count(), 1);
+
+        // records received outside of notes can be tracked directly
+        let genesis = scanner.insert_record(ros[3].clone(), 0)?;
+        assert!(scanner.is_unspent(&genesis.nullifier));
+        assert_eq!(scanner.unspent_records().count(), 2);
+        Ok(())
+    }
+}